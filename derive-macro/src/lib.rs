@@ -127,7 +127,7 @@ fn derive_status_from_named_struct(
         })
         .collect::<Vec<_>>();
 
-    return Ok(quote! {
+    Ok(quote! {
         #ast
 
         impl #ident {
@@ -136,5 +136,5 @@ fn derive_status_from_named_struct(
             )*
         }
     }
-    .into());
+    .into())
 }
@@ -0,0 +1,105 @@
+use thiserror::Error;
+
+pub use crate::unit::SnapshotError;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors of the calculator, also wrapped by the errors of the loaders
+#[derive(Error, Debug)]
+pub enum Error {
+    /// A unit state doesn't fit its unit
+    #[error(transparent)]
+    State(#[from] StateError),
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+    #[error(transparent)]
+    Snapshot(#[from] SnapshotError),
+}
+
+impl Error {
+    /// Id of the unit the error is about, if any
+    pub fn unit_id(&self) -> Option<i64> {
+        match self {
+            Error::State(error) => Some(error.unit_id()),
+            Error::Calculation(CalculationError::NonFinitePower { unit_id, .. }) => Some(*unit_id),
+            Error::Snapshot(_) => None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum StateError {
+    #[error("{field} of unit {unit_id} is not set")]
+    NotSet { unit_id: i64, field: &'static str },
+    #[error("Unit {unit_id} doesn't unlock rarity 6")]
+    Rarity6NotUnlocked { unit_id: i64 },
+    #[error("Rarity {rarity} of unit {unit_id} is out of 1-{max} range")]
+    RarityOutOfRange {
+        unit_id: i64,
+        rarity: i32,
+        max: usize,
+    },
+    #[error("Rank {rank} of unit {unit_id} is out of 1-{max} range")]
+    RankOutOfRange { unit_id: i64, rank: i32, max: usize },
+    #[error("Skill level {skill_level} of unit {unit_id} is higher than its level {level}")]
+    SkillLevelTooHigh {
+        unit_id: i64,
+        skill_level: i32,
+        level: i32,
+    },
+    #[error("Unit {unit_id} has {found} equipment slots at rank {rank}, expected {expected}")]
+    EquipSlotCount {
+        unit_id: i64,
+        rank: i32,
+        found: usize,
+        expected: usize,
+    },
+    #[error("Equipment slot {slot} of unit {unit_id} doesn't match rank {rank}")]
+    EquipSlotMismatch {
+        unit_id: i64,
+        rank: i32,
+        slot: usize,
+    },
+    #[error("Unit {unit_id} has no unique equipment")]
+    NoUniqueEquipment { unit_id: i64 },
+    #[error("Unit {unit_id} has {found} unique equipment slots, expected 1")]
+    UniqueEquipSlotCount { unit_id: i64, found: usize },
+    #[error(
+        "Unique equipment slot of unit {unit_id} doesn't match unique equipment {equipment_id}"
+    )]
+    UniqueEquipSlotMismatch { unit_id: i64, equipment_id: i64 },
+    #[error("Rarity 6 slot {slot} of unit {unit_id} is at level {level}, max is {max}")]
+    Rarity6LevelOutOfRange {
+        unit_id: i64,
+        slot: usize,
+        level: i32,
+        max: usize,
+    },
+    #[error("Story group {story_group_id} of unit {unit_id} does not exist")]
+    StoryGroupNotFound { unit_id: i64, story_group_id: i64 },
+}
+
+impl StateError {
+    pub fn unit_id(&self) -> i64 {
+        match self {
+            StateError::NotSet { unit_id, .. }
+            | StateError::Rarity6NotUnlocked { unit_id }
+            | StateError::RarityOutOfRange { unit_id, .. }
+            | StateError::RankOutOfRange { unit_id, .. }
+            | StateError::SkillLevelTooHigh { unit_id, .. }
+            | StateError::EquipSlotCount { unit_id, .. }
+            | StateError::EquipSlotMismatch { unit_id, .. }
+            | StateError::NoUniqueEquipment { unit_id }
+            | StateError::UniqueEquipSlotCount { unit_id, .. }
+            | StateError::UniqueEquipSlotMismatch { unit_id, .. }
+            | StateError::Rarity6LevelOutOfRange { unit_id, .. }
+            | StateError::StoryGroupNotFound { unit_id, .. } => *unit_id,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CalculationError {
+    #[error("Power of unit {unit_id} is not finite: {power}")]
+    NonFinitePower { unit_id: i64, power: f64 },
+}
@@ -0,0 +1,8 @@
+//! Power calculation of units, without async or database dependencies
+//!
+//! Unit caches are loaded by the `powermagic` crate, or read from a
+//! [`unit::UnitSnapshot`].
+
+pub mod error;
+pub mod model;
+pub mod unit;
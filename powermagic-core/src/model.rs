@@ -0,0 +1,61 @@
+//! Master data rows kept in unit caches
+
+use derive_macro::impl_status;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[impl_status("{}_coefficient")]
+pub struct UnitStatusCoefficient {
+    pub coefficient_id: i64,
+    pub hp_coefficient: f64,
+    pub atk_coefficient: f64,
+    pub magic_str_coefficient: f64,
+    pub def_coefficient: f64,
+    pub magic_def_coefficient: f64,
+    pub physical_critical_coefficient: f64,
+    pub magic_critical_coefficient: f64,
+    pub wave_hp_recovery_coefficient: f64,
+    pub wave_energy_recovery_coefficient: f64,
+    pub dodge_coefficient: f64,
+    pub physical_penetrate_coefficient: f64,
+    pub magic_penetrate_coefficient: f64,
+    pub life_steal_coefficient: f64,
+    pub hp_recovery_rate_coefficient: f64,
+    pub energy_recovery_rate_coefficient: f64,
+    pub energy_reduce_rate_coefficient: f64,
+    pub skill_lv_coefficient: f64,
+    pub exskill_evolution_coefficient: i64,
+    pub overall_coefficient: f64,
+    pub accuracy_coefficient: f64,
+    pub skill1_evolution_coefficient: i64,
+    pub skill1_evolution_slv_coefficient: f64,
+    pub ub_evolution_coefficient: i64,
+    pub ub_evolution_slv_coefficient: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CharaStoryStatus {
+    pub story_id: i64,
+    pub unlock_story_name: String,
+    // pub status_type_1: i64,
+    // pub status_rate_1: i64,
+    // pub status_type_2: i64,
+    // pub status_rate_2: i64,
+    // pub status_type_3: i64,
+    // pub status_rate_3: i64,
+    // pub status_type_4: i64,
+    // pub status_rate_4: i64,
+    // pub status_type_5: i64,
+    // pub status_rate_5: i64,
+    pub status: Vec<(i64, i64)>,
+    // pub chara_id_1: i64,
+    // pub chara_id_2: i64,
+    // pub chara_id_3: i64,
+    // pub chara_id_4: i64,
+    // pub chara_id_5: i64,
+    // pub chara_id_6: i64,
+    // pub chara_id_7: i64,
+    // pub chara_id_8: i64,
+    // pub chara_id_9: i64,
+    // pub chara_id_10: i64,
+    pub chara_id: Vec<i64>,
+}
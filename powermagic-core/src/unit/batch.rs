@@ -0,0 +1,85 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::unit::*;
+
+/// Status of every state, one row per state
+pub fn status_matrix(items: &[(&UnitCache, &UnitState)]) -> DMatrix<f64> {
+    let mut matrix = DMatrix::zeros(items.len(), 17);
+    for (row, (cache, state)) in items.iter().enumerate() {
+        let status = BorrowedUnitCalculator::new(cache, state).param();
+        matrix
+            .row_mut(row)
+            .copy_from(&nalgebra::convert::<_, UnitStatus<f64>>(status).transpose());
+    }
+
+    matrix
+}
+
+/// Power of many states, possibly of different units
+///
+/// States are grouped by status coefficient, the statuses of each group are
+/// stacked with [`status_matrix`] and multiplied by the coefficient in one
+/// product. Each row is summed in the order of [`UnitCalculator::power`], so
+/// the values are the same to the bit.
+pub fn batch_power(items: &[(&UnitCache, &UnitState)]) -> Vec<f64> {
+    // Caches loaded from the same database share their coefficient,
+    // so there is almost always a single group.
+    let mut groups: Vec<(&UnitCache, Vec<usize>)> = vec![];
+    for (index, (cache, _)) in items.iter().enumerate() {
+        let group = groups.iter_mut().find(|(group, _)| {
+            group.status_coefficient_cache == cache.status_coefficient_cache
+                && group.status_coefficient.skill_lv_coefficient
+                    == cache.status_coefficient.skill_lv_coefficient
+        });
+        match group {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((cache, vec![index])),
+        }
+    }
+
+    let mut power = vec![0f64; items.len()];
+    for (cache, indices) in groups {
+        let group: Vec<_> = indices.iter().map(|index| items[*index]).collect();
+        let coefficient = DVector::from_column_slice(cache.status_coefficient_cache.as_slice());
+        // Same as `status * coefficient`, but `tr_mul` sums each row with
+        // `dot` like `power` does, a plain product sums in another order
+        let status_power = status_matrix(&group).transpose().tr_mul(&coefficient);
+
+        let skill_lv_coefficient = cache.status_coefficient.skill_lv_coefficient;
+        for ((cache, state), (index, status_power)) in
+            group.iter().zip(indices.into_iter().zip(status_power.iter()))
+        {
+            let skill_power = BorrowedUnitCalculator::new(cache, state).skill_power();
+            power[index] = skill_power * skill_lv_coefficient + status_power;
+        }
+    }
+
+    power
+}
+
+/// Same as [`batch_power`], split across `workers` threads
+pub fn batch_power_parallel(items: &[(&UnitCache, &UnitState)], workers: usize) -> Vec<f64> {
+    let workers = workers.max(1);
+    if workers == 1 || items.len() < workers {
+        return batch_power(items);
+    }
+
+    let chunk_size = items.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || batch_power(chunk)))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Power of many states of a single unit
+pub fn batch_power_of(cache: &UnitCache, states: &[UnitState]) -> Vec<f64> {
+    let items: Vec<_> = states.iter().map(|state| (cache, state)).collect();
+    batch_power(&items)
+}
@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+use crate::unit::*;
+
+/// Power split into the parts it's made of
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerBreakdown {
+    /// Status from rarity and promotion
+    pub base: UnitStatus<i64>,
+    /// Status from rank, unique and rarity 6 equipments
+    pub equipment: UnitStatus<i64>,
+    /// Status from watched stories
+    pub story: UnitStatus<i64>,
+    /// Total status
+    pub status: UnitStatus<i64>,
+    /// Power of each status
+    pub status_power: UnitStatus<f64>,
+    /// Skill levels weighted by their coefficients
+    pub skill_power: f64,
+    /// Power of skills, `skill_power` times `skill_lv_coefficient`
+    pub skill: f64,
+    pub power: f64,
+}
+
+impl BorrowedUnitCalculator<'_> {
+    pub fn breakdown(&self) -> PowerBreakdown {
+        let status = self.param();
+        let skill_power = self.skill_power();
+
+        PowerBreakdown {
+            base: self.base_param(),
+            equipment: self.equip_param(),
+            story: self.story_param(),
+            status,
+            status_power: nalgebra::convert::<_, UnitStatus<f64>>(status)
+                .component_mul(&self.cache.status_coefficient_cache),
+            skill_power,
+            skill: skill_power * self.cache.status_coefficient.skill_lv_coefficient,
+            power: self.power(),
+        }
+    }
+}
+
+impl UnitCalculator<'_> {
+    pub fn breakdown(&self) -> PowerBreakdown {
+        BorrowedUnitCalculator::new(&self.cache, &self.state).breakdown()
+    }
+}
+
+impl MemorizedUnitCalculator<'_> {
+    /// Not memorized
+    pub fn breakdown(&self) -> PowerBreakdown {
+        self.calculator.breakdown()
+    }
+}
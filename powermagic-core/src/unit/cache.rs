@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::utils::*;
+use crate::model;
+use crate::unit::*;
+
+// TODO: conversion unit
+
+/// Cached data for faster power calculation
+#[derive(Debug, Clone)]
+pub struct UnitCache {
+    /// Id
+    pub unit_id: i64,
+    /// Skill
+    pub skill: UnitSkill,
+    /// Star
+    pub rarity: Vec<UnitRarityCache>,
+    /// Rank promotions
+    pub promotion: Vec<UnitPromotionCache>,
+    /// Union burst
+    pub unique_equip: Option<UniqueEquipmentCache>,
+    /// Unlock rarity 6 slot
+    pub unlock_rarity_6: Option<[Vec<UnlockRarity6Cache>; 3]>,
+    /// Stories
+    pub story: HashMap<i64, Arc<StoryData>>,
+    pub status_coefficient: model::UnitStatusCoefficient,
+    pub status_coefficient_cache: UnitStatus<f64>,
+}
+
+impl UnitCache {
+    /// Compute power with another coefficient, unit data is kept
+    pub fn set_status_coefficient(&mut self, status_coefficient: model::UnitStatusCoefficient) {
+        self.status_coefficient_cache = status_coefficient.status_coefficient();
+        self.status_coefficient = status_coefficient;
+    }
+
+    /// Copy of the cache computing power with another coefficient
+    ///
+    /// Equipments and stories are shared with this cache.
+    pub fn with_status_coefficient(
+        &self,
+        status_coefficient: model::UnitStatusCoefficient,
+    ) -> UnitCache {
+        let mut cache = self.clone();
+        cache.set_status_coefficient(status_coefficient);
+        cache
+    }
+
+    pub fn base_param(&self, rank: i32, level: i32, rarity: i32) -> UnitStatus<i64> {
+        let rarity = &self.rarity[rarity as usize - 1];
+        let rank_up = rank_up_bonus(rank);
+        let mut param = rarity.status;
+        param += rarity.growth * (level as f64);
+        param += rarity.growth.component_mul(&rank_up);
+
+        let promotion = &self.promotion[rank as usize - 1];
+        if let Some(status) = promotion.status {
+            param += status;
+        }
+        if let Some(bonus) = promotion.bonus {
+            param += bonus;
+        }
+
+        param.map(|x| x.cy_round())
+    }
+
+    pub fn equip_param(&self, rank: i32, equip: &[(bool, i32)]) -> UnitStatus<f64> {
+        let promotion = &self.promotion[rank as usize - 1];
+        let mut param = UnitStatus::zeros();
+        promotion.equipments.iter().zip(equip.iter()).for_each(
+            |(equipment, (equipped, enhance_level))| {
+                if !(*equipped) {
+                    return;
+                }
+
+                if equipment.is_none() {
+                    return;
+                }
+
+                param += equipment.as_ref().unwrap().param(*enhance_level);
+            },
+        );
+
+        param
+    }
+
+    pub fn unique_equip_param(&self, enhancement_level: i32) -> UnitStatus<f64> {
+        self.unique_equip.as_ref().unwrap().param(enhancement_level)
+    }
+
+    pub fn unlock_rarity_6_param(&self, level: [i32; 3]) -> UnitStatus<i64> {
+        let mut param = UnitStatus::zeros();
+
+        self.unlock_rarity_6
+            .as_ref()
+            .unwrap()
+            .iter()
+            .zip(level)
+            .filter(|(_, l)| *l > 0)
+            .map(|(r, l)| &r[l as usize - 1])
+            .for_each(|r| {
+                param += r.status;
+            });
+
+        param
+    }
+
+    pub fn story_param(&self, watched_bonus: HashMap<i64, i64>) -> UnitStatus<i64> {
+        let mut param = UnitStatus::zeros();
+        for (group_id, stories) in self.story.iter() {
+            let watched = watched_bonus.get(group_id).unwrap_or(&0);
+            stories.param_append(*watched as usize, &mut param);
+        }
+
+        param
+    }
+
+    pub fn skill_level(&self, level: i32, rarity: i32, is_unique_equipped: bool) -> f64 {
+        skill_level(
+            &self.skill,
+            level,
+            &self.status_coefficient,
+            rarity,
+            rarity >= 6 || is_unique_equipped,
+        )
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnlockRarity6Cache {
+    pub status: UnitStatus<i64>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EquipmentCache {
+    pub id: i64,
+    pub data: UnitStatus<f64>,
+    pub enhance_rate: UnitStatus<f64>,
+    pub max_enhance_level: i32,
+}
+
+impl EquipmentCache {
+    /// Added to the enhancement level before multiplying the enhance rate
+    pub const ENHANCE_LV_OFFSET: i32 = 0;
+
+    pub fn param(&self, enhancement_level: i32) -> UnitStatus<f64> {
+        self.data
+            + (self.enhance_rate * (enhancement_level + Self::ENHANCE_LV_OFFSET) as f64)
+                .map(|x| x.ceil())
+    }
+}
+
+impl Slot for EquipmentCache {
+    fn slot(&self) -> EquipSlot {
+        EquipSlot::Unequipped {
+            id: self.id,
+            max_enhancement_level: Some(self.max_enhance_level),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UniqueEquipmentCache {
+    pub id: i64,
+    pub status: UnitStatus<f64>,
+    pub enhance_rate: UnitStatus<f64>,
+    pub max_enhancement_level: i32,
+}
+
+impl UniqueEquipmentCache {
+    /// Added to the enhancement level before multiplying the enhance rate
+    pub const ENHANCE_LV_OFFSET: i32 = -1;
+
+    pub fn param(&self, enhancement_level: i32) -> UnitStatus<f64> {
+        self.status
+            + (self.enhance_rate * (enhancement_level + Self::ENHANCE_LV_OFFSET) as f64)
+                .map(|x| x.ceil())
+    }
+}
+
+impl Slot for UniqueEquipmentCache {
+    fn slot(&self) -> EquipSlot {
+        EquipSlot::Unequipped {
+            id: self.id,
+            max_enhancement_level: Some(self.max_enhancement_level),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnitPromotionCache {
+    pub equipments: Vec<Option<Arc<EquipmentCache>>>,
+    pub status: Option<UnitStatus<f64>>,
+    pub bonus: Option<UnitStatus<f64>>,
+}
+
+impl UnitPromotionCache {
+    pub fn equipments_to_slots(&self) -> Vec<EquipSlot> {
+        self.equipments
+            .iter()
+            .map(|e| e.as_ref().map_or(EquipSlot::None, |e| e.slot()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnitRarityCache {
+    pub status: UnitStatus<f64>,
+    pub growth: UnitStatus<f64>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct StoryData(pub Vec<model::CharaStoryStatus>);
+
+impl StoryData {
+    /// Group story rows sorted by `story_id` into story groups
+    pub fn group(rows: Vec<model::CharaStoryStatus>) -> HashMap<i64, StoryData> {
+        let mut story_status: HashMap<i64, StoryData> = HashMap::new();
+
+        for story_bonus_item in rows.into_iter() {
+            let story_id = story_bonus_item.story_id;
+            let story_group_id = story_id / 1000;
+            let entry = story_status.entry(story_group_id).or_default();
+            entry.0.push(story_bonus_item);
+        }
+
+        story_status
+    }
+
+    pub fn param_append(&self, bonus_stories: usize, param: &mut UnitStatus<i64>) {
+        self.0
+            .iter()
+            .take(bonus_stories)
+            .flat_map(|status| &status.status)
+            .for_each(|(index, value)| {
+                param[*index as usize - 1] += value;
+            });
+    }
+
+    pub fn param(&self, bonus_stories: usize) -> UnitStatus<i64> {
+        let mut param = UnitStatus::<i64>::zeros();
+        self.0
+            .iter()
+            .take(bonus_stories)
+            .flat_map(|status| &status.status)
+            .for_each(|(index, value)| {
+                param[*index as usize - 1] += value;
+            });
+
+        param
+    }
+
+    pub fn param_all(&self) -> UnitStatus<i64> {
+        let mut param = UnitStatus::<i64>::zeros();
+        self.0
+            .iter()
+            .flat_map(|status| &status.status)
+            .for_each(|(index, value)| {
+                param[*index as usize - 1] += value;
+            });
+
+        param
+    }
+
+    pub fn story_group(&self) -> StoryGroup {
+        StoryGroup {
+            story_group_id: self.0[0].story_id / 1000,
+            total: self.0.len(),
+            watched: 0,
+        }
+    }
+}
@@ -1,28 +1,106 @@
 use std::cell::RefCell;
+use std::ops::Deref;
+use std::sync::Arc;
 
+use crate::error::{CalculationError, Error};
 use crate::unit::*;
 
+/// Cache used by a calculator, either borrowed or shared
+#[derive(Debug, Clone)]
+pub enum UnitCacheRef<'a> {
+    Borrowed(&'a UnitCache),
+    Shared(Arc<UnitCache>),
+}
+
+impl Deref for UnitCacheRef<'_> {
+    type Target = UnitCache;
+
+    fn deref(&self) -> &UnitCache {
+        match self {
+            UnitCacheRef::Borrowed(cache) => cache,
+            UnitCacheRef::Shared(cache) => cache,
+        }
+    }
+}
+
 /// Calculate unit power
+#[derive(Clone)]
 pub struct UnitCalculator<'a> {
-    pub(crate) cache: &'a UnitCache,
+    pub(crate) cache: UnitCacheRef<'a>,
     pub(crate) state: UnitState,
 }
 
+/// Calculator over a borrowed state, without any memo
+///
+/// Cheap to create, useful to evaluate many states that are already built.
+#[derive(Debug, Clone, Copy)]
+pub struct BorrowedUnitCalculator<'a> {
+    pub cache: &'a UnitCache,
+    pub state: &'a UnitState,
+}
+
+impl<'a> BorrowedUnitCalculator<'a> {
+    pub fn new(cache: &'a UnitCache, state: &'a UnitState) -> Self {
+        Self { cache, state }
+    }
+}
+
+/// Calculator that owns a shared handle to its cache
+///
+/// Does not borrow anything, so it can be stored in long-lived
+/// structures or moved to another thread.
+pub type OwnedUnitCalculator = UnitCalculator<'static>;
+
+/// Memorized calculator that owns a shared handle to its cache
+pub type OwnedMemorizedUnitCalculator = MemorizedUnitCalculator<'static>;
+
 pub struct MemorizedUnitCalculator<'a> {
     pub(crate) calculator: UnitCalculator<'a>,
     memo: RefCell<UnitMemo>,
     need_update: RefCell<UnitCalculatorNeedUpdate>,
 }
 
+/// Saved state and memo of a [`MemorizedUnitCalculator`]
+#[derive(Debug, Clone)]
+pub struct UnitCalculatorSnapshot {
+    pub state: UnitState,
+    pub memo: UnitMemo,
+    pub need_update: UnitCalculatorNeedUpdate,
+}
+
 /// Constructor
+impl UnitCalculator<'static> {
+    pub fn from_arc(cache: Arc<UnitCache>) -> Self {
+        let state = cache.unit_state();
+        Self {
+            cache: UnitCacheRef::Shared(cache),
+            state,
+        }
+    }
+}
+
 impl<'a> UnitCalculator<'a> {
     pub fn new(cache: &'a UnitCache) -> Self {
         Self {
-            cache,
+            cache: UnitCacheRef::Borrowed(cache),
             state: cache.unit_state(),
         }
     }
 
+    pub fn cache(&self) -> &UnitCache {
+        &self.cache
+    }
+
+    pub fn state(&self) -> &UnitState {
+        &self.state
+    }
+
+    /// Start from `state` instead of the default state of the cache
+    pub fn with_state(mut self, state: UnitState) -> Self {
+        self.state = state;
+        self
+    }
+
     pub fn memorized(self) -> MemorizedUnitCalculator<'a> {
         MemorizedUnitCalculator {
             calculator: self,
@@ -32,6 +110,31 @@ impl<'a> UnitCalculator<'a> {
     }
 }
 
+impl<'a> MemorizedUnitCalculator<'a> {
+    pub fn calculator(&self) -> &UnitCalculator<'a> {
+        &self.calculator
+    }
+
+    pub fn state(&self) -> &UnitState {
+        &self.calculator.state
+    }
+
+    pub fn snapshot(&self) -> UnitCalculatorSnapshot {
+        UnitCalculatorSnapshot {
+            state: self.calculator.state.clone(),
+            memo: self.memo.borrow().clone(),
+            need_update: self.need_update.borrow().clone(),
+        }
+    }
+
+    /// Restore a snapshot taken from a calculator of the same unit
+    pub fn restore(&mut self, snapshot: UnitCalculatorSnapshot) {
+        self.calculator.state = snapshot.state;
+        *self.memo.get_mut() = snapshot.memo;
+        *self.need_update.get_mut() = snapshot.need_update;
+    }
+}
+
 pub trait StatusSetter
 where
     Self: std::marker::Sized,
@@ -71,52 +174,117 @@ where
     }
 }
 
+/// In-place version of [`StatusSetter`]
+///
+/// Every type implementing this trait also gets [`StatusSetter`],
+/// so only this one needs to be implemented.
+pub trait StatusSetterMut {
+    fn set_level_mut(&mut self, level: i32) -> &mut Self;
+    fn set_skill_level_mut(&mut self, level: i32) -> &mut Self;
+    fn wear_unlock_rarity_6_equipment_mut(&mut self) -> &mut Self;
+    fn set_rarity_mut(&mut self, rarity: i32) -> &mut Self;
+    fn set_promotion_mut(&mut self, promotion: i32) -> &mut Self;
+    fn wear_all_equipments_0_mut(&mut self) -> &mut Self;
+    fn wear_all_equipments_mut(&mut self, level: i32) -> &mut Self;
+    fn unequip_all_equipments_mut(&mut self) -> &mut Self;
+    fn wear_unique_equipment_mut(&mut self, level: i32) -> &mut Self;
+    fn watch_all_stories_mut(&mut self) -> &mut Self;
+    fn watch_story_mut(&mut self, story_group_id: i64, watched_count: usize) -> &mut Self;
+    fn set_equipment_mut(&mut self, slot_id: usize, slot: EquipSlot) -> &mut Self;
+    fn set_equipments_mut(&mut self, equipments: Vec<EquipSlot>) -> &mut Self;
+
+    fn watch_stories_mut(&mut self, stories: &[(i64, usize)]) -> &mut Self {
+        for (story_group_id, watched_count) in stories {
+            self.watch_story_mut(*story_group_id, *watched_count);
+        }
+
+        self
+    }
+
+    fn set_star_mut(&mut self, star: i32) -> &mut Self {
+        self.set_rarity_mut(star)
+    }
+
+    fn set_rank_mut(&mut self, rank: i32) -> &mut Self {
+        self.set_promotion_mut(rank)
+    }
+
+    fn set_all_level_mut(&mut self, level: i32) -> &mut Self {
+        self.set_level_mut(level).set_skill_level_mut(level)
+    }
+}
+
+macro_rules! consuming_setter {
+    ($fn:ident => $fn_mut:ident($( $arg:ident : $type:ty),*)) => {
+        fn $fn(mut self $(, $arg: $type)*) -> Self {
+            self.$fn_mut($($arg),*);
+            self
+        }
+    };
+}
+
+impl<T: StatusSetterMut> StatusSetter for T {
+    consuming_setter!(set_level => set_level_mut(level: i32));
+    consuming_setter!(set_skill_level => set_skill_level_mut(level: i32));
+    consuming_setter!(wear_unlock_rarity_6_equipment => wear_unlock_rarity_6_equipment_mut());
+    consuming_setter!(set_rarity => set_rarity_mut(rarity: i32));
+    consuming_setter!(set_promotion => set_promotion_mut(promotion: i32));
+    consuming_setter!(wear_all_equipments_0 => wear_all_equipments_0_mut());
+    consuming_setter!(wear_all_equipments => wear_all_equipments_mut(level: i32));
+    consuming_setter!(unequip_all_equipments => unequip_all_equipments_mut());
+    consuming_setter!(wear_unique_equipment => wear_unique_equipment_mut(level: i32));
+    consuming_setter!(watch_all_stories => watch_all_stories_mut());
+    consuming_setter!(watch_story => watch_story_mut(story_id: i64, watched_count: usize));
+    consuming_setter!(set_equipment => set_equipment_mut(slot_id: usize, slot: EquipSlot));
+    consuming_setter!(set_equipments => set_equipments_mut(equipments: Vec<EquipSlot>));
+}
+
 macro_rules! memorized_setter {
     ($fn:ident($( $arg:ident : $type:ty),*): $on:ident) => {
-        fn $fn(mut self $(, $arg: $type)*) -> Self {
-            self.need_update.borrow_mut().$on();
-            self.calculator = self.calculator.$fn($($arg),*);
+        fn $fn(&mut self $(, $arg: $type)*) -> &mut Self {
+            self.need_update.get_mut().$on();
+            self.calculator.$fn($($arg),*);
             self
         }
     };
 }
 
-impl StatusSetter for MemorizedUnitCalculator<'_> {
-    memorized_setter!(set_level(level: i32): on_level_change);
-    memorized_setter!(set_skill_level(level: i32): on_skill_change);
-    memorized_setter!(wear_unlock_rarity_6_equipment(): on_rarity_6_change);
-    memorized_setter!(set_rarity(rarity: i32): on_rarity_change);
-    memorized_setter!(set_promotion(promotion: i32): on_promotion_change);
-    memorized_setter!(wear_all_equipments_0(): on_rank_equip_change);
-    memorized_setter!(wear_all_equipments(level: i32): on_rank_equip_change);
-    memorized_setter!(unequip_all_equipments(): on_rank_equip_change);
-    memorized_setter!(wear_unique_equipment(level: i32): on_unique_change);
-    memorized_setter!(watch_all_stories(): on_story_change);
-    memorized_setter!(watch_story(story_id: i64, watched_count: usize): on_story_change);
-    memorized_setter!(set_equipment(slot_id: usize, slot: EquipSlot): on_rank_equip_change);
-    memorized_setter!(set_equipments(equipments: Vec<EquipSlot>): on_rank_equip_change);
+impl StatusSetterMut for MemorizedUnitCalculator<'_> {
+    memorized_setter!(set_level_mut(level: i32): on_level_change);
+    memorized_setter!(set_skill_level_mut(level: i32): on_skill_change);
+    memorized_setter!(wear_unlock_rarity_6_equipment_mut(): on_rarity_6_change);
+    memorized_setter!(set_rarity_mut(rarity: i32): on_rarity_change);
+    memorized_setter!(set_promotion_mut(promotion: i32): on_promotion_change);
+    memorized_setter!(wear_all_equipments_0_mut(): on_rank_equip_change);
+    memorized_setter!(wear_all_equipments_mut(level: i32): on_rank_equip_change);
+    memorized_setter!(unequip_all_equipments_mut(): on_rank_equip_change);
+    memorized_setter!(wear_unique_equipment_mut(level: i32): on_unique_change);
+    memorized_setter!(watch_all_stories_mut(): on_story_change);
+    memorized_setter!(watch_story_mut(story_id: i64, watched_count: usize): on_story_change);
+    memorized_setter!(set_equipment_mut(slot_id: usize, slot: EquipSlot): on_rank_equip_change);
+    memorized_setter!(set_equipments_mut(equipments: Vec<EquipSlot>): on_rank_equip_change);
 }
 
 /// Setters
-impl StatusSetter for UnitCalculator<'_> {
-    fn set_level(mut self, level: i32) -> Self {
+impl StatusSetterMut for UnitCalculator<'_> {
+    fn set_level_mut(&mut self, level: i32) -> &mut Self {
         self.state.level = level;
 
         self
     }
 
-    fn set_skill_level(mut self, level: i32) -> Self {
+    fn set_skill_level_mut(&mut self, level: i32) -> &mut Self {
         assert!(level <= self.state.level);
         self.state.skill.set_all_level(level);
 
         self
     }
 
-    fn wear_unlock_rarity_6_equipment(mut self) -> Self {
+    fn wear_unlock_rarity_6_equipment_mut(&mut self) -> &mut Self {
         assert!(self.state.level != 6);
         assert!(self.state.unlock_rarity_6_slot.is_some());
 
-        let mut slots = self.state.unlock_rarity_6_slot.as_mut().unwrap();
+        let slots = self.state.unlock_rarity_6_slot.as_mut().unwrap();
         slots.slot_1_level = 0;
         slots.slot_2_level = 0;
         slots.slot_3_level = 0;
@@ -124,20 +292,20 @@ impl StatusSetter for UnitCalculator<'_> {
         self
     }
 
-    fn set_rarity(mut self, rarity: i32) -> Self {
+    fn set_rarity_mut(&mut self, rarity: i32) -> &mut Self {
         self.state.rarity = rarity;
 
         self
     }
 
-    fn set_promotion(mut self, promotion: i32) -> Self {
+    fn set_promotion_mut(&mut self, promotion: i32) -> &mut Self {
         self.state.promotion = promotion;
         self.state.equip_slot = self.cache.promotion[promotion as usize - 1].equipments_to_slots();
 
         self
     }
 
-    fn wear_all_equipments_0(mut self) -> Self {
+    fn wear_all_equipments_0_mut(&mut self) -> &mut Self {
         self.state.equip_slot.iter_mut().for_each(|slot| {
             slot.equip_0();
         });
@@ -145,7 +313,7 @@ impl StatusSetter for UnitCalculator<'_> {
         self
     }
 
-    fn wear_all_equipments(mut self, level: i32) -> Self {
+    fn wear_all_equipments_mut(&mut self, level: i32) -> &mut Self {
         self.state.equip_slot.iter_mut().for_each(|slot| {
             slot.equip(level);
         });
@@ -153,7 +321,7 @@ impl StatusSetter for UnitCalculator<'_> {
         self
     }
 
-    fn wear_unique_equipment(mut self, level: i32) -> Self {
+    fn wear_unique_equipment_mut(&mut self, level: i32) -> &mut Self {
         assert!(self.cache.unique_equip.is_some());
 
         self.state.unique_equip_slot.iter_mut().for_each(|slot| {
@@ -163,7 +331,7 @@ impl StatusSetter for UnitCalculator<'_> {
         self
     }
 
-    fn unequip_all_equipments(mut self) -> Self {
+    fn unequip_all_equipments_mut(&mut self) -> &mut Self {
         self.state.equip_slot.iter_mut().for_each(|slot| {
             slot.unequip();
         });
@@ -171,7 +339,7 @@ impl StatusSetter for UnitCalculator<'_> {
         self
     }
 
-    fn watch_all_stories(mut self) -> Self {
+    fn watch_all_stories_mut(&mut self) -> &mut Self {
         self.state.story.iter_mut().for_each(|(_, group)| {
             group.watched = group.total;
         });
@@ -179,20 +347,20 @@ impl StatusSetter for UnitCalculator<'_> {
         self
     }
 
-    fn watch_story(mut self, story_id: i64, watched_count: usize) -> Self {
+    fn watch_story_mut(&mut self, story_id: i64, watched_count: usize) -> &mut Self {
         let group = self.state.story.get_mut(&story_id).unwrap();
         group.watched = watched_count;
 
         self
     }
 
-    fn set_equipment(mut self, slot_id: usize, slot: EquipSlot) -> Self {
+    fn set_equipment_mut(&mut self, slot_id: usize, slot: EquipSlot) -> &mut Self {
         self.state.equip_slot[slot_id] = slot;
 
         self
     }
 
-    fn set_equipments(mut self, equipments: Vec<EquipSlot>) -> Self {
+    fn set_equipments_mut(&mut self, equipments: Vec<EquipSlot>) -> &mut Self {
         self.state.equip_slot = equipments;
 
         self
@@ -211,6 +379,7 @@ macro_rules! memorized_getter_ng {
     )*) => {
         impl UnitCalculator<'_> {
             $(
+                #[allow(unused_variables)]
                 pub fn $fn(&$self $($($(,$arg: $type)*)?)?) -> $ret {
                     $(let $cache = &$self.cache;
                     $(let $state = &$self.state;)?)?
@@ -220,8 +389,21 @@ macro_rules! memorized_getter_ng {
             )*
         }
 
+        impl BorrowedUnitCalculator<'_> {
+            $(
+                #[allow(unused_variables)]
+                pub fn $fn(&$self $($($(,$arg: $type)*)?)?) -> $ret {
+                    $(let $cache = $self.cache;
+                    $(let $state = $self.state;)?)?
+
+                    $block
+                }
+            )*
+        }
+
         impl MemorizedUnitCalculator<'_> {
             $(
+                #[allow(unused_variables)]
                 pub fn $fn(&$self $($($(,$arg: $type)*)?)?) -> $ret {
                     $(let $cache = &$self.calculator.cache;
                     $(let $state = &$self.calculator.state;)?)?
@@ -388,7 +570,48 @@ memorized_getter_ng! {
     }
 }
 
-impl UnitCalculator<'_> {}
+/// Validate the state, then compute the power with `power`
+pub(super) fn checked_power(
+    cache: &UnitCache,
+    state: &UnitState,
+    power: impl FnOnce() -> f64,
+) -> Result<f64, Error> {
+    state.validate(cache)?;
+
+    let power = power();
+    if !power.is_finite() {
+        return Err(CalculationError::NonFinitePower {
+            unit_id: cache.unit_id,
+            power,
+        }
+        .into());
+    }
+
+    Ok(power)
+}
+
+impl UnitCalculator<'_> {
+    /// [`Self::power`], with an error instead of a panic on an invalid state
+    pub fn try_power(&self) -> Result<f64, Error> {
+        checked_power(&self.cache, &self.state, || self.power())
+    }
+}
+
+impl MemorizedUnitCalculator<'_> {
+    /// [`Self::power`], with an error instead of a panic on an invalid state
+    pub fn try_power(&self) -> Result<f64, Error> {
+        checked_power(&self.calculator.cache, &self.calculator.state, || {
+            self.power()
+        })
+    }
+}
+
+impl BorrowedUnitCalculator<'_> {
+    /// [`Self::power`], with an error instead of a panic on an invalid state
+    pub fn try_power(&self) -> Result<f64, Error> {
+        checked_power(self.cache, self.state, || self.power())
+    }
+}
 
 /// State of a unit that have changed
 #[derive(Debug)]
@@ -426,6 +649,7 @@ impl Default for UnitChangedState {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct UnitMemo {
     pub power: f64,
     pub skill: f64,
@@ -458,6 +682,7 @@ impl Default for UnitMemo {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct UnitCalculatorNeedUpdate {
     pub power: bool,
     pub skill: bool,
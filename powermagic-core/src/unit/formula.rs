@@ -0,0 +1,212 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::calc::checked_power;
+use crate::error::Error;
+use crate::unit::*;
+
+/// Parts of the power formula that changed over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormulaRules {
+    /// EX skills add `exskill_evolution_coefficient`, at any rarity
+    pub ex_skill_evolution: bool,
+    /// Evolved main skills use the `skill1_evolution` coefficients
+    pub main_skill_evolution: bool,
+    /// Union bursts at rarity 6 use the `ub_evolution` coefficients
+    pub union_burst_evolution: bool,
+    /// Rarity 6 slots add status
+    pub rarity_6_status: bool,
+    /// Added to the unique equipment level before multiplying the enhance rate
+    pub unique_equipment_level_offset: i32,
+}
+
+/// Named versions of the power formula, oldest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PowerFormula {
+    /// Skills only count their levels
+    Original,
+    /// EX skill evolution
+    ExSkillEvolution,
+    /// Unique equipments, counted from level 0, and main skill evolution
+    UniqueEquipment,
+    /// Rarity 6 slots
+    Rarity6,
+    /// Union burst evolution coefficients
+    UnionBurstEvolution,
+    /// Unique equipments counted from level 1
+    #[default]
+    Current,
+}
+
+impl PowerFormula {
+    pub const ALL: [PowerFormula; 6] = [
+        PowerFormula::Original,
+        PowerFormula::ExSkillEvolution,
+        PowerFormula::UniqueEquipment,
+        PowerFormula::Rarity6,
+        PowerFormula::UnionBurstEvolution,
+        PowerFormula::Current,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerFormula::Original => "original",
+            PowerFormula::ExSkillEvolution => "ex-skill-evolution",
+            PowerFormula::UniqueEquipment => "unique-equipment",
+            PowerFormula::Rarity6 => "rarity-6",
+            PowerFormula::UnionBurstEvolution => "ub-evolution",
+            PowerFormula::Current => "current",
+        }
+    }
+
+    pub fn rules(&self) -> FormulaRules {
+        let version = Self::ALL
+            .iter()
+            .position(|formula| formula == self)
+            .unwrap();
+        FormulaRules {
+            ex_skill_evolution: version >= 1,
+            main_skill_evolution: version >= 2,
+            rarity_6_status: version >= 3,
+            union_burst_evolution: version >= 4,
+            unique_equipment_level_offset: if version >= 5 {
+                UniqueEquipmentCache::ENHANCE_LV_OFFSET
+            } else {
+                0
+            },
+        }
+    }
+
+    pub fn power(&self, cache: &UnitCache, state: &UnitState) -> f64 {
+        self.rules().power(cache, state)
+    }
+}
+
+impl fmt::Display for PowerFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown power formula {0}")]
+pub struct ParsePowerFormulaError(String);
+
+impl FromStr for PowerFormula {
+    type Err = ParsePowerFormulaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|formula| formula.name() == s)
+            .ok_or_else(|| ParsePowerFormulaError(s.to_string()))
+    }
+}
+
+impl FormulaRules {
+    pub fn skill_power(&self, cache: &UnitCache, state: &UnitState) -> f64 {
+        let coefficient = &cache.status_coefficient;
+        let is_unique_equipped =
+            cache.unique_equip.is_some() && state.unique_equip_slot[0].is_equipped();
+
+        let mut power = 0f64;
+        for skill in &state.skill.union_burst {
+            let is_evolution = self.union_burst_evolution && state.rarity >= 6;
+            power += coefficient.union_burst_power(skill.skill_level, is_evolution);
+        }
+
+        for skill in &state.skill.main_skill {
+            let is_evolution = self.main_skill_evolution
+                && is_unique_equipped
+                && skill.skill_evolution_id.is_some();
+            power += coefficient.main_skill_power(skill.skill_level, is_evolution);
+        }
+
+        for skill in &state.skill.ex_skill {
+            power += skill.skill_level as f64;
+            if self.ex_skill_evolution {
+                power += coefficient.exskill_evolution_coefficient as f64;
+            }
+        }
+
+        for skill in &state.skill.free_skill {
+            power += coefficient.free_skill_power(skill.skill_level);
+        }
+
+        power
+    }
+
+    pub fn unique_equip_param(&self, cache: &UnitCache, state: &UnitState) -> UnitStatus<f64> {
+        let equipment = match &cache.unique_equip {
+            Some(equipment) => equipment,
+            None => return UnitStatus::zeros(),
+        };
+        assert_eq!(state.unique_equip_slot.len(), 1);
+
+        match state.unique_equip_slot[0] {
+            EquipSlot::None => panic!("unique slot is not set"),
+            EquipSlot::Equipped {
+                enhancement_level, ..
+            } => {
+                let level = enhancement_level + self.unique_equipment_level_offset;
+                equipment.status + (equipment.enhance_rate * level as f64).map(|x| x.ceil())
+            }
+            _ => UnitStatus::zeros(),
+        }
+    }
+
+    pub fn param(&self, cache: &UnitCache, state: &UnitState) -> UnitStatus<i64> {
+        let calculator = BorrowedUnitCalculator::new(cache, state);
+
+        let equip = calculator.rank_equip_param() + self.unique_equip_param(cache, state);
+        let mut param =
+            calculator.base_param() + equip.map(|x| x.cy_round::<i64>()) + calculator.story_param();
+        if self.rarity_6_status && cache.unlock_rarity_6.is_some() {
+            param += calculator.rarity_6_param();
+        }
+
+        param
+    }
+
+    pub fn power(&self, cache: &UnitCache, state: &UnitState) -> f64 {
+        self.skill_power(cache, state) * cache.status_coefficient.skill_lv_coefficient
+            + nalgebra::convert::<_, UnitStatus<f64>>(self.param(cache, state))
+                .dot(&cache.status_coefficient_cache)
+    }
+}
+
+impl UnitCalculator<'_> {
+    /// Power under an older version of the formula
+    pub fn power_with(&self, formula: PowerFormula) -> f64 {
+        formula.power(&self.cache, &self.state)
+    }
+
+    /// [`Self::power_with`], with an error instead of a panic on an invalid state
+    pub fn try_power_with(&self, formula: PowerFormula) -> Result<f64, Error> {
+        checked_power(&self.cache, &self.state, || self.power_with(formula))
+    }
+}
+
+impl MemorizedUnitCalculator<'_> {
+    /// Power under an older version of the formula, not memorized
+    pub fn power_with(&self, formula: PowerFormula) -> f64 {
+        self.calculator.power_with(formula)
+    }
+
+    /// [`Self::power_with`], with an error instead of a panic on an invalid state
+    pub fn try_power_with(&self, formula: PowerFormula) -> Result<f64, Error> {
+        self.calculator.try_power_with(formula)
+    }
+}
+
+impl BorrowedUnitCalculator<'_> {
+    /// Power under an older version of the formula
+    pub fn power_with(&self, formula: PowerFormula) -> f64 {
+        formula.power(self.cache, self.state)
+    }
+
+    /// [`Self::power_with`], with an error instead of a panic on an invalid state
+    pub fn try_power_with(&self, formula: PowerFormula) -> Result<f64, Error> {
+        checked_power(self.cache, self.state, || self.power_with(formula))
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+
+use crate::unit::*;
+
+/// A setter call, recorded so it can be replayed
+#[derive(Debug, Clone)]
+pub enum StatusOperation {
+    SetLevel(i32),
+    SetSkillLevel(i32),
+    WearUnlockRarity6Equipment,
+    SetRarity(i32),
+    SetPromotion(i32),
+    WearAllEquipments0,
+    WearAllEquipments(i32),
+    UnequipAllEquipments,
+    WearUniqueEquipment(i32),
+    WatchAllStories,
+    WatchStory(i64, usize),
+    SetEquipment(usize, EquipSlot),
+    SetEquipments(Vec<EquipSlot>),
+    /// Several operations applied as a single step
+    Group(String, Vec<StatusOperation>),
+}
+
+impl StatusOperation {
+    pub fn apply<T: StatusSetterMut>(&self, target: &mut T) {
+        match self {
+            StatusOperation::SetLevel(level) => {
+                target.set_level_mut(*level);
+            }
+            StatusOperation::SetSkillLevel(level) => {
+                target.set_skill_level_mut(*level);
+            }
+            StatusOperation::WearUnlockRarity6Equipment => {
+                target.wear_unlock_rarity_6_equipment_mut();
+            }
+            StatusOperation::SetRarity(rarity) => {
+                target.set_rarity_mut(*rarity);
+            }
+            StatusOperation::SetPromotion(promotion) => {
+                target.set_promotion_mut(*promotion);
+            }
+            StatusOperation::WearAllEquipments0 => {
+                target.wear_all_equipments_0_mut();
+            }
+            StatusOperation::WearAllEquipments(level) => {
+                target.wear_all_equipments_mut(*level);
+            }
+            StatusOperation::UnequipAllEquipments => {
+                target.unequip_all_equipments_mut();
+            }
+            StatusOperation::WearUniqueEquipment(level) => {
+                target.wear_unique_equipment_mut(*level);
+            }
+            StatusOperation::WatchAllStories => {
+                target.watch_all_stories_mut();
+            }
+            StatusOperation::WatchStory(story_group_id, watched_count) => {
+                target.watch_story_mut(*story_group_id, *watched_count);
+            }
+            StatusOperation::SetEquipment(slot_id, slot) => {
+                target.set_equipment_mut(*slot_id, slot.clone());
+            }
+            StatusOperation::SetEquipments(equipments) => {
+                target.set_equipments_mut(equipments.clone());
+            }
+            StatusOperation::Group(_, operations) => {
+                operations
+                    .iter()
+                    .for_each(|operation| operation.apply(target));
+            }
+        }
+    }
+
+    /// Human readable description, using the state before the operation
+    pub fn describe(&self, before: &UnitState) -> String {
+        match self {
+            StatusOperation::SetLevel(level) => format!("level {}→{}", before.level, level),
+            StatusOperation::SetSkillLevel(level) => format!("skill level →{}", level),
+            StatusOperation::WearUnlockRarity6Equipment => "wear rarity 6 equipments".to_string(),
+            StatusOperation::SetRarity(rarity) => format!("rarity {}→{}", before.rarity, rarity),
+            StatusOperation::SetPromotion(promotion) => {
+                format!("rank {}→{}", before.promotion, promotion)
+            }
+            StatusOperation::WearAllEquipments0 => "equip all slots".to_string(),
+            StatusOperation::WearAllEquipments(level) => {
+                format!("equip all slots at level {}", level)
+            }
+            StatusOperation::UnequipAllEquipments => "unequip all slots".to_string(),
+            StatusOperation::WearUniqueEquipment(level) => {
+                format!("unique equipment level {}", level)
+            }
+            StatusOperation::WatchAllStories => "watch all stories".to_string(),
+            StatusOperation::WatchStory(story_group_id, watched_count) => {
+                let watched = before
+                    .story
+                    .get(story_group_id)
+                    .map_or(0, |group| group.watched);
+                format!("story {} {}→{}", story_group_id, watched, watched_count)
+            }
+            StatusOperation::SetEquipment(slot_id, slot) => match slot {
+                EquipSlot::Equipped {
+                    enhancement_level, ..
+                } => format!("equip slot {} at level {}", slot_id + 1, enhancement_level),
+                _ => format!("unequip slot {}", slot_id + 1),
+            },
+            StatusOperation::SetEquipments(_) => "set equipments".to_string(),
+            StatusOperation::Group(label, _) => label.clone(),
+        }
+    }
+}
+
+/// A recorded step and the power around it
+#[derive(Debug, Clone)]
+pub struct HistoryStep {
+    pub description: String,
+    pub power_before: f64,
+    pub power_after: f64,
+    operation: Option<StatusOperation>,
+    before: UnitCalculatorSnapshot,
+    after: UnitCalculatorSnapshot,
+}
+
+impl HistoryStep {
+    /// Difference of the rounded power shown in game
+    pub fn delta(&self) -> i64 {
+        self.power_after.cy_round::<i64>() - self.power_before.cy_round::<i64>()
+    }
+
+    /// Operation of this step, `None` if it restored a snapshot
+    pub fn operation(&self) -> Option<&StatusOperation> {
+        self.operation.as_ref()
+    }
+}
+
+impl fmt::Display for HistoryStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:+}", self.description, self.delta())
+    }
+}
+
+/// Calculator that records every applied setter, for what-if exploration
+///
+/// Each step keeps the snapshot before and after it, so undo and redo
+/// restore the memo as well and never recompute anything.
+pub struct UnitCalculatorHistory<'a> {
+    calculator: MemorizedUnitCalculator<'a>,
+    undo: Vec<HistoryStep>,
+    redo: Vec<HistoryStep>,
+}
+
+impl<'a> UnitCalculatorHistory<'a> {
+    pub fn new(calculator: MemorizedUnitCalculator<'a>) -> Self {
+        Self {
+            calculator,
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+
+    pub fn calculator(&self) -> &MemorizedUnitCalculator<'a> {
+        &self.calculator
+    }
+
+    pub fn into_calculator(self) -> MemorizedUnitCalculator<'a> {
+        self.calculator
+    }
+
+    pub fn power(&self) -> f64 {
+        self.calculator.power()
+    }
+
+    pub fn apply(&mut self, operation: StatusOperation) -> &mut Self {
+        let description = operation.describe(self.calculator.state());
+        let power_before = self.calculator.power();
+        let before = self.calculator.snapshot();
+        operation.apply(&mut self.calculator);
+        self.push_step(description, Some(operation), power_before, before);
+
+        self
+    }
+
+    /// Apply several operations as one step, e.g. `"equip slots 1-3"`
+    pub fn apply_group(&mut self, label: &str, operations: Vec<StatusOperation>) -> &mut Self {
+        self.apply(StatusOperation::Group(label.to_string(), operations))
+    }
+
+    pub fn snapshot(&self) -> UnitCalculatorSnapshot {
+        self.calculator.snapshot()
+    }
+
+    /// Restore a snapshot, recorded as a step so it can be undone
+    pub fn restore(&mut self, snapshot: UnitCalculatorSnapshot) -> &mut Self {
+        let power_before = self.calculator.power();
+        let before = self.calculator.snapshot();
+        self.calculator.restore(snapshot);
+        self.push_step("restore".to_string(), None, power_before, before);
+
+        self
+    }
+
+    fn push_step(
+        &mut self,
+        description: String,
+        operation: Option<StatusOperation>,
+        power_before: f64,
+        before: UnitCalculatorSnapshot,
+    ) {
+        let power_after = self.calculator.power();
+        let after = self.calculator.snapshot();
+
+        self.redo.clear();
+        self.undo.push(HistoryStep {
+            description,
+            power_before,
+            power_after,
+            operation,
+            before,
+            after,
+        });
+    }
+
+    /// Undo the last step, returns `false` if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(step) => {
+                self.calculator.restore(step.before.clone());
+                self.redo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone step, returns `false` if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(step) => {
+                self.calculator.restore(step.after.clone());
+                self.undo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Applied steps, oldest first
+    pub fn steps(&self) -> &[HistoryStep] {
+        &self.undo
+    }
+
+    /// One line per applied step, e.g. `rank 20→21: -1200`
+    pub fn log(&self) -> Vec<String> {
+        self.undo.iter().map(|step| step.to_string()).collect()
+    }
+}
+
+macro_rules! history_setter {
+    ($fn:ident($( $arg:ident : $type:ty),*) => $op:expr) => {
+        fn $fn(&mut self $(, $arg: $type)*) -> &mut Self {
+            self.apply($op)
+        }
+    };
+}
+
+impl StatusSetterMut for UnitCalculatorHistory<'_> {
+    history_setter!(set_level_mut(level: i32) => StatusOperation::SetLevel(level));
+    history_setter!(set_skill_level_mut(level: i32) => StatusOperation::SetSkillLevel(level));
+    history_setter!(wear_unlock_rarity_6_equipment_mut() => StatusOperation::WearUnlockRarity6Equipment);
+    history_setter!(set_rarity_mut(rarity: i32) => StatusOperation::SetRarity(rarity));
+    history_setter!(set_promotion_mut(promotion: i32) => StatusOperation::SetPromotion(promotion));
+    history_setter!(wear_all_equipments_0_mut() => StatusOperation::WearAllEquipments0);
+    history_setter!(wear_all_equipments_mut(level: i32) => StatusOperation::WearAllEquipments(level));
+    history_setter!(unequip_all_equipments_mut() => StatusOperation::UnequipAllEquipments);
+    history_setter!(wear_unique_equipment_mut(level: i32) => StatusOperation::WearUniqueEquipment(level));
+    history_setter!(watch_all_stories_mut() => StatusOperation::WatchAllStories);
+    history_setter!(watch_story_mut(story_id: i64, watched_count: usize) => StatusOperation::WatchStory(story_id, watched_count));
+    history_setter!(set_equipment_mut(slot_id: usize, slot: EquipSlot) => StatusOperation::SetEquipment(slot_id, slot));
+    history_setter!(set_equipments_mut(equipments: Vec<EquipSlot>) => StatusOperation::SetEquipments(equipments));
+}
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use crate::unit::*;
+
+/// Calculator that only reevaluates the parts of the status a change touches
+///
+/// Every equipment slot and story group keeps its own parameter, so changing
+/// one of them only evaluates that one. The integer status is then updated
+/// with the difference of the changed part, which is exact. Floating point
+/// sums are re-added from the cached terms in the same order
+/// [`UnitCalculator`] uses, and the power itself is never updated in place:
+/// the skill term and the 17-term dot product are computed again from the
+/// status after each change, so it stays bit for bit equal to
+/// [`UnitCalculator::power`].
+pub struct IncrementalUnitCalculator<'a> {
+    calculator: UnitCalculator<'a>,
+    /// Parameter of each equipment slot, `None` if nothing is equipped
+    slot: Vec<Option<UnitStatus<f64>>>,
+    /// Parameter of each story group
+    story_group: HashMap<i64, UnitStatus<i64>>,
+    unique: UnitStatus<f64>,
+    rarity_6: UnitStatus<i64>,
+    skill: f64,
+    base: UnitStatus<i64>,
+    equip: UnitStatus<i64>,
+    story: UnitStatus<i64>,
+    status: UnitStatus<i64>,
+    power: f64,
+}
+
+impl<'a> UnitCalculator<'a> {
+    pub fn incremental(self) -> IncrementalUnitCalculator<'a> {
+        let mut calculator = IncrementalUnitCalculator {
+            calculator: self,
+            slot: vec![],
+            story_group: HashMap::new(),
+            unique: UnitStatus::zeros(),
+            rarity_6: UnitStatus::zeros(),
+            skill: 0f64,
+            base: UnitStatus::zeros(),
+            equip: UnitStatus::zeros(),
+            story: UnitStatus::zeros(),
+            status: UnitStatus::zeros(),
+            power: 0f64,
+        };
+        calculator.refresh();
+
+        calculator
+    }
+}
+
+impl<'a> IncrementalUnitCalculator<'a> {
+    pub fn calculator(&self) -> &UnitCalculator<'a> {
+        &self.calculator
+    }
+
+    pub fn into_calculator(self) -> UnitCalculator<'a> {
+        self.calculator
+    }
+
+    pub fn state(&self) -> &UnitState {
+        &self.calculator.state
+    }
+
+    pub fn power(&self) -> f64 {
+        self.power
+    }
+
+    pub fn param(&self) -> UnitStatus<i64> {
+        self.status
+    }
+
+    pub fn skill_power(&self) -> f64 {
+        self.skill
+    }
+
+    pub fn base_param(&self) -> UnitStatus<i64> {
+        self.base
+    }
+
+    pub fn equip_param(&self) -> UnitStatus<i64> {
+        self.equip
+    }
+
+    pub fn story_param(&self) -> UnitStatus<i64> {
+        self.story
+    }
+
+    /// Recompute everything from the state
+    pub fn refresh(&mut self) {
+        self.slot = (0..self.calculator.state.equip_slot.len())
+            .map(|slot_id| self.slot_param(slot_id))
+            .collect();
+        self.story_group = self
+            .calculator
+            .state
+            .story
+            .keys()
+            .map(|story_id| (*story_id, self.story_group_param(*story_id)))
+            .collect();
+        self.unique = self.unique_param();
+        self.rarity_6 = self.rarity_6_param();
+        self.skill = self.calculator.skill_power();
+        self.base = self.calculator.base_param();
+        self.equip = self.sum_equip();
+        self.story = self.story_group.values().sum();
+        self.status = self.base + self.equip + self.story;
+        self.update_power();
+    }
+
+    fn slot_param(&self, slot_id: usize) -> Option<UnitStatus<f64>> {
+        let state = &self.calculator.state;
+        let equipment =
+            &self.calculator.cache.promotion[state.promotion as usize - 1].equipments[slot_id];
+        let slot = &state.equip_slot[slot_id];
+
+        match equipment {
+            None => {
+                assert!(
+                    slot.is_none(),
+                    "equip slot is not empty but promotion is None"
+                );
+                None
+            }
+            Some(equipment) => match slot {
+                EquipSlot::Equipped {
+                    enhancement_level, ..
+                } => Some(equipment.param(*enhancement_level)),
+                _ => None,
+            },
+        }
+    }
+
+    fn story_group_param(&self, story_id: i64) -> UnitStatus<i64> {
+        let group = &self.calculator.state.story[&story_id];
+        self.calculator.cache.story[&story_id].param(group.watched)
+    }
+
+    fn unique_param(&self) -> UnitStatus<f64> {
+        if self.calculator.cache.unique_equip.is_some() {
+            self.calculator.unique_equip_param()
+        } else {
+            UnitStatus::zeros()
+        }
+    }
+
+    fn rarity_6_param(&self) -> UnitStatus<i64> {
+        if self.calculator.cache.unlock_rarity_6.is_some() {
+            self.calculator.rarity_6_param()
+        } else {
+            UnitStatus::zeros()
+        }
+    }
+
+    /// Same order of additions as `UnitCalculator::equip_param`
+    fn sum_equip(&self) -> UnitStatus<i64> {
+        let mut rank = UnitStatus::zeros();
+        self.slot.iter().flatten().for_each(|param| rank += param);
+        if self.calculator.cache.unique_equip.is_some() {
+            rank += self.unique;
+        }
+
+        let mut rank: UnitStatus<i64> = rank.map(|x| x.cy_round());
+
+        if self.calculator.cache.unlock_rarity_6.is_some() {
+            rank += self.rarity_6;
+        }
+
+        rank
+    }
+
+    fn update_base(&mut self) {
+        let base = self.calculator.base_param();
+        self.status += base - self.base;
+        self.base = base;
+    }
+
+    fn update_equip(&mut self) {
+        let equip = self.sum_equip();
+        self.status += equip - self.equip;
+        self.equip = equip;
+    }
+
+    fn update_slot(&mut self, slot_id: usize) {
+        self.slot[slot_id] = self.slot_param(slot_id);
+    }
+
+    fn update_all_slots(&mut self) {
+        self.slot = (0..self.calculator.state.equip_slot.len())
+            .map(|slot_id| self.slot_param(slot_id))
+            .collect();
+    }
+
+    fn update_story_group(&mut self, story_id: i64) {
+        let param = self.story_group_param(story_id);
+        let old = self.story_group.insert(story_id, param).unwrap_or_default();
+        self.story += param - old;
+        self.status += param - old;
+    }
+
+    fn update_skill(&mut self) {
+        self.skill = self.calculator.skill_power();
+    }
+
+    fn update_power(&mut self) {
+        let cache = &self.calculator.cache;
+        self.power = self.skill * cache.status_coefficient.skill_lv_coefficient
+            + nalgebra::convert::<_, UnitStatus<f64>>(self.status)
+                .dot(&cache.status_coefficient_cache);
+    }
+}
+
+impl StatusSetterMut for IncrementalUnitCalculator<'_> {
+    fn set_level_mut(&mut self, level: i32) -> &mut Self {
+        self.calculator.set_level_mut(level);
+        self.update_base();
+        self.update_power();
+
+        self
+    }
+
+    fn set_skill_level_mut(&mut self, level: i32) -> &mut Self {
+        self.calculator.set_skill_level_mut(level);
+        self.update_skill();
+        self.update_power();
+
+        self
+    }
+
+    fn wear_unlock_rarity_6_equipment_mut(&mut self) -> &mut Self {
+        self.calculator.wear_unlock_rarity_6_equipment_mut();
+        self.rarity_6 = self.rarity_6_param();
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+
+    fn set_rarity_mut(&mut self, rarity: i32) -> &mut Self {
+        self.calculator.set_rarity_mut(rarity);
+        self.update_base();
+        self.update_skill();
+        self.update_power();
+
+        self
+    }
+
+    fn set_promotion_mut(&mut self, promotion: i32) -> &mut Self {
+        self.calculator.set_promotion_mut(promotion);
+        self.update_base();
+        self.update_all_slots();
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+
+    fn wear_all_equipments_0_mut(&mut self) -> &mut Self {
+        self.calculator.wear_all_equipments_0_mut();
+        self.update_all_slots();
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+
+    fn wear_all_equipments_mut(&mut self, level: i32) -> &mut Self {
+        self.calculator.wear_all_equipments_mut(level);
+        self.update_all_slots();
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+
+    fn unequip_all_equipments_mut(&mut self) -> &mut Self {
+        self.calculator.unequip_all_equipments_mut();
+        self.update_all_slots();
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+
+    fn wear_unique_equipment_mut(&mut self, level: i32) -> &mut Self {
+        self.calculator.wear_unique_equipment_mut(level);
+        self.unique = self.unique_param();
+        self.update_equip();
+        self.update_skill();
+        self.update_power();
+
+        self
+    }
+
+    fn watch_all_stories_mut(&mut self) -> &mut Self {
+        self.calculator.watch_all_stories_mut();
+        let story_ids: Vec<i64> = self.story_group.keys().copied().collect();
+        story_ids
+            .into_iter()
+            .for_each(|story_id| self.update_story_group(story_id));
+        self.update_power();
+
+        self
+    }
+
+    fn watch_story_mut(&mut self, story_group_id: i64, watched_count: usize) -> &mut Self {
+        self.calculator
+            .watch_story_mut(story_group_id, watched_count);
+        self.update_story_group(story_group_id);
+        self.update_power();
+
+        self
+    }
+
+    fn set_equipment_mut(&mut self, slot_id: usize, slot: EquipSlot) -> &mut Self {
+        self.calculator.set_equipment_mut(slot_id, slot);
+        self.update_slot(slot_id);
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+
+    fn set_equipments_mut(&mut self, equipments: Vec<EquipSlot>) -> &mut Self {
+        self.calculator.set_equipments_mut(equipments);
+        self.update_all_slots();
+        self.update_equip();
+        self.update_power();
+
+        self
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bincode::Options;
+
+use super::cache::{
+    EquipmentCache, StoryData, UniqueEquipmentCache, UnitPromotionCache, UnitRarityCache,
+    UnlockRarity6Cache,
+};
+use crate::model;
+use crate::unit::*;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"PMSNAP\0\0";
+
+/// Encoding of `bincode::serialize`, with a limit on the bytes read so a
+/// corrupted length can't allocate without bound
+fn encoding(limit: u64) -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("IO error {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Encoding error {0}")]
+    Encoding(#[from] bincode::Error),
+    #[error("Not a unit snapshot")]
+    InvalidMagic,
+    #[error("Snapshot format version {0} is not supported, expected {1}")]
+    UnsupportedVersion(u32, u32),
+    #[error("Snapshot is corrupted, {0} is missing")]
+    Corrupted(String),
+}
+
+/// Header of a snapshot, readable without decoding the units
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotHeader {
+    /// Format of the file, written before the header itself
+    #[serde(skip)]
+    pub format_version: u32,
+    /// Version of the master database the units were loaded from
+    pub source_version: String,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub unit_count: usize,
+}
+
+/// Unit caches that can be saved and used without the master database
+///
+/// The file is a magic number, the format version, then the bincode encoded
+/// header and units. Equipments and story groups are stored once and shared
+/// again when loaded.
+#[derive(Debug)]
+pub struct UnitSnapshot {
+    pub header: SnapshotHeader,
+    pub status_coefficient: model::UnitStatusCoefficient,
+    pub units: HashMap<i64, UnitCache>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SnapshotBody {
+    /// The first one is the coefficient of the snapshot
+    coefficients: Vec<model::UnitStatusCoefficient>,
+    equipments: Vec<EquipmentCache>,
+    stories: Vec<StoryData>,
+    units: Vec<StoredUnit>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StoredUnit {
+    unit_id: i64,
+    skill: UnitSkill,
+    rarity: Vec<UnitRarityCache>,
+    promotion: Vec<StoredPromotion>,
+    unique_equip: Option<UniqueEquipmentCache>,
+    unlock_rarity_6: Option<[Vec<UnlockRarity6Cache>; 3]>,
+    /// Story group id and index in `SnapshotBody::stories`
+    story: Vec<(i64, usize)>,
+    /// Index in `SnapshotBody::coefficients`
+    coefficient: usize,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StoredPromotion {
+    /// Equipment ids
+    equipments: Vec<Option<i64>>,
+    status: Option<UnitStatus<f64>>,
+    bonus: Option<UnitStatus<f64>>,
+}
+
+impl UnitSnapshot {
+    pub const FORMAT_VERSION: u32 = 1;
+    /// Largest encoded header
+    pub const MAX_HEADER_SIZE: u64 = 1 << 16;
+    /// Largest encoded units
+    pub const MAX_BODY_SIZE: u64 = 1 << 30;
+
+    pub fn new(
+        source_version: &str,
+        status_coefficient: model::UnitStatusCoefficient,
+        units: HashMap<i64, UnitCache>,
+    ) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        Self {
+            header: SnapshotHeader {
+                format_version: Self::FORMAT_VERSION,
+                source_version: source_version.to_string(),
+                created_at,
+                unit_count: units.len(),
+            },
+            status_coefficient,
+            units,
+        }
+    }
+
+    pub fn unit_cache(&self, unit_id: i64) -> Option<&UnitCache> {
+        self.units.get(&unit_id)
+    }
+
+    pub fn unit_ids(&self) -> Vec<i64> {
+        let mut unit_ids: Vec<i64> = self.units.keys().copied().collect();
+        unit_ids.sort_unstable();
+
+        unit_ids
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), SnapshotError> {
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&Self::FORMAT_VERSION.to_le_bytes())?;
+        encoding(Self::MAX_HEADER_SIZE).serialize_into(&mut writer, &self.header)?;
+        encoding(Self::MAX_BODY_SIZE).serialize_into(&mut writer, &self.body())?;
+
+        Ok(())
+    }
+
+    /// Read only the header, e.g. to check the source version
+    pub fn read_header<R: Read>(mut reader: R) -> Result<SnapshotHeader, SnapshotError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::InvalidMagic);
+        }
+
+        let mut format_version = [0u8; 4];
+        reader.read_exact(&mut format_version)?;
+        let format_version = u32::from_le_bytes(format_version);
+        if format_version != Self::FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(
+                format_version,
+                Self::FORMAT_VERSION,
+            ));
+        }
+
+        let mut header: SnapshotHeader =
+            encoding(Self::MAX_HEADER_SIZE).deserialize_from(&mut reader)?;
+        header.format_version = format_version;
+
+        Ok(header)
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, SnapshotError> {
+        let header = Self::read_header(&mut reader)?;
+        let body: SnapshotBody = encoding(Self::MAX_BODY_SIZE).deserialize_from(&mut reader)?;
+
+        let status_coefficient = body
+            .coefficients
+            .first()
+            .cloned()
+            .ok_or_else(|| SnapshotError::Corrupted("status coefficient".to_string()))?;
+        let equipments: HashMap<i64, Arc<EquipmentCache>> = body
+            .equipments
+            .into_iter()
+            .map(|equipment| (equipment.id, Arc::new(equipment)))
+            .collect();
+        let stories: Vec<Arc<StoryData>> = body.stories.into_iter().map(Arc::new).collect();
+
+        let mut units = HashMap::new();
+        for unit in body.units.into_iter() {
+            let status_coefficient = body
+                .coefficients
+                .get(unit.coefficient)
+                .ok_or_else(|| {
+                    SnapshotError::Corrupted(format!("coefficient of unit {}", unit.unit_id))
+                })?
+                .clone();
+
+            let mut promotion = vec![];
+            for stored in unit.promotion.into_iter() {
+                let mut promotion_equipments = vec![];
+                for equipment_id in stored.equipments.into_iter() {
+                    let equipment = match equipment_id {
+                        Some(equipment_id) => Some(
+                            equipments
+                                .get(&equipment_id)
+                                .ok_or_else(|| {
+                                    SnapshotError::Corrupted(format!("equipment {}", equipment_id))
+                                })?
+                                .clone(),
+                        ),
+                        None => None,
+                    };
+                    promotion_equipments.push(equipment);
+                }
+
+                promotion.push(UnitPromotionCache {
+                    equipments: promotion_equipments,
+                    status: stored.status,
+                    bonus: stored.bonus,
+                });
+            }
+
+            let mut story = HashMap::new();
+            for (story_group_id, index) in unit.story.into_iter() {
+                let story_data = stories.get(index).ok_or_else(|| {
+                    SnapshotError::Corrupted(format!("story group {}", story_group_id))
+                })?;
+                story.insert(story_group_id, story_data.clone());
+            }
+
+            units.insert(
+                unit.unit_id,
+                UnitCache {
+                    unit_id: unit.unit_id,
+                    skill: unit.skill,
+                    rarity: unit.rarity,
+                    promotion,
+                    unique_equip: unit.unique_equip,
+                    unlock_rarity_6: unit.unlock_rarity_6,
+                    story,
+                    status_coefficient_cache: status_coefficient.status_coefficient(),
+                    status_coefficient,
+                },
+            );
+        }
+
+        Ok(Self {
+            header,
+            status_coefficient,
+            units,
+        })
+    }
+
+    /// Flatten the units, storing shared equipments and stories once
+    fn body(&self) -> SnapshotBody {
+        let mut coefficients = vec![self.status_coefficient.clone()];
+        let mut equipments: HashMap<i64, EquipmentCache> = HashMap::new();
+        let mut stories = vec![];
+        let mut story_index: HashMap<*const StoryData, usize> = HashMap::new();
+        let mut units = vec![];
+
+        for unit_id in self.unit_ids() {
+            let cache = &self.units[&unit_id];
+
+            // Compared on every column, caches may hold edited rows under the
+            // id of the database row
+            let coefficient = match coefficients
+                .iter()
+                .position(|coefficient| *coefficient == cache.status_coefficient)
+            {
+                Some(index) => index,
+                None => {
+                    coefficients.push(cache.status_coefficient.clone());
+                    coefficients.len() - 1
+                }
+            };
+
+            let promotion = cache
+                .promotion
+                .iter()
+                .map(|promotion| StoredPromotion {
+                    equipments: promotion
+                        .equipments
+                        .iter()
+                        .map(|equipment| {
+                            equipment.as_ref().map(|equipment| {
+                                equipments
+                                    .entry(equipment.id)
+                                    .or_insert_with(|| equipment.as_ref().clone());
+                                equipment.id
+                            })
+                        })
+                        .collect(),
+                    status: promotion.status,
+                    bonus: promotion.bonus,
+                })
+                .collect();
+
+            let mut story: Vec<(i64, usize)> = cache
+                .story
+                .iter()
+                .map(|(story_group_id, story_data)| {
+                    let index = *story_index
+                        .entry(Arc::as_ptr(story_data))
+                        .or_insert_with(|| {
+                            stories.push(story_data.as_ref().clone());
+                            stories.len() - 1
+                        });
+                    (*story_group_id, index)
+                })
+                .collect();
+            story.sort_unstable();
+
+            units.push(StoredUnit {
+                unit_id,
+                skill: cache.skill.clone(),
+                rarity: cache.rarity.clone(),
+                promotion,
+                unique_equip: cache.unique_equip.clone(),
+                unlock_rarity_6: cache.unlock_rarity_6.clone(),
+                story,
+                coefficient,
+            });
+        }
+
+        let mut equipments: Vec<EquipmentCache> = equipments.into_values().collect();
+        equipments.sort_unstable_by_key(|equipment| equipment.id);
+
+        SnapshotBody {
+            coefficients,
+            equipments,
+            stories,
+            units,
+        }
+    }
+}
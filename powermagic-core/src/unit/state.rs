@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use crate::error::StateError;
+use crate::model;
+use crate::unit::*;
+
+/// State of a unit
+/// All parameters needed to calculate the unit's power
+/// Does not include actual unit data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnitState {
+    /// Unit id
+    pub id: i64,
+    /// Number of stars
+    pub rarity: i32,
+    /// Lv
+    pub level: i32,
+    /// Rank
+    pub promotion: i32,
+    /// Skill,
+    pub skill: UnitSkill,
+    /// Equipment slots
+    pub equip_slot: Vec<EquipSlot>,
+    /// Unique equipment slots
+    pub unique_equip_slot: Vec<EquipSlot>,
+    /// Unlock rarity 6 slot
+    pub unlock_rarity_6_slot: Option<UnlockRarity6Slot>,
+    /// Watched stories
+    pub story: HashMap<i64, StoryGroup>,
+}
+
+impl UnitState {
+    /// Check the state against the unit data before calculating with it
+    ///
+    /// Calculators index the cache with the state, so a state of another
+    /// unit or out of range values would make them panic.
+    pub fn validate(&self, cache: &UnitCache) -> Result<(), StateError> {
+        let unit_id = cache.unit_id;
+
+        if self.rarity < 1 || self.rarity as usize > cache.rarity.len() {
+            return Err(StateError::RarityOutOfRange {
+                unit_id,
+                rarity: self.rarity,
+                max: cache.rarity.len(),
+            });
+        }
+
+        if self.promotion < 1 || self.promotion as usize > cache.promotion.len() {
+            return Err(StateError::RankOutOfRange {
+                unit_id,
+                rank: self.promotion,
+                max: cache.promotion.len(),
+            });
+        }
+
+        let skills = self
+            .skill
+            .union_burst
+            .iter()
+            .chain(&self.skill.main_skill)
+            .chain(&self.skill.ex_skill)
+            .chain(&self.skill.free_skill);
+        for skill in skills {
+            if skill.skill_level > self.level {
+                return Err(StateError::SkillLevelTooHigh {
+                    unit_id,
+                    skill_level: skill.skill_level,
+                    level: self.level,
+                });
+            }
+        }
+
+        let equipments = &cache.promotion[self.promotion as usize - 1].equipments;
+        if self.equip_slot.len() != equipments.len() {
+            return Err(StateError::EquipSlotCount {
+                unit_id,
+                rank: self.promotion,
+                found: self.equip_slot.len(),
+                expected: equipments.len(),
+            });
+        }
+
+        for (slot, (equip, equipment)) in self.equip_slot.iter().zip(equipments).enumerate() {
+            let matches = match (equip, equipment) {
+                (EquipSlot::None, None) => true,
+                (EquipSlot::Unequipped { id, .. }, Some(equipment))
+                | (EquipSlot::Equipped { id, .. }, Some(equipment)) => *id == equipment.id,
+                _ => false,
+            };
+            if !matches {
+                return Err(StateError::EquipSlotMismatch {
+                    unit_id,
+                    rank: self.promotion,
+                    slot: slot + 1,
+                });
+            }
+        }
+
+        // The calculator reads exactly one slot holding the unique equipment
+        match (&cache.unique_equip, self.unique_equip_slot.as_slice()) {
+            (Some(_), []) => {
+                return Err(StateError::NotSet {
+                    unit_id,
+                    field: "unique equip slot",
+                });
+            }
+            (Some(equipment), [slot]) => {
+                let matches = match slot {
+                    EquipSlot::Unequipped { id, .. } | EquipSlot::Equipped { id, .. } => {
+                        *id == equipment.id
+                    }
+                    EquipSlot::None => false,
+                };
+                if !matches {
+                    return Err(StateError::UniqueEquipSlotMismatch {
+                        unit_id,
+                        equipment_id: equipment.id,
+                    });
+                }
+            }
+            (Some(_), slots) => {
+                return Err(StateError::UniqueEquipSlotCount {
+                    unit_id,
+                    found: slots.len(),
+                });
+            }
+            (None, slots) if slots.iter().any(|slot| slot.is_equipped()) => {
+                return Err(StateError::NoUniqueEquipment { unit_id });
+            }
+            (None, _) => {}
+        }
+
+        match (&self.unlock_rarity_6_slot, &cache.unlock_rarity_6) {
+            (Some(slot), Some(levels)) => {
+                for (i, (level, levels)) in slot.slot_level().iter().zip(levels).enumerate() {
+                    if *level < 0 || *level as usize > levels.len() {
+                        return Err(StateError::Rarity6LevelOutOfRange {
+                            unit_id,
+                            slot: i + 1,
+                            level: *level,
+                            max: levels.len(),
+                        });
+                    }
+                }
+            }
+            (None, Some(_)) => {
+                return Err(StateError::NotSet {
+                    unit_id,
+                    field: "unlock rarity 6 slot",
+                });
+            }
+            (Some(_), None) => return Err(StateError::Rarity6NotUnlocked { unit_id }),
+            (None, None) if self.rarity >= 6 => {
+                return Err(StateError::Rarity6NotUnlocked { unit_id })
+            }
+            (None, None) => {}
+        }
+
+        for story_group_id in self.story.keys() {
+            if !cache.story.contains_key(story_group_id) {
+                return Err(StateError::StoryGroupNotFound {
+                    unit_id,
+                    story_group_id: *story_group_id,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl model::UnitStatusCoefficient {
+    fn generic_power_evolution(
+        level: i32,
+        is_evolution: bool,
+        evolution_coefficient: f64,
+        evolution_slv_coefficient: f64,
+    ) -> f64 {
+        if is_evolution {
+            evolution_slv_coefficient * (level as f64) + evolution_coefficient
+        } else {
+            level as f64
+        }
+    }
+
+    pub fn union_burst_power(&self, level: i32, is_evolution: bool) -> f64 {
+        Self::generic_power_evolution(
+            level,
+            is_evolution,
+            self.ub_evolution_coefficient as f64,
+            self.ub_evolution_slv_coefficient,
+        )
+    }
+
+    pub fn main_skill_power(&self, level: i32, is_evolution: bool) -> f64 {
+        Self::generic_power_evolution(
+            level,
+            is_evolution,
+            self.skill1_evolution_coefficient as f64,
+            self.skill1_evolution_slv_coefficient,
+        )
+    }
+
+    pub fn ex_skill_power(&self, level: i32, _is_evolution: bool) -> f64 {
+        level as f64 + self.exskill_evolution_coefficient as f64
+    }
+
+    pub fn free_skill_power(&self, level: i32) -> f64 {
+        level as f64
+    }
+
+    pub fn union_burst_id_power(&self, level: i32, skill_id: i64) -> f64 {
+        self.union_burst_power(level, is_evolution_skill(skill_id))
+    }
+
+    pub fn main_skill_id_power(&self, level: i32, skill_id: i64) -> f64 {
+        self.main_skill_power(level, is_evolution_skill(skill_id))
+    }
+
+    pub fn ex_skill_id_power(&self, level: i32, rarity: i32) -> f64 {
+        self.ex_skill_power(level, level > 0 && rarity >= 5)
+    }
+
+    pub fn free_skill_id_power(&self, level: i32) -> f64 {
+        level as f64
+    }
+
+    pub fn skill_power(
+        &self,
+        skill: &UnitSkill,
+        level: i32,
+        rarity: i32,
+        unique_equipped: bool,
+    ) -> f64 {
+        skill_level(skill, level, self, rarity, unique_equipped)
+    }
+}
+
+impl UnitCache {
+    pub fn unit_state(&self) -> UnitState {
+        UnitState {
+            id: self.unit_id,
+            rarity: 5,
+            level: 1,
+            promotion: 1,
+            skill: self.skill.clone(),
+            equip_slot: self.promotion[0].equipments_to_slots(),
+            unique_equip_slot: self
+                .unique_equip
+                .as_ref()
+                .map_or(vec![], |e| vec![e.slot()]),
+            unlock_rarity_6_slot: self.unlock_rarity_6.as_ref().map(|_e| UnlockRarity6Slot {
+                slot_1_level: 0,
+                slot_2_level: 0,
+                slot_3_level: 0,
+            }),
+            story: self
+                .story
+                .iter()
+                .map(|(k, v)| (*k, v.story_group()))
+                .collect(),
+        }
+    }
+    /// State with the highest rarity and rank, every equipment fully
+    /// enhanced and every story watched, at `level`
+    pub fn max_state(&self, level: i32) -> UnitState {
+        let mut state = self.unit_state();
+        let promotion = &self.promotion[self.promotion.len() - 1];
+
+        state.rarity = self.rarity.len() as i32;
+        state.level = level;
+        state.promotion = self.promotion.len() as i32;
+        state.skill.set_all_level(level);
+        state.equip_slot = promotion.equipments_to_slots();
+        state.equip_slot.iter_mut().for_each(|slot| {
+            slot.equip_full();
+        });
+        state.unique_equip_slot.iter_mut().for_each(|slot| {
+            slot.equip_full();
+        });
+        if let (Some(slot), Some(levels)) = (&mut state.unlock_rarity_6_slot, &self.unlock_rarity_6)
+        {
+            if state.rarity >= 6 {
+                slot.slot_1_level = levels[0].len() as i32;
+                slot.slot_2_level = levels[1].len() as i32;
+                slot.slot_3_level = levels[2].len() as i32;
+            }
+        }
+        state.story.iter_mut().for_each(|(_, group)| {
+            group.watched = group.total;
+        });
+
+        state
+    }
+}
@@ -71,23 +71,8 @@ macro_rules! rankup_bonus {
 
     ($rank:expr) => {
         nalgebra::vector![
-            $rank,
-            $rank,
-            $rank,
-            $rank,
-            $rank,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64,
-            1f64
+            $rank, $rank, $rank, $rank, $rank, 1f64, 1f64, 1f64, 1f64, 1f64, 1f64, 1f64, 1f64,
+            1f64, 1f64, 1f64, 1f64
         ]
     };
 }
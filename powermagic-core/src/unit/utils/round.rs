@@ -2,8 +2,7 @@ pub trait CyRound {
     fn cy_round<T: Round>(self) -> T;
 }
 
-impl CyRound for f64
-{
+impl CyRound for f64 {
     fn cy_round<T: Round>(self) -> T {
         T::round(self)
     }
@@ -0,0 +1,97 @@
+mod common;
+
+use powermagic_core::unit::*;
+
+use common::{status_coefficient, unit_cache, LEVEL};
+
+#[test]
+fn batch_power_matches_power() {
+    let cache = unit_cache();
+    let mut coefficient = status_coefficient();
+    coefficient.coefficient_id = 2;
+    coefficient.hp_coefficient = 0.3;
+    coefficient.skill_lv_coefficient = 7.7;
+    let other = cache.with_status_coefficient(coefficient);
+
+    let mut states = vec![cache.unit_state(), cache.max_state(LEVEL)];
+    for rank in 1..=4 {
+        for level in [1, 37, LEVEL] {
+            let mut calculator = UnitCalculator::new(&cache);
+            calculator
+                .set_all_level_mut(level)
+                .set_rank_mut(rank)
+                .wear_all_equipments_mut(rank - 1)
+                .wear_unique_equipment_mut(level)
+                .watch_story_mut(1001, rank as usize - 1);
+            states.push(calculator.state().clone());
+        }
+    }
+
+    let items: Vec<_> = states
+        .iter()
+        .flat_map(|state| [(&cache, state), (&other, state)])
+        .collect();
+    let expected: Vec<u64> = items
+        .iter()
+        .map(|(cache, state)| BorrowedUnitCalculator::new(cache, state).power().to_bits())
+        .collect();
+
+    let bits = |power: Vec<f64>| power.into_iter().map(f64::to_bits).collect::<Vec<_>>();
+    assert_eq!(bits(batch_power(&items)), expected);
+    assert_eq!(bits(batch_power_parallel(&items, 3)), expected);
+    assert_eq!(
+        bits(batch_power_of(&cache, &states)),
+        expected.iter().step_by(2).copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn batch_power_of_many_units_and_coefficients() {
+    let coefficients: Vec<_> = (1..=4)
+        .map(|coefficient_id| {
+            let mut coefficient = status_coefficient();
+            coefficient.coefficient_id = coefficient_id;
+            coefficient.atk_coefficient *= 1.0 + coefficient_id as f64 / 9.0;
+            coefficient.dodge_coefficient /= coefficient_id as f64;
+            coefficient.skill_lv_coefficient += coefficient_id as f64 * 0.3;
+            coefficient
+        })
+        .collect();
+
+    // Units with the same shape and different statuses, each under every
+    // coefficient
+    let caches: Vec<UnitCache> = (0..60)
+        .map(|unit| {
+            let mut cache = unit_cache();
+            cache.unit_id += unit * 100;
+            for rarity in cache.rarity.iter_mut() {
+                rarity.status *= 1.0 + unit as f64 / 7.0;
+                rarity.growth *= 1.0 + unit as f64 / 13.0;
+            }
+            cache.with_status_coefficient(coefficients[unit as usize % coefficients.len()].clone())
+        })
+        .collect();
+    let states: Vec<UnitState> = caches
+        .iter()
+        .enumerate()
+        .map(|(i, cache)| {
+            let mut calculator = UnitCalculator::new(cache);
+            calculator
+                .set_all_level_mut(1 + i as i32 % LEVEL)
+                .set_rank_mut(1 + i as i32 % 4)
+                .wear_all_equipments_mut(i as i32 % 6)
+                .wear_unique_equipment_mut(i as i32);
+            calculator.state().clone()
+        })
+        .collect();
+
+    let items: Vec<_> = caches.iter().zip(states.iter()).collect();
+    let expected: Vec<u64> = items
+        .iter()
+        .map(|(cache, state)| BorrowedUnitCalculator::new(cache, state).power().to_bits())
+        .collect();
+
+    let bits = |power: Vec<f64>| power.into_iter().map(f64::to_bits).collect::<Vec<_>>();
+    assert_eq!(bits(batch_power(&items)), expected);
+    assert_eq!(bits(batch_power_parallel(&items, 4)), expected);
+}
@@ -0,0 +1,134 @@
+//! Unit cache built by hand, with values that don't round evenly
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use powermagic_core::model;
+use powermagic_core::unit::*;
+
+pub const UNIT_ID: i64 = 100101;
+pub const LEVEL: i32 = 100;
+
+/// Status whose every value is a different non-integer
+pub fn status(seed: usize) -> UnitStatus<f64> {
+    UnitStatus::from_fn(|i, _| ((seed * 31 + i * 17) % 97) as f64 * 1.37 + 0.3)
+}
+
+pub fn status_coefficient() -> model::UnitStatusCoefficient {
+    model::UnitStatusCoefficient {
+        coefficient_id: 1,
+        hp_coefficient: 0.1,
+        atk_coefficient: 4.5,
+        magic_str_coefficient: 4.5,
+        def_coefficient: 4.5,
+        magic_def_coefficient: 4.5,
+        physical_critical_coefficient: 0.5,
+        magic_critical_coefficient: 0.5,
+        wave_hp_recovery_coefficient: 0.1,
+        wave_energy_recovery_coefficient: 0.3,
+        dodge_coefficient: 6.0,
+        physical_penetrate_coefficient: 6.0,
+        magic_penetrate_coefficient: 6.0,
+        life_steal_coefficient: 4.5,
+        hp_recovery_rate_coefficient: 1.0,
+        energy_recovery_rate_coefficient: 1.5,
+        energy_reduce_rate_coefficient: 3.0,
+        skill_lv_coefficient: 10.0,
+        exskill_evolution_coefficient: 200,
+        overall_coefficient: 1.0,
+        accuracy_coefficient: 2.0,
+        skill1_evolution_coefficient: 100,
+        skill1_evolution_slv_coefficient: 1.3,
+        ub_evolution_coefficient: 15,
+        ub_evolution_slv_coefficient: 0.7,
+    }
+}
+
+fn skill(skill_id: i64, skill_evolution_id: Option<i64>) -> Vec<SkillLevelInfo> {
+    vec![SkillLevelInfo {
+        skill_id,
+        skill_evolution_id,
+        skill_level: 1,
+    }]
+}
+
+fn equipment(id: i64) -> Option<Arc<EquipmentCache>> {
+    Some(Arc::new(EquipmentCache {
+        id,
+        data: status(id as usize),
+        enhance_rate: status(id as usize + 1) / 7.0,
+        max_enhance_level: 5,
+    }))
+}
+
+fn story(story_id: i64, count: i64) -> Arc<StoryData> {
+    Arc::new(StoryData(
+        (1..=count)
+            .map(|index| model::CharaStoryStatus {
+                story_id: story_id + index,
+                unlock_story_name: format!("Story {}", index),
+                status: vec![(index, 10 * index), (index + 5, 3)],
+                chara_id: vec![UNIT_ID / 100],
+            })
+            .collect(),
+    ))
+}
+
+/// Unit with 6 rarities, 4 ranks, a unique equipment, rarity 6 slots and
+/// two story groups
+pub fn unit_cache() -> UnitCache {
+    let status_coefficient = status_coefficient();
+
+    UnitCache {
+        unit_id: UNIT_ID,
+        skill: UnitSkill {
+            union_burst: skill(1001001, None),
+            main_skill: skill(1001002, Some(1001012)),
+            ex_skill: skill(1001003, None),
+            free_skill: vec![],
+        },
+        rarity: (1..=6)
+            .map(|rarity| UnitRarityCache {
+                status: status(rarity) * rarity as f64,
+                growth: status(rarity + 10) / 11.0,
+            })
+            .collect(),
+        promotion: (1..=4)
+            .map(|rank| UnitPromotionCache {
+                equipments: (0..6)
+                    .map(|slot| {
+                        if rank == 1 && slot % 2 == 1 {
+                            None
+                        } else {
+                            equipment(100000 + rank * 100 + slot)
+                        }
+                    })
+                    .collect(),
+                status: Some(status(rank as usize + 20) * (rank - 1) as f64 * 3.1),
+                bonus: (rank > 2).then(|| status(rank as usize + 30) / 3.0),
+            })
+            .collect(),
+        unique_equip: Some(UniqueEquipmentCache {
+            id: 130011,
+            status: status(40) * 2.3,
+            enhance_rate: status(41) / 13.0,
+            max_enhancement_level: 140,
+        }),
+        unlock_rarity_6: Some([
+            vec![UnlockRarity6Cache {
+                status: status(50).map(|x| x as i64),
+            }],
+            vec![UnlockRarity6Cache {
+                status: status(51).map(|x| x as i64),
+            }],
+            (52..57)
+                .map(|seed| UnlockRarity6Cache {
+                    status: status(seed).map(|x| x as i64),
+                })
+                .collect(),
+        ]),
+        story: HashMap::from([(1001, story(1001000, 3)), (1002, story(1002000, 2))]),
+        status_coefficient_cache: status_coefficient.status_coefficient(),
+        status_coefficient,
+    }
+}
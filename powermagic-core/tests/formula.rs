@@ -0,0 +1,208 @@
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use powermagic_core::error::{Error, StateError};
+use powermagic_core::unit::*;
+
+use common::{unit_cache, LEVEL, UNIT_ID};
+
+/// States at each rarity, with the unique equipment equipped or not
+fn states(cache: &UnitCache) -> Vec<UnitState> {
+    let mut states = vec![cache.unit_state()];
+    for rarity in 1..=6 {
+        let mut state = cache.max_state(LEVEL);
+        state.rarity = rarity;
+        if rarity < 6 {
+            state.unlock_rarity_6_slot = cache.unit_state().unlock_rarity_6_slot;
+        }
+        states.push(state.clone());
+
+        state.unique_equip_slot = cache.unit_state().unique_equip_slot;
+        states.push(state);
+    }
+
+    states
+}
+
+#[test]
+fn current_formula_is_power() {
+    let cache = unit_cache();
+    for state in states(&cache) {
+        state.validate(&cache).unwrap();
+        let borrowed = BorrowedUnitCalculator::new(&cache, &state);
+        let power = borrowed.power();
+        let calculator = UnitCalculator::new(&cache).with_state(state.clone());
+
+        assert_eq!(PowerFormula::default(), PowerFormula::Current);
+        assert_eq!(
+            PowerFormula::Current.power(&cache, &state).to_bits(),
+            power.to_bits()
+        );
+        assert_eq!(
+            borrowed.power_with(PowerFormula::Current).to_bits(),
+            power.to_bits()
+        );
+        assert_eq!(
+            calculator
+                .try_power_with(PowerFormula::Current)
+                .unwrap()
+                .to_bits(),
+            power.to_bits()
+        );
+        assert_eq!(
+            calculator
+                .memorized()
+                .power_with(PowerFormula::Current)
+                .to_bits(),
+            power.to_bits()
+        );
+        assert_eq!(
+            PowerFormula::Current
+                .rules()
+                .skill_power(&cache, &state)
+                .to_bits(),
+            borrowed.skill_power().to_bits()
+        );
+    }
+}
+
+#[test]
+fn older_formulas_drop_their_parts() {
+    let cache = unit_cache();
+    let state = cache.max_state(LEVEL);
+    let calculator = BorrowedUnitCalculator::new(&cache, &state);
+    let level = LEVEL as f64;
+    // 1.3 and 0.7 per level and 100 and 15 for the evolved main skill and
+    // union burst, 200 for each EX skill
+    let main_skill = 1.3 * level + 100.0;
+    let union_burst = 0.7 * level + 15.0;
+    let expected_skill_power = [
+        3.0 * level,
+        3.0 * level + 200.0,
+        2.0 * level + main_skill + 200.0,
+        2.0 * level + main_skill + 200.0,
+        level + main_skill + union_burst + 200.0,
+        level + main_skill + union_burst + 200.0,
+    ];
+
+    let unique_equip = cache.unique_equip.as_ref().unwrap();
+    let enhancement_level = unique_equip.max_enhancement_level;
+    for (formula, skill_power) in PowerFormula::ALL.into_iter().zip(expected_skill_power) {
+        let rules = formula.rules();
+        assert_eq!(
+            rules.skill_power(&cache, &state).to_bits(),
+            skill_power.to_bits(),
+            "{}",
+            formula
+        );
+
+        // Counted from level 0 until the current formula
+        let unique_equip_level = if formula == PowerFormula::Current {
+            enhancement_level
+        } else {
+            enhancement_level + 1
+        };
+        assert_eq!(
+            rules.unique_equip_param(&cache, &state),
+            unique_equip.param(unique_equip_level),
+            "{}",
+            formula
+        );
+
+        assert_eq!(
+            formula.power(&cache, &state).to_bits(),
+            (skill_power * cache.status_coefficient.skill_lv_coefficient
+                + nalgebra::convert::<_, UnitStatus<f64>>(rules.param(&cache, &state))
+                    .dot(&cache.status_coefficient_cache))
+            .to_bits(),
+            "{}",
+            formula
+        );
+    }
+
+    // Rarity 6 slots are added after rounding
+    let param = |formula: PowerFormula| formula.rules().param(&cache, &state);
+    assert_eq!(
+        param(PowerFormula::Rarity6) - param(PowerFormula::UniqueEquipment),
+        calculator.rarity_6_param()
+    );
+    assert_eq!(
+        param(PowerFormula::ExSkillEvolution),
+        param(PowerFormula::Original)
+    );
+
+    let powers: Vec<f64> = PowerFormula::ALL
+        .iter()
+        .map(|formula| calculator.power_with(*formula))
+        .collect();
+    let skill_lv_coefficient = cache.status_coefficient.skill_lv_coefficient;
+    assert_eq!(powers[1] - powers[0], 200.0 * skill_lv_coefficient);
+    assert_eq!(
+        powers[2] - powers[1],
+        (main_skill - level) * skill_lv_coefficient
+    );
+    assert_eq!(
+        powers[4] - powers[3],
+        (union_burst - level) * skill_lv_coefficient
+    );
+    assert_eq!(powers[5].to_bits(), calculator.power().to_bits());
+}
+
+#[test]
+fn ex_skill_evolution_at_any_rarity() {
+    let cache = unit_cache();
+    for rarity in 1..=6 {
+        let mut state = cache.max_state(LEVEL);
+        state.rarity = rarity;
+
+        let skill_power = |formula: PowerFormula| formula.rules().skill_power(&cache, &state);
+        assert_eq!(
+            skill_power(PowerFormula::ExSkillEvolution) - skill_power(PowerFormula::Original),
+            cache.status_coefficient.exskill_evolution_coefficient as f64,
+            "rarity {}",
+            rarity
+        );
+    }
+}
+
+#[test]
+fn unset_unique_equip_slot_in_every_formula() {
+    let mut cache = unit_cache();
+    let state = UnitState {
+        unique_equip_slot: vec![EquipSlot::None],
+        ..cache.max_state(LEVEL)
+    };
+
+    for formula in PowerFormula::ALL {
+        let calculator = BorrowedUnitCalculator::new(&cache, &state);
+        assert!(catch_unwind(AssertUnwindSafe(|| calculator.power())).is_err());
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| calculator.power_with(formula))).is_err(),
+            "{}",
+            formula
+        );
+        match calculator.try_power_with(formula) {
+            Err(Error::State(StateError::UniqueEquipSlotMismatch {
+                unit_id: UNIT_ID, ..
+            })) => {}
+            result => panic!("{}: {:?}", formula, result),
+        }
+    }
+
+    // Fine for units without a unique equipment
+    cache.unique_equip = None;
+    for formula in PowerFormula::ALL {
+        let calculator = BorrowedUnitCalculator::new(&cache, &state);
+        let power = calculator.try_power_with(formula).unwrap();
+        assert_eq!(power.to_bits(), calculator.power_with(formula).to_bits());
+    }
+    assert_eq!(
+        BorrowedUnitCalculator::new(&cache, &state)
+            .power_with(PowerFormula::Current)
+            .to_bits(),
+        BorrowedUnitCalculator::new(&cache, &state)
+            .power()
+            .to_bits()
+    );
+}
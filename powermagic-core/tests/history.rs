@@ -0,0 +1,159 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use powermagic_core::unit::*;
+
+use common::{unit_cache, LEVEL};
+
+/// States and memos have no `PartialEq`, every field shows in `Debug`
+fn assert_same<T: Debug>(left: &T, right: &T) {
+    assert_eq!(format!("{:?}", left), format!("{:?}", right));
+}
+
+/// Same as [`assert_same`], story groups are compared in order of id
+fn assert_same_state(left: &UnitState, right: &UnitState) {
+    let sorted = |state: &UnitState| {
+        let mut state = state.clone();
+        let story: BTreeMap<i64, String> = state
+            .story
+            .drain()
+            .map(|(id, group)| (id, format!("{:?}", group)))
+            .collect();
+        (state, story)
+    };
+    let (left, left_story) = sorted(left);
+    let (right, right_story) = sorted(right);
+    assert_same(&left, &right);
+    assert_eq!(left_story, right_story);
+}
+
+fn full_power(cache: &UnitCache, state: &UnitState) -> f64 {
+    BorrowedUnitCalculator::new(cache, state).power()
+}
+
+#[test]
+fn undo_then_redo_restores_state_and_power() {
+    let cache = unit_cache();
+    let mut history = UnitCalculatorHistory::new(UnitCalculator::new(&cache).memorized());
+    history
+        .set_level_mut(LEVEL)
+        .set_promotion_mut(3)
+        .wear_all_equipments_mut(5)
+        .watch_story_mut(1001, 2);
+
+    let state = history.calculator().state().clone();
+    let power = history.power();
+    let snapshot = history.snapshot();
+
+    assert!(history.undo());
+    assert!(history.undo());
+    assert_eq!(history.calculator().state().promotion, 3);
+    assert_eq!(
+        history.power().to_bits(),
+        full_power(&cache, history.calculator().state()).to_bits()
+    );
+
+    assert!(history.redo());
+    assert!(history.redo());
+    assert!(!history.redo());
+    assert_same_state(history.calculator().state(), &state);
+    assert_same(&history.snapshot().memo, &snapshot.memo);
+    assert_eq!(history.power().to_bits(), power.to_bits());
+
+    while history.undo() {}
+    assert_same_state(
+        history.calculator().state(),
+        UnitCalculator::new(&cache).state(),
+    );
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn new_step_after_undo_clears_redo() {
+    let cache = unit_cache();
+    let mut history = UnitCalculatorHistory::new(UnitCalculator::new(&cache).memorized());
+    history.set_level_mut(LEVEL).set_rarity_mut(3);
+
+    assert!(history.undo());
+    assert!(history.can_redo());
+
+    history.set_promotion_mut(2);
+    assert!(!history.can_redo());
+    assert!(!history.redo());
+    assert_eq!(history.steps().len(), 2);
+    assert_eq!(history.calculator().state().rarity, 5);
+    assert_eq!(history.calculator().state().promotion, 2);
+}
+
+#[test]
+fn restore_gives_back_state_and_memo() {
+    let cache = unit_cache();
+    let mut history = UnitCalculatorHistory::new(UnitCalculator::new(&cache).memorized());
+    history.set_level_mut(LEVEL).wear_all_equipments_0_mut();
+    history.power();
+    let snapshot = history.snapshot();
+    let power = history.power();
+
+    history
+        .set_promotion_mut(4)
+        .wear_unique_equipment_mut(100)
+        .watch_all_stories_mut();
+    assert_ne!(history.power().to_bits(), power.to_bits());
+
+    history.restore(snapshot.clone());
+    assert_same_state(history.calculator().state(), &snapshot.state);
+    assert_same(&history.snapshot().memo, &snapshot.memo);
+    assert_eq!(history.power().to_bits(), power.to_bits());
+
+    // Restoring is a step of its own
+    let last = history.steps().last().unwrap();
+    assert!(last.operation().is_none());
+    assert_eq!(last.description, "restore");
+    assert!(history.undo());
+    assert_eq!(history.calculator().state().promotion, 4);
+}
+
+#[test]
+fn log_has_the_power_delta_of_each_step() {
+    let cache = unit_cache();
+    let mut history = UnitCalculatorHistory::new(UnitCalculator::new(&cache).memorized());
+    let mut powers = vec![history.power()];
+    let mut calculator = UnitCalculator::new(&cache);
+
+    let operations = [
+        StatusOperation::SetLevel(LEVEL),
+        StatusOperation::SetPromotion(4),
+        StatusOperation::WearAllEquipments(5),
+        StatusOperation::SetRarity(6),
+        StatusOperation::WearUnlockRarity6Equipment,
+        StatusOperation::WatchStory(1002, 1),
+        StatusOperation::SetLevel(LEVEL - 10),
+    ];
+    for operation in operations.iter() {
+        history.apply(operation.clone());
+        operation.apply(&mut calculator);
+        powers.push(full_power(&cache, calculator.state()));
+    }
+
+    let steps = history.steps();
+    assert_eq!(steps.len(), operations.len());
+    for (i, step) in steps.iter().enumerate() {
+        assert_eq!(step.power_before.to_bits(), powers[i].to_bits());
+        assert_eq!(step.power_after.to_bits(), powers[i + 1].to_bits());
+        assert_eq!(
+            step.delta(),
+            powers[i + 1].cy_round::<i64>() - powers[i].cy_round::<i64>()
+        );
+    }
+
+    let log = history.log();
+    assert_eq!(log[0], format!("level 1→{}: {:+}", LEVEL, steps[0].delta()));
+    assert_eq!(log[1], format!("rank 1→4: {:+}", steps[1].delta()));
+    assert_eq!(
+        log[6],
+        format!("level {}→{}: {:+}", LEVEL, LEVEL - 10, steps[6].delta())
+    );
+    assert!(steps[6].delta() < 0);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use powermagic_core::unit::*;
+
+use common::{unit_cache, LEVEL};
+
+/// Walk through neighbouring states, checking every step against a full
+/// computation of the same state
+#[test]
+fn incremental_power_matches_power() {
+    let cache = unit_cache();
+    let mut calculator = UnitCalculator::new(&cache).incremental();
+
+    let check = |calculator: &mut IncrementalUnitCalculator, step: &str| {
+        let state = calculator.state();
+        state.validate(&cache).unwrap();
+        let power = BorrowedUnitCalculator::new(&cache, state).power();
+        assert_eq!(
+            calculator.power().to_bits(),
+            power.to_bits(),
+            "{}: {} != {}",
+            step,
+            calculator.power(),
+            power
+        );
+    };
+
+    check(&mut calculator, "initial");
+    calculator.set_all_level_mut(LEVEL);
+    check(&mut calculator, "level");
+    calculator.wear_all_equipments_0_mut();
+    check(&mut calculator, "equipments at 0");
+    for rank in 2..=4 {
+        calculator.set_rank_mut(rank);
+        check(&mut calculator, "rank up");
+        for level in 1..=5 {
+            calculator.wear_all_equipments_mut(level);
+            check(&mut calculator, "equipments");
+        }
+    }
+    calculator.set_equipment_mut(0, cache.promotion[3].equipments_to_slots()[0].clone());
+    check(&mut calculator, "one equipment removed");
+    for level in [1, 2, 70, 139, 140] {
+        calculator.wear_unique_equipment_mut(level);
+        check(&mut calculator, "unique equipment");
+    }
+    for rarity in [3, 4, 5] {
+        calculator.set_rarity_mut(rarity);
+        check(&mut calculator, "rarity");
+    }
+    calculator.watch_story_mut(1001, 1);
+    check(&mut calculator, "one story");
+    calculator.watch_story_mut(1002, 2);
+    check(&mut calculator, "another story group");
+    calculator.watch_all_stories_mut();
+    check(&mut calculator, "all stories");
+    calculator.watch_story_mut(1001, 0);
+    check(&mut calculator, "stories reset");
+    calculator.unequip_all_equipments_mut();
+    check(&mut calculator, "equipments removed");
+    calculator.set_rank_mut(1);
+    check(&mut calculator, "rank down");
+    calculator.set_all_level_mut(1);
+    check(&mut calculator, "level down");
+}
+
+/// Rarity 6 slots are only set from a state, walk down from the max state
+#[test]
+fn incremental_power_matches_power_at_rarity_6() {
+    let cache = unit_cache();
+
+    for slot_3_level in 0..=5 {
+        let mut state = cache.max_state(LEVEL);
+        state.unlock_rarity_6_slot.as_mut().unwrap().slot_3_level = slot_3_level;
+        let mut calculator = UnitCalculator::new(&cache)
+            .with_state(state)
+            .incremental();
+
+        for rarity in [6, 5] {
+            calculator.set_rarity_mut(rarity);
+            let state = calculator.state();
+            state.validate(&cache).unwrap();
+            let power = BorrowedUnitCalculator::new(&cache, state).power();
+            assert_eq!(calculator.power().to_bits(), power.to_bits());
+        }
+
+        calculator.set_rarity_mut(6).wear_unlock_rarity_6_equipment_mut();
+        let power = BorrowedUnitCalculator::new(&cache, calculator.state()).power();
+        assert_eq!(calculator.power().to_bits(), power.to_bits());
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::collections::HashMap;
+
+use powermagic_core::unit::*;
+
+use common::{status_coefficient, unit_cache, LEVEL, UNIT_ID};
+
+#[test]
+fn snapshot_round_trip() {
+    let cache = unit_cache();
+    let snapshot = UnitSnapshot::new(
+        "10000000",
+        status_coefficient(),
+        HashMap::from([(UNIT_ID, cache.clone())]),
+    );
+
+    let mut bytes = vec![];
+    snapshot.write(&mut bytes).unwrap();
+    let header = UnitSnapshot::read_header(bytes.as_slice()).unwrap();
+    assert_eq!(header.source_version, "10000000");
+    assert_eq!(header.unit_count, 1);
+
+    let read = UnitSnapshot::read(bytes.as_slice()).unwrap();
+    let state = cache.max_state(LEVEL);
+    assert_eq!(
+        BorrowedUnitCalculator::new(read.unit_cache(UNIT_ID).unwrap(), &state)
+            .power()
+            .to_bits(),
+        BorrowedUnitCalculator::new(&cache, &state).power().to_bits()
+    );
+}
+
+/// A corrupted length is rejected before anything is allocated
+#[test]
+fn snapshot_size_limit() {
+    let mut bytes = b"PMSNAP\0\0".to_vec();
+    bytes.extend(UnitSnapshot::FORMAT_VERSION.to_le_bytes());
+    bytes.extend(u64::MAX.to_le_bytes());
+
+    let error = UnitSnapshot::read_header(bytes.as_slice()).unwrap_err();
+    match error {
+        SnapshotError::Encoding(error) => {
+            assert!(matches!(*error, bincode::ErrorKind::SizeLimit), "{}", error)
+        }
+        error => panic!("{}", error),
+    }
+}
+
+/// Coefficients sharing an id but not their values stay apart
+#[test]
+fn snapshot_keeps_edited_coefficients() {
+    let cache = unit_cache();
+    let mut edited = status_coefficient();
+    edited.atk_coefficient *= 2.0;
+    let edited_cache = UnitCache {
+        unit_id: UNIT_ID + 100,
+        ..cache.with_status_coefficient(edited)
+    };
+    let snapshot = UnitSnapshot::new(
+        "10000000",
+        status_coefficient(),
+        HashMap::from([
+            (UNIT_ID, cache.clone()),
+            (UNIT_ID + 100, edited_cache.clone()),
+        ]),
+    );
+
+    let mut bytes = vec![];
+    snapshot.write(&mut bytes).unwrap();
+    let read = UnitSnapshot::read(bytes.as_slice()).unwrap();
+
+    let state = cache.max_state(LEVEL);
+    for original in [&cache, &edited_cache] {
+        let unit_cache = read.unit_cache(original.unit_id).unwrap();
+        assert_eq!(unit_cache.status_coefficient, original.status_coefficient);
+        assert_eq!(
+            BorrowedUnitCalculator::new(unit_cache, &state)
+                .power()
+                .to_bits(),
+            BorrowedUnitCalculator::new(original, &state)
+                .power()
+                .to_bits()
+        );
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use powermagic_core::error::StateError;
+use powermagic_core::unit::*;
+
+use common::{unit_cache, LEVEL, UNIT_ID};
+
+#[test]
+fn unique_equip_slot_must_hold_the_unique_equipment() {
+    let cache = unit_cache();
+    let state = cache.max_state(LEVEL);
+    state.validate(&cache).unwrap();
+
+    let with_slots = |unique_equip_slot: Vec<EquipSlot>| UnitState {
+        unique_equip_slot,
+        ..state.clone()
+    };
+    let other = EquipSlot::Equipped {
+        id: 130021,
+        enhancement_level: 1,
+        max_enhancement_level: None,
+    };
+
+    match with_slots(vec![]).validate(&cache) {
+        Err(StateError::NotSet {
+            unit_id: UNIT_ID, ..
+        }) => {}
+        result => panic!("{:?}", result),
+    }
+    for slots in [vec![EquipSlot::None], vec![other]] {
+        match with_slots(slots).validate(&cache) {
+            Err(StateError::UniqueEquipSlotMismatch {
+                unit_id: UNIT_ID,
+                equipment_id: 130011,
+            }) => {}
+            result => panic!("{:?}", result),
+        }
+    }
+    let slots = [
+        state.unique_equip_slot.clone(),
+        state.unique_equip_slot.clone(),
+    ]
+    .concat();
+    match with_slots(slots).validate(&cache) {
+        Err(StateError::UniqueEquipSlotCount {
+            unit_id: UNIT_ID,
+            found: 2,
+        }) => {}
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn unique_equip_slot_without_unique_equipment() {
+    let mut cache = unit_cache();
+    cache.unique_equip = None;
+    let state = cache.max_state(LEVEL);
+
+    for slots in [vec![], vec![EquipSlot::None]] {
+        UnitState {
+            unique_equip_slot: slots,
+            ..state.clone()
+        }
+        .validate(&cache)
+        .unwrap();
+    }
+}
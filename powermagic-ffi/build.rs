@@ -0,0 +1,25 @@
+use std::env;
+use std::path::PathBuf;
+
+use powermagic_core::unit::STATUS_NAMES;
+
+/// Regenerate `include/powermagic.h`, configured by `cbindgen.toml`
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let mut config = cbindgen::Config::from_root_or_default(&crate_dir);
+    // cbindgen can't evaluate `STATUS_NAMES.len()`, define the count here
+    config.after_includes = Some(format!(
+        "\n// Number of statuses written by `pm_calculator_status`\n#define PM_STATUS_COUNT {}",
+        STATUS_NAMES.len()
+    ));
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate the C header")
+        .write_to_file(crate_dir.join("include/powermagic.h"));
+}
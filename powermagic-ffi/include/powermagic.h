@@ -0,0 +1,128 @@
+#ifndef POWERMAGIC_H
+#define POWERMAGIC_H
+
+/* Generated by cbindgen from src/lib.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Number of statuses written by `pm_calculator_status`
+#define PM_STATUS_COUNT 17
+
+// Result of every function
+typedef enum PmError {
+  PM_ERROR_OK = 0,
+  // A handle or out pointer is null
+  PM_ERROR_NULL_POINTER,
+  // A string isn't UTF-8 or can't be parsed
+  PM_ERROR_INVALID_ARGUMENT,
+  // The database can't be opened or queried
+  PM_ERROR_DATABASE,
+  // The database doesn't have the tables or columns the loaders read
+  PM_ERROR_SCHEMA,
+  // Rows are missing or contradict each other, like an unknown unit
+  PM_ERROR_DATA,
+  // A change doesn't fit the unit
+  PM_ERROR_STATE,
+  PM_ERROR_CALCULATION,
+  // Bug in powermagic, the handle should not be used anymore
+  PM_ERROR_PANIC,
+} PmError;
+
+typedef struct PmCalculator PmCalculator;
+
+typedef struct PmDataManager PmDataManager;
+
+typedef struct PmUnitCache PmUnitCache;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last failed call on this thread, null if none failed
+//
+// The message is owned by the library and valid until the next failed
+// call on this thread.
+const char *pm_last_error(void);
+
+// Name of the status at `index` of `pm_calculator_status`, null if out
+// of range
+const char *pm_status_name(size_t index);
+
+// Open a master database, like `sqlite://powermagic.db`
+//
+// `region` is the server of the database, like `jp`, or null for
+// the default profile.
+enum PmError pm_data_manager_open(const char *connection,
+                                  const char *region,
+                                  struct PmDataManager **out);
+
+// Load every unit at once, later unit caches run no query
+enum PmError pm_data_manager_preload(struct PmDataManager *data_manager);
+
+enum PmError pm_data_manager_unit_id_by_name(const struct PmDataManager *data_manager,
+                                             const char *name,
+                                             int64_t *out);
+
+void pm_data_manager_free(struct PmDataManager *data_manager);
+
+enum PmError pm_unit_cache_load(const struct PmDataManager *data_manager,
+                                int64_t unit_id,
+                                struct PmUnitCache **out);
+
+void pm_unit_cache_free(struct PmUnitCache *cache);
+
+// Calculator at the default state of the unit
+enum PmError pm_calculator_new(const struct PmUnitCache *cache, struct PmCalculator **out);
+
+// Copy of a calculator with the same state
+enum PmError pm_calculator_clone(const struct PmCalculator *calculator, struct PmCalculator **out);
+
+void pm_calculator_free(struct PmCalculator *calculator);
+
+enum PmError pm_calculator_set_level(struct PmCalculator *calculator, int32_t level);
+
+// Set every skill to `level`, which can't be higher than the unit level
+enum PmError pm_calculator_set_skill_level(struct PmCalculator *calculator, int32_t level);
+
+// Set the unit and every skill to `level`
+enum PmError pm_calculator_set_all_level(struct PmCalculator *calculator, int32_t level);
+
+enum PmError pm_calculator_set_rarity(struct PmCalculator *calculator, int32_t rarity);
+
+// Set the rank, equipment slots of the new rank are left empty
+enum PmError pm_calculator_set_rank(struct PmCalculator *calculator, int32_t rank);
+
+// Equip every slot of the rank, enhanced to `level`
+enum PmError pm_calculator_wear_all_equipments(struct PmCalculator *calculator, int32_t level);
+
+// Equip every slot of the rank, not enhanced
+enum PmError pm_calculator_wear_all_equipments_0(struct PmCalculator *calculator);
+
+enum PmError pm_calculator_unequip_all_equipments(struct PmCalculator *calculator);
+
+enum PmError pm_calculator_wear_unique_equipment(struct PmCalculator *calculator, int32_t level);
+
+enum PmError pm_calculator_wear_unlock_rarity_6_equipment(struct PmCalculator *calculator);
+
+enum PmError pm_calculator_watch_all_stories(struct PmCalculator *calculator);
+
+// Watch the first `watched_count` stories of a story group
+enum PmError pm_calculator_watch_story(struct PmCalculator *calculator,
+                                       int64_t story_group_id,
+                                       size_t watched_count);
+
+enum PmError pm_calculator_power(const struct PmCalculator *calculator, double *out);
+
+// Write the total status to `out`, an array of `PM_STATUS_COUNT` values
+// named by `pm_status_name`
+enum PmError pm_calculator_status(const struct PmCalculator *calculator, int64_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* POWERMAGIC_H */
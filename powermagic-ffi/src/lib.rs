@@ -0,0 +1,513 @@
+//! C interface of powermagic, declared in `include/powermagic.h`
+//!
+//! Every function returns a [`PmError`], values are written through out
+//! pointers. On failure, [`pm_last_error`] gives a message.
+//!
+//! ```c
+//! PmDataManager *data_manager;
+//! PmUnitCache *cache;
+//! PmCalculator *calculator;
+//! double power;
+//!
+//! pm_data_manager_open("sqlite://powermagic.db", NULL, &data_manager);
+//! pm_unit_cache_load(data_manager, 100101, &cache);
+//! pm_calculator_new(cache, &calculator);
+//! pm_calculator_set_all_level(calculator, 100);
+//! pm_calculator_power(calculator, &power);
+//!
+//! pm_calculator_free(calculator);
+//! pm_unit_cache_free(cache);
+//! pm_data_manager_free(data_manager);
+//! ```
+//!
+//! # Safety
+//!
+//! Handles must come from the matching `pm_*_new`, `pm_*_open` or
+//! `pm_*_load` function and be freed once with the matching `pm_*_free`.
+//! A handle may be used from any thread, but not from two threads at the
+//! same time. Calculators keep their unit cache alive, so the cache and the
+//! data manager may be freed before them.
+
+// The contract is the same for every function, see the crate doc
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Arc, OnceLock};
+
+use powermagic::blocking::DataManager;
+use powermagic::error::{Error, StateError};
+use powermagic::region::Region;
+use powermagic::unit::*;
+
+/// Number of statuses written by `pm_calculator_status`
+pub const PM_STATUS_COUNT: usize = STATUS_NAMES.len();
+
+/// Result of every function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PmError {
+    Ok = 0,
+    /// A handle or out pointer is null
+    NullPointer,
+    /// A string isn't UTF-8 or can't be parsed
+    InvalidArgument,
+    /// The database can't be opened or queried
+    Database,
+    /// The database doesn't have the tables or columns the loaders read
+    Schema,
+    /// Rows are missing or contradict each other, like an unknown unit
+    Data,
+    /// A change doesn't fit the unit
+    State,
+    Calculation,
+    /// Bug in powermagic, the handle should not be used anymore
+    Panic,
+}
+
+pub struct PmDataManager(DataManager);
+
+pub struct PmUnitCache(Arc<UnitCache>);
+
+pub struct PmCalculator(OwnedUnitCalculator);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(code: PmError, message: impl ToString) -> PmError {
+    let message = CString::new(message.to_string().replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+
+    code
+}
+
+fn fail_with(error: Error) -> PmError {
+    let code = match error {
+        Error::Database(_) | Error::Dump(_) => PmError::Database,
+        Error::Schema(_) => PmError::Schema,
+        Error::Data(_) | Error::Snapshot(_) => PmError::Data,
+        Error::State(_) => PmError::State,
+        Error::Calculation(_) => PmError::Calculation,
+    };
+
+    fail(code, error)
+}
+
+fn fail_state(error: StateError) -> PmError {
+    fail_with(error.into())
+}
+
+/// Run `f`, turning a panic into [`PmError::Panic`] instead of unwinding
+/// into C
+fn guard(f: impl FnOnce() -> Result<(), PmError>) -> PmError {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => PmError::Ok,
+        Ok(Err(code)) => code,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            fail(PmError::Panic, message)
+        }
+    }
+}
+
+unsafe fn handle<'a, T>(pointer: *const T) -> Result<&'a T, PmError> {
+    pointer
+        .as_ref()
+        .ok_or_else(|| fail(PmError::NullPointer, "null handle"))
+}
+
+unsafe fn handle_mut<'a, T>(pointer: *mut T) -> Result<&'a mut T, PmError> {
+    pointer
+        .as_mut()
+        .ok_or_else(|| fail(PmError::NullPointer, "null handle"))
+}
+
+unsafe fn write<T>(out: *mut T, value: T) -> Result<(), PmError> {
+    if out.is_null() {
+        return Err(fail(PmError::NullPointer, "null out pointer"));
+    }
+
+    out.write(value);
+    Ok(())
+}
+
+unsafe fn string<'a>(pointer: *const c_char) -> Result<&'a str, PmError> {
+    if pointer.is_null() {
+        return Err(fail(PmError::NullPointer, "null string"));
+    }
+
+    CStr::from_ptr(pointer)
+        .to_str()
+        .map_err(|e| fail(PmError::InvalidArgument, e))
+}
+
+unsafe fn free<T>(pointer: *mut T) {
+    if !pointer.is_null() {
+        drop(Box::from_raw(pointer));
+    }
+}
+
+/// Message of the last failed call on this thread, null if none failed
+///
+/// The message is owned by the library and valid until the next failed
+/// call on this thread.
+#[no_mangle]
+pub extern "C" fn pm_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Name of the status at `index` of `pm_calculator_status`, null if out
+/// of range
+#[no_mangle]
+pub extern "C" fn pm_status_name(index: usize) -> *const c_char {
+    static NAMES: OnceLock<Vec<CString>> = OnceLock::new();
+    let names = NAMES.get_or_init(|| {
+        STATUS_NAMES
+            .iter()
+            .map(|name| CString::new(*name).unwrap())
+            .collect()
+    });
+
+    names.get(index).map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// Open a master database, like `sqlite://powermagic.db`
+///
+/// `region` is the server of the database, like `jp`, or null for
+/// the default profile.
+#[no_mangle]
+pub unsafe extern "C" fn pm_data_manager_open(
+    connection: *const c_char,
+    region: *const c_char,
+    out: *mut *mut PmDataManager,
+) -> PmError {
+    guard(|| {
+        let connection = string(connection)?;
+        let data_manager = if region.is_null() {
+            DataManager::new(connection)
+        } else {
+            let region: Region = string(region)?
+                .parse()
+                .map_err(|e| fail(PmError::InvalidArgument, e))?;
+            DataManager::new_in_region(connection, region)
+        }
+        .map_err(fail_with)?;
+
+        write(out, Box::into_raw(Box::new(PmDataManager(data_manager))))
+    })
+}
+
+/// Load every unit at once, later unit caches run no query
+#[no_mangle]
+pub unsafe extern "C" fn pm_data_manager_preload(data_manager: *mut PmDataManager) -> PmError {
+    guard(|| handle_mut(data_manager)?.0.preload().map_err(fail_with))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_data_manager_unit_id_by_name(
+    data_manager: *const PmDataManager,
+    name: *const c_char,
+    out: *mut i64,
+) -> PmError {
+    guard(|| {
+        let unit_id = handle(data_manager)?
+            .0
+            .unit_id_by_name(string(name)?)
+            .map_err(fail_with)?;
+
+        write(out, unit_id)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_data_manager_free(data_manager: *mut PmDataManager) {
+    free(data_manager)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_unit_cache_load(
+    data_manager: *const PmDataManager,
+    unit_id: i64,
+    out: *mut *mut PmUnitCache,
+) -> PmError {
+    guard(|| {
+        let cache = handle(data_manager)?
+            .0
+            .shared_unit_cache(unit_id)
+            .map_err(fail_with)?;
+
+        write(out, Box::into_raw(Box::new(PmUnitCache(cache))))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_unit_cache_free(cache: *mut PmUnitCache) {
+    free(cache)
+}
+
+/// Calculator at the default state of the unit
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_new(
+    cache: *const PmUnitCache,
+    out: *mut *mut PmCalculator,
+) -> PmError {
+    guard(|| {
+        let calculator = UnitCalculator::from_arc(handle(cache)?.0.clone());
+
+        write(out, Box::into_raw(Box::new(PmCalculator(calculator))))
+    })
+}
+
+/// Copy of a calculator with the same state
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_clone(
+    calculator: *const PmCalculator,
+    out: *mut *mut PmCalculator,
+) -> PmError {
+    guard(|| {
+        let calculator = handle(calculator)?.0.clone();
+
+        write(out, Box::into_raw(Box::new(PmCalculator(calculator))))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_free(calculator: *mut PmCalculator) {
+    free(calculator)
+}
+
+/// Run `f` on the calculator behind `calculator`
+unsafe fn with_calculator(
+    calculator: *mut PmCalculator,
+    f: impl FnOnce(&mut OwnedUnitCalculator) -> Result<(), PmError>,
+) -> PmError {
+    guard(|| f(&mut handle_mut(calculator)?.0))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_set_level(
+    calculator: *mut PmCalculator,
+    level: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        calculator.set_level_mut(level);
+        Ok(())
+    })
+}
+
+/// Set every skill to `level`, which can't be higher than the unit level
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_set_skill_level(
+    calculator: *mut PmCalculator,
+    level: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        let state = calculator.state();
+        if level > state.level {
+            return Err(fail_state(StateError::SkillLevelTooHigh {
+                unit_id: state.id,
+                skill_level: level,
+                level: state.level,
+            }));
+        }
+
+        calculator.set_skill_level_mut(level);
+        Ok(())
+    })
+}
+
+/// Set the unit and every skill to `level`
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_set_all_level(
+    calculator: *mut PmCalculator,
+    level: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        calculator.set_all_level_mut(level);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_set_rarity(
+    calculator: *mut PmCalculator,
+    rarity: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        let max = calculator.cache().rarity.len();
+        if rarity < 1 || rarity as usize > max {
+            return Err(fail_state(StateError::RarityOutOfRange {
+                unit_id: calculator.state().id,
+                rarity,
+                max,
+            }));
+        }
+
+        calculator.set_rarity_mut(rarity);
+        Ok(())
+    })
+}
+
+/// Set the rank, equipment slots of the new rank are left empty
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_set_rank(
+    calculator: *mut PmCalculator,
+    rank: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        let max = calculator.cache().promotion.len();
+        if rank < 1 || rank as usize > max {
+            return Err(fail_state(StateError::RankOutOfRange {
+                unit_id: calculator.state().id,
+                rank,
+                max,
+            }));
+        }
+
+        calculator.set_rank_mut(rank);
+        Ok(())
+    })
+}
+
+/// Equip every slot of the rank, enhanced to `level`
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_wear_all_equipments(
+    calculator: *mut PmCalculator,
+    level: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        calculator.wear_all_equipments_mut(level);
+        Ok(())
+    })
+}
+
+/// Equip every slot of the rank, not enhanced
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_wear_all_equipments_0(
+    calculator: *mut PmCalculator,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        calculator.wear_all_equipments_0_mut();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_unequip_all_equipments(
+    calculator: *mut PmCalculator,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        calculator.unequip_all_equipments_mut();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_wear_unique_equipment(
+    calculator: *mut PmCalculator,
+    level: i32,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        if calculator.cache().unique_equip.is_none() {
+            return Err(fail_state(StateError::NoUniqueEquipment {
+                unit_id: calculator.state().id,
+            }));
+        }
+
+        calculator.wear_unique_equipment_mut(level);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_wear_unlock_rarity_6_equipment(
+    calculator: *mut PmCalculator,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        if calculator.state().unlock_rarity_6_slot.is_none() {
+            return Err(fail_state(StateError::Rarity6NotUnlocked {
+                unit_id: calculator.state().id,
+            }));
+        }
+
+        calculator.wear_unlock_rarity_6_equipment_mut();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_watch_all_stories(calculator: *mut PmCalculator) -> PmError {
+    with_calculator(calculator, |calculator| {
+        calculator.watch_all_stories_mut();
+        Ok(())
+    })
+}
+
+/// Watch the first `watched_count` stories of a story group
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_watch_story(
+    calculator: *mut PmCalculator,
+    story_group_id: i64,
+    watched_count: usize,
+) -> PmError {
+    with_calculator(calculator, |calculator| {
+        if !calculator.state().story.contains_key(&story_group_id) {
+            return Err(fail_state(StateError::StoryGroupNotFound {
+                unit_id: calculator.state().id,
+                story_group_id,
+            }));
+        }
+
+        calculator.watch_story_mut(story_group_id, watched_count);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_power(
+    calculator: *const PmCalculator,
+    out: *mut f64,
+) -> PmError {
+    guard(|| {
+        let power = handle(calculator)?
+            .0
+            .try_power()
+            .map_err(|e| fail_with(e.into()))?;
+
+        write(out, power)
+    })
+}
+
+/// Write the total status to `out`, an array of `PM_STATUS_COUNT` values
+/// named by `pm_status_name`
+#[no_mangle]
+pub unsafe extern "C" fn pm_calculator_status(
+    calculator: *const PmCalculator,
+    out: *mut i64,
+) -> PmError {
+    guard(|| {
+        let calculator = &handle(calculator)?.0;
+        calculator
+            .state()
+            .validate(calculator.cache())
+            .map_err(fail_state)?;
+        if out.is_null() {
+            return Err(fail(PmError::NullPointer, "null out pointer"));
+        }
+
+        let status = calculator.param();
+        ptr::copy_nonoverlapping(status.as_ptr(), out, PM_STATUS_COUNT);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,240 @@
+#[path = "../../powermagic/tests/common/mod.rs"]
+mod common;
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use powermagic::unit::STATUS_NAMES;
+use powermagic_ffi::*;
+
+use common::{database_file, dump, UNIT_ID};
+
+const LEVEL: i32 = 10;
+
+fn last_error() -> String {
+    let message = pm_last_error();
+    assert!(!message.is_null());
+    unsafe { CStr::from_ptr(message) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+/// Data manager over a database file of the fixture, kept in `dir`
+fn open(dir: &tempfile::TempDir) -> *mut PmDataManager {
+    let path = dir.path().join("master.db");
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(database_file(&path, &dump()));
+
+    let connection = CString::new(format!("sqlite://{}", path.display())).unwrap();
+    let mut data_manager = ptr::null_mut();
+    let code = unsafe { pm_data_manager_open(connection.as_ptr(), ptr::null(), &mut data_manager) };
+    assert_eq!(code, PmError::Ok);
+    assert!(!data_manager.is_null());
+
+    data_manager
+}
+
+#[test]
+fn power_and_status_of_a_loaded_unit() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_manager = open(&dir);
+
+    unsafe {
+        let mut cache = ptr::null_mut();
+        assert_eq!(
+            pm_unit_cache_load(data_manager, UNIT_ID, &mut cache),
+            PmError::Ok
+        );
+        let mut calculator = ptr::null_mut();
+        assert_eq!(pm_calculator_new(cache, &mut calculator), PmError::Ok);
+        // Calculators keep their cache alive
+        pm_unit_cache_free(cache);
+        pm_data_manager_free(data_manager);
+
+        assert_eq!(pm_calculator_set_all_level(calculator, LEVEL), PmError::Ok);
+        assert_eq!(pm_calculator_set_rank(calculator, 2), PmError::Ok);
+        assert_eq!(
+            pm_calculator_wear_all_equipments(calculator, 0),
+            PmError::Ok
+        );
+
+        let mut power = 0f64;
+        assert_eq!(pm_calculator_power(calculator, &mut power), PmError::Ok);
+        assert!(power > 0.0);
+
+        let mut status = [0i64; PM_STATUS_COUNT];
+        assert_eq!(
+            pm_calculator_status(calculator, status.as_mut_ptr()),
+            PmError::Ok
+        );
+        // hp of rarity 1 and more from its growth and the rank 2 status
+        assert!(status[0] > 100, "{:?}", status);
+        assert_eq!(PM_STATUS_COUNT, STATUS_NAMES.len());
+        for (index, name) in STATUS_NAMES.iter().enumerate() {
+            assert_eq!(
+                CStr::from_ptr(pm_status_name(index)).to_str().unwrap(),
+                *name
+            );
+        }
+        assert!(pm_status_name(PM_STATUS_COUNT).is_null());
+
+        // A clone starts from the same state and changes on its own
+        let mut clone = ptr::null_mut();
+        assert_eq!(pm_calculator_clone(calculator, &mut clone), PmError::Ok);
+        assert_eq!(pm_calculator_set_rarity(clone, 1), PmError::Ok);
+        let mut clone_power = 0f64;
+        assert_eq!(pm_calculator_power(clone, &mut clone_power), PmError::Ok);
+        assert!(clone_power < power);
+        let mut same_power = 0f64;
+        assert_eq!(
+            pm_calculator_power(calculator, &mut same_power),
+            PmError::Ok
+        );
+        assert_eq!(same_power.to_bits(), power.to_bits());
+
+        pm_calculator_free(clone);
+        pm_calculator_free(calculator);
+    }
+}
+
+#[test]
+fn null_handles_and_out_pointers() {
+    unsafe {
+        let mut power = 0f64;
+        assert_eq!(
+            pm_calculator_power(ptr::null(), &mut power),
+            PmError::NullPointer
+        );
+        assert_eq!(last_error(), "null handle");
+        assert_eq!(
+            pm_calculator_set_level(ptr::null_mut(), LEVEL),
+            PmError::NullPointer
+        );
+        assert_eq!(
+            pm_calculator_status(ptr::null(), ptr::null_mut()),
+            PmError::NullPointer
+        );
+        let mut calculator = ptr::null_mut();
+        assert_eq!(
+            pm_calculator_new(ptr::null(), &mut calculator),
+            PmError::NullPointer
+        );
+        assert!(calculator.is_null());
+        let mut cache = ptr::null_mut();
+        assert_eq!(
+            pm_unit_cache_load(ptr::null(), UNIT_ID, &mut cache),
+            PmError::NullPointer
+        );
+        assert!(cache.is_null());
+        assert_eq!(
+            pm_data_manager_open(ptr::null(), ptr::null(), ptr::null_mut()),
+            PmError::NullPointer
+        );
+        assert_eq!(last_error(), "null string");
+
+        let dir = tempfile::tempdir().unwrap();
+        let data_manager = open(&dir);
+        assert_eq!(
+            pm_unit_cache_load(data_manager, UNIT_ID, ptr::null_mut()),
+            PmError::NullPointer
+        );
+        assert_eq!(last_error(), "null out pointer");
+
+        // Freeing null does nothing
+        pm_calculator_free(ptr::null_mut());
+        pm_unit_cache_free(ptr::null_mut());
+        pm_data_manager_free(data_manager);
+        pm_data_manager_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn bad_states_and_arguments() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_manager = open(&dir);
+
+    unsafe {
+        let mut cache = ptr::null_mut();
+        assert_eq!(
+            pm_unit_cache_load(data_manager, 999999, &mut cache),
+            PmError::Data
+        );
+        assert_eq!(last_error(), "Unit 999999 does not exist");
+
+        let region = CString::new("mars").unwrap();
+        let connection = CString::new("sqlite::memory:").unwrap();
+        let mut other = ptr::null_mut();
+        assert_eq!(
+            pm_data_manager_open(connection.as_ptr(), region.as_ptr(), &mut other),
+            PmError::InvalidArgument
+        );
+        assert!(other.is_null());
+
+        assert_eq!(
+            pm_unit_cache_load(data_manager, UNIT_ID, &mut cache),
+            PmError::Ok
+        );
+        let mut calculator = ptr::null_mut();
+        assert_eq!(pm_calculator_new(cache, &mut calculator), PmError::Ok);
+
+        let failures: [(&dyn Fn() -> PmError, &str); 4] = [
+            (
+                &|| pm_calculator_set_rarity(calculator, 6),
+                "Rarity 6 of unit 100101 is out of 1-5 range",
+            ),
+            (
+                &|| pm_calculator_set_rank(calculator, 0),
+                "Rank 0 of unit 100101 is out of 1-2 range",
+            ),
+            (
+                &|| pm_calculator_set_skill_level(calculator, 2),
+                "Skill level 2 of unit 100101 is higher than its level 1",
+            ),
+            (
+                &|| pm_calculator_watch_story(calculator, 9999, 1),
+                "Story group 9999 of unit 100101 does not exist",
+            ),
+        ];
+        for (change, message) in failures {
+            assert_eq!(change(), PmError::State, "{}", message);
+            assert_eq!(last_error(), message);
+        }
+        assert_eq!(
+            pm_calculator_wear_unique_equipment(calculator, 1),
+            PmError::State
+        );
+        assert!(
+            last_error().contains("unique equipment"),
+            "{}",
+            last_error()
+        );
+
+        // Invalid states only show when calculating
+        assert_eq!(pm_calculator_set_all_level(calculator, LEVEL), PmError::Ok);
+        assert_eq!(pm_calculator_set_rank(calculator, 2), PmError::Ok);
+        assert_eq!(pm_calculator_set_level(calculator, LEVEL - 1), PmError::Ok);
+        let mut power = 0f64;
+        assert_eq!(pm_calculator_power(calculator, &mut power), PmError::State);
+        assert_eq!(
+            last_error(),
+            format!(
+                "Skill level {} of unit {} is higher than its level {}",
+                LEVEL,
+                UNIT_ID,
+                LEVEL - 1
+            )
+        );
+        let mut status = [0i64; PM_STATUS_COUNT];
+        assert_eq!(
+            pm_calculator_status(calculator, status.as_mut_ptr()),
+            PmError::State
+        );
+        assert_eq!(status, [0; PM_STATUS_COUNT]);
+
+        pm_calculator_free(calculator);
+        pm_unit_cache_free(cache);
+        pm_data_manager_free(data_manager);
+    }
+}
@@ -0,0 +1,455 @@
+//! Python bindings of powermagic
+//!
+//! ```python
+//! import powermagic_py as pm
+//!
+//! data_manager = pm.DataManager("sqlite://powermagic.db")
+//! calculator = data_manager.unit_cache(100101).calculator()
+//! calculator.set_rank(10).set_all_level(100).wear_all_equipments(5)
+//! print(calculator.power(), calculator.status())
+//! ```
+//!
+//! Build the wheel with `maturin build` in this directory, status arrays
+//! need numpy installed. Run the tests with `maturin develop` then `pytest`.
+
+use std::sync::Arc;
+
+use numpy::{Element, PyArray1};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use powermagic::blocking;
+use powermagic::error::{Error, StateError};
+use powermagic::region::Region;
+use powermagic::unit::*;
+
+create_exception!(
+    powermagic_py,
+    PowermagicError,
+    PyException,
+    "Error of the powermagic library"
+);
+create_exception!(
+    powermagic_py,
+    InvalidStateError,
+    PyValueError,
+    "Unit state that doesn't fit its unit"
+);
+
+fn to_py_error(error: Error) -> PyErr {
+    match error {
+        Error::State(error) => state_error(error),
+        error => PowermagicError::new_err(error.to_string()),
+    }
+}
+
+fn state_error(error: StateError) -> PyErr {
+    InvalidStateError::new_err(error.to_string())
+}
+
+/// Status as a dict keyed by status name, or a numpy array in the order
+/// of `STATUS_NAMES`
+fn status_object<'py, T>(
+    py: Python<'py>,
+    status: &UnitStatus<T>,
+    array: bool,
+) -> PyResult<Bound<'py, PyAny>>
+where
+    T: Element + IntoPyObject<'py> + Copy + nalgebra::Scalar,
+{
+    if array {
+        return Ok(PyArray1::from_slice(py, status.as_slice()).into_any());
+    }
+
+    let dict = PyDict::new(py);
+    for (name, value) in STATUS_NAMES.iter().zip(status.iter()) {
+        dict.set_item(*name, *value)?;
+    }
+
+    Ok(dict.into_any())
+}
+
+/// Master database opened for reading unit caches
+#[pyclass(name = "DataManager")]
+struct PyDataManager {
+    inner: blocking::DataManager,
+}
+
+#[pymethods]
+impl PyDataManager {
+    #[new]
+    #[pyo3(signature = (connection, region = None))]
+    fn new(py: Python<'_>, connection: &str, region: Option<&str>) -> PyResult<Self> {
+        let region = region
+            .map(|region| region.parse::<Region>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        let inner = py
+            .detach(|| match region {
+                Some(region) => blocking::DataManager::new_in_region(connection, region),
+                None => blocking::DataManager::new(connection),
+            })
+            .map_err(to_py_error)?;
+
+        Ok(Self { inner })
+    }
+
+    /// Load every unit at once, later unit caches run no query
+    fn preload(&mut self, py: Python<'_>) -> PyResult<()> {
+        let inner = &mut self.inner;
+        py.detach(|| inner.preload()).map_err(to_py_error)
+    }
+
+    #[getter]
+    fn region(&self) -> Option<String> {
+        self.inner.region().map(|region| region.to_string())
+    }
+
+    fn unit_ids(&self, py: Python<'_>) -> PyResult<Vec<i64>> {
+        py.detach(|| self.inner.unit_ids()).map_err(to_py_error)
+    }
+
+    fn unit_id_by_name(&self, py: Python<'_>, name: &str) -> PyResult<i64> {
+        py.detach(|| self.inner.unit_id_by_name(name))
+            .map_err(to_py_error)
+    }
+
+    /// `(unit_id, unit_name)` of units named `name`, written the way of the
+    /// region
+    fn find_units(&self, py: Python<'_>, name: &str) -> PyResult<Vec<(i64, String)>> {
+        let units = py
+            .detach(|| self.inner.find_units_by_name(name))
+            .map_err(to_py_error)?;
+
+        Ok(units
+            .into_iter()
+            .map(|unit| (unit.unit_id, unit.unit_name))
+            .collect())
+    }
+
+    fn unit_cache(&self, py: Python<'_>, unit_id: i64) -> PyResult<PyUnitCache> {
+        let cache = py
+            .detach(|| self.inner.shared_unit_cache(unit_id))
+            .map_err(to_py_error)?;
+
+        Ok(PyUnitCache { cache })
+    }
+
+    fn __repr__(&self) -> String {
+        match self.inner.region() {
+            Some(region) => format!("DataManager(region={})", region),
+            None => "DataManager()".to_string(),
+        }
+    }
+}
+
+/// Data of a unit needed to compute its power
+#[pyclass(name = "UnitCache", frozen)]
+struct PyUnitCache {
+    cache: Arc<UnitCache>,
+}
+
+#[pymethods]
+impl PyUnitCache {
+    #[getter]
+    fn unit_id(&self) -> i64 {
+        self.cache.unit_id
+    }
+
+    #[getter]
+    fn max_rarity(&self) -> usize {
+        self.cache.rarity.len()
+    }
+
+    #[getter]
+    fn max_rank(&self) -> usize {
+        self.cache.promotion.len()
+    }
+
+    #[getter]
+    fn has_unique_equipment(&self) -> bool {
+        self.cache.unique_equip.is_some()
+    }
+
+    #[getter]
+    fn has_rarity_6(&self) -> bool {
+        self.cache.unlock_rarity_6.is_some()
+    }
+
+    #[getter]
+    fn story_group_ids(&self) -> Vec<i64> {
+        let mut ids: Vec<i64> = self.cache.story.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Calculator at the default state of the unit
+    fn calculator(&self) -> PyUnitCalculator {
+        PyUnitCalculator {
+            calculator: UnitCalculator::from_arc(self.cache.clone()),
+        }
+    }
+
+    /// Calculator at the highest rarity and rank, every equipment fully
+    /// enhanced and every story watched
+    fn max_calculator(&self, level: i32) -> PyUnitCalculator {
+        let state = self.cache.max_state(level);
+        PyUnitCalculator {
+            calculator: UnitCalculator::from_arc(self.cache.clone()).with_state(state),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("UnitCache(unit_id={})", self.cache.unit_id)
+    }
+}
+
+/// Power calculator of a unit
+///
+/// Setters change the calculator in place and return it, so they can be
+/// chained. Changes that don't fit the unit raise `InvalidStateError`, a
+/// `ValueError`.
+#[pyclass(name = "UnitCalculator")]
+struct PyUnitCalculator {
+    calculator: OwnedUnitCalculator,
+}
+
+impl PyUnitCalculator {
+    fn validate(&self) -> PyResult<()> {
+        self.calculator
+            .state()
+            .validate(self.calculator.cache())
+            .map_err(state_error)
+    }
+}
+
+type SelfRef<'py> = PyRefMut<'py, PyUnitCalculator>;
+
+#[pymethods]
+impl PyUnitCalculator {
+    #[getter]
+    fn unit_id(&self) -> i64 {
+        self.calculator.cache().unit_id
+    }
+
+    #[getter]
+    fn rarity(&self) -> i32 {
+        self.calculator.state().rarity
+    }
+
+    #[getter]
+    fn level(&self) -> i32 {
+        self.calculator.state().level
+    }
+
+    #[getter]
+    fn rank(&self) -> i32 {
+        self.calculator.state().promotion
+    }
+
+    fn set_level(mut slf: SelfRef<'_>, level: i32) -> SelfRef<'_> {
+        slf.calculator.set_level_mut(level);
+        slf
+    }
+
+    fn set_skill_level(mut slf: SelfRef<'_>, level: i32) -> PyResult<SelfRef<'_>> {
+        let state = slf.calculator.state();
+        if level > state.level {
+            return Err(state_error(StateError::SkillLevelTooHigh {
+                unit_id: slf.unit_id(),
+                skill_level: level,
+                level: state.level,
+            }));
+        }
+
+        slf.calculator.set_skill_level_mut(level);
+        Ok(slf)
+    }
+
+    fn set_all_level(mut slf: SelfRef<'_>, level: i32) -> SelfRef<'_> {
+        slf.calculator.set_all_level_mut(level);
+        slf
+    }
+
+    fn set_rarity(mut slf: SelfRef<'_>, rarity: i32) -> PyResult<SelfRef<'_>> {
+        let max = slf.calculator.cache().rarity.len();
+        if rarity < 1 || rarity as usize > max {
+            return Err(state_error(StateError::RarityOutOfRange {
+                unit_id: slf.unit_id(),
+                rarity,
+                max,
+            }));
+        }
+
+        slf.calculator.set_rarity_mut(rarity);
+        Ok(slf)
+    }
+
+    fn set_star(slf: SelfRef<'_>, star: i32) -> PyResult<SelfRef<'_>> {
+        Self::set_rarity(slf, star)
+    }
+
+    fn set_rank(mut slf: SelfRef<'_>, rank: i32) -> PyResult<SelfRef<'_>> {
+        let max = slf.calculator.cache().promotion.len();
+        if rank < 1 || rank as usize > max {
+            return Err(state_error(StateError::RankOutOfRange {
+                unit_id: slf.unit_id(),
+                rank,
+                max,
+            }));
+        }
+
+        slf.calculator.set_rank_mut(rank);
+        Ok(slf)
+    }
+
+    fn set_promotion(slf: SelfRef<'_>, promotion: i32) -> PyResult<SelfRef<'_>> {
+        Self::set_rank(slf, promotion)
+    }
+
+    fn wear_all_equipments(mut slf: SelfRef<'_>, level: i32) -> SelfRef<'_> {
+        slf.calculator.wear_all_equipments_mut(level);
+        slf
+    }
+
+    fn wear_all_equipments_0(mut slf: SelfRef<'_>) -> SelfRef<'_> {
+        slf.calculator.wear_all_equipments_0_mut();
+        slf
+    }
+
+    fn unequip_all_equipments(mut slf: SelfRef<'_>) -> SelfRef<'_> {
+        slf.calculator.unequip_all_equipments_mut();
+        slf
+    }
+
+    fn wear_unique_equipment(mut slf: SelfRef<'_>, level: i32) -> PyResult<SelfRef<'_>> {
+        if slf.calculator.cache().unique_equip.is_none() {
+            return Err(state_error(StateError::NoUniqueEquipment {
+                unit_id: slf.unit_id(),
+            }));
+        }
+
+        slf.calculator.wear_unique_equipment_mut(level);
+        Ok(slf)
+    }
+
+    fn wear_unlock_rarity_6_equipment(mut slf: SelfRef<'_>) -> PyResult<SelfRef<'_>> {
+        if slf.calculator.state().unlock_rarity_6_slot.is_none() {
+            return Err(state_error(StateError::Rarity6NotUnlocked {
+                unit_id: slf.unit_id(),
+            }));
+        }
+
+        slf.calculator.wear_unlock_rarity_6_equipment_mut();
+        Ok(slf)
+    }
+
+    fn watch_all_stories(mut slf: SelfRef<'_>) -> SelfRef<'_> {
+        slf.calculator.watch_all_stories_mut();
+        slf
+    }
+
+    fn watch_story(
+        mut slf: SelfRef<'_>,
+        story_group_id: i64,
+        watched_count: usize,
+    ) -> PyResult<SelfRef<'_>> {
+        if !slf.calculator.state().story.contains_key(&story_group_id) {
+            return Err(state_error(StateError::StoryGroupNotFound {
+                unit_id: slf.unit_id(),
+                story_group_id,
+            }));
+        }
+
+        slf.calculator
+            .watch_story_mut(story_group_id, watched_count);
+        Ok(slf)
+    }
+
+    /// Power of the current state, under an older version of the formula
+    /// if `formula` is given, like `"rarity-6"`
+    #[pyo3(signature = (formula = None))]
+    fn power(&self, formula: Option<&str>) -> PyResult<f64> {
+        let formula = match formula {
+            Some(formula) => formula
+                .parse::<PowerFormula>()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+            None => {
+                return self
+                    .calculator
+                    .try_power()
+                    .map_err(|e| to_py_error(e.into()))
+            }
+        };
+
+        self.validate()?;
+        Ok(self.calculator.power_with(formula))
+    }
+
+    /// Total status, a dict by default or a numpy array if `array` is true
+    #[pyo3(signature = (array = false))]
+    fn status<'py>(&self, py: Python<'py>, array: bool) -> PyResult<Bound<'py, PyAny>> {
+        self.validate()?;
+        status_object(py, &self.calculator.param(), array)
+    }
+
+    /// Power split into status and skill parts, status vectors are dicts
+    /// or numpy arrays like [`Self::status`]
+    #[pyo3(signature = (array = false))]
+    fn breakdown<'py>(&self, py: Python<'py>, array: bool) -> PyResult<Bound<'py, PyDict>> {
+        self.validate()?;
+        let breakdown = self.calculator.breakdown();
+
+        let dict = PyDict::new(py);
+        dict.set_item("base", status_object(py, &breakdown.base, array)?)?;
+        dict.set_item("equipment", status_object(py, &breakdown.equipment, array)?)?;
+        dict.set_item("story", status_object(py, &breakdown.story, array)?)?;
+        dict.set_item("status", status_object(py, &breakdown.status, array)?)?;
+        dict.set_item(
+            "status_power",
+            status_object(py, &breakdown.status_power, array)?,
+        )?;
+        dict.set_item("skill_power", breakdown.skill_power)?;
+        dict.set_item("skill", breakdown.skill)?;
+        dict.set_item("power", breakdown.power)?;
+
+        Ok(dict)
+    }
+
+    fn copy(&self) -> Self {
+        Self {
+            calculator: self.calculator.clone(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let state = self.calculator.state();
+        format!(
+            "UnitCalculator(unit_id={}, rarity={}, level={}, rank={})",
+            state.id, state.rarity, state.level, state.promotion
+        )
+    }
+}
+
+#[pymodule]
+fn powermagic_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_class::<PyDataManager>()?;
+    m.add_class::<PyUnitCache>()?;
+    m.add_class::<PyUnitCalculator>()?;
+    m.add("PowermagicError", py.get_type::<PowermagicError>())?;
+    m.add("InvalidStateError", py.get_type::<InvalidStateError>())?;
+    m.add("STATUS_NAMES", STATUS_NAMES.to_vec())?;
+    m.add(
+        "POWER_FORMULAS",
+        PowerFormula::ALL
+            .iter()
+            .map(|formula| formula.name())
+            .collect::<Vec<_>>(),
+    )?;
+
+    Ok(())
+}
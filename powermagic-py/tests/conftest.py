@@ -0,0 +1,222 @@
+"""Master database of a single unit, written with the columns the loaders read"""
+
+import sqlite3
+
+import pytest
+
+import powermagic_py as pm
+
+UNIT_ID = 100101
+EQUIPMENT_ID = 101011
+UNIQUE_EQUIPMENT_ID = 130011
+
+# table: (columns, status column formats, type of status columns), columns
+# are integers unless typed
+TABLES = {
+    "unit_status_coefficient": (
+        "coefficient_id skill_lv_coefficient:REAL exskill_evolution_coefficient"
+        " overall_coefficient:REAL skill1_evolution_coefficient"
+        " skill1_evolution_slv_coefficient:REAL ub_evolution_coefficient"
+        " ub_evolution_slv_coefficient:REAL",
+        ["{}_coefficient"],
+        "REAL",
+    ),
+    "unit_data": (
+        "unit_id unit_name:TEXT kana:TEXT prefab_id prefab_id_battle is_limited"
+        " rarity motion_type se_type move_speed search_area_width atk_type"
+        " normal_atk_cast_time:REAL cutin_1 cutin_2 cutin1_star6 cutin2_star6"
+        " guild_id exskill_display comment:TEXT only_disp_owned"
+        " start_time:TEXT end_time:TEXT original_unit_id",
+        [],
+        "REAL",
+    ),
+    "unit_promotion": (
+        "unit_id promotion_level equip_slot_1 equip_slot_2 equip_slot_3"
+        " equip_slot_4 equip_slot_5 equip_slot_6",
+        [],
+        "REAL",
+    ),
+    "unit_promotion_status": ("unit_id promotion_level", ["{}"], "REAL"),
+    "promotion_bonus": ("unit_id promotion_level", ["{}"], "REAL"),
+    "unit_rarity": (
+        "unit_id rarity unit_material_id consume_num consume_gold",
+        ["{}", "{}_growth"],
+        "REAL",
+    ),
+    "unlock_rarity_6": (
+        "unit_id slot_id unlock_level unlock_flag consume_gold material_type"
+        " material_id material_count",
+        ["{}"],
+        "INTEGER",
+    ),
+    "unit_unique_equip": ("unit_id equip_slot equip_id", [], "REAL"),
+    "unit_skill_data": (
+        "unit_id union_burst "
+        + " ".join(f"main_skill_{i}" for i in range(1, 11))
+        + " "
+        + " ".join(f"ex_skill_{i}" for i in range(1, 6))
+        + " "
+        + " ".join(f"ex_skill_evolution_{i}" for i in range(1, 6)),
+        [],
+        "REAL",
+    ),
+    "chara_story_status": (
+        "story_id unlock_story_name:TEXT "
+        + " ".join(f"status_type_{i} status_rate_{i}" for i in range(1, 6))
+        + " "
+        + " ".join(f"chara_id_{i}" for i in range(1, 11)),
+        [],
+        "REAL",
+    ),
+    "equipment_data": (
+        "equipment_id equipment_name:TEXT description:TEXT promotion_level"
+        " craft_flg equipment_enhance_point sale_price require_level"
+        " enable_donation display_item item_type",
+        ["{}"],
+        "REAL",
+    ),
+    "equipment_enhance_rate": (
+        "equipment_id equipment_name:TEXT description:TEXT promotion_level",
+        ["{}"],
+        "REAL",
+    ),
+    "equipment_enhance_data": (
+        "promotion_level equipment_enhance_level needed_point total_point",
+        [],
+        "REAL",
+    ),
+    "unique_equipment_data": (
+        "equipment_id equipment_name:TEXT description:TEXT promotion_level"
+        " craft_flg equipment_enhance_point sale_price require_level"
+        " enable_donation",
+        ["{}"],
+        "REAL",
+    ),
+    "unique_equipment_enhance_rate": (
+        "equipment_id equipment_name:TEXT description:TEXT promotion_level",
+        ["{}"],
+        "REAL",
+    ),
+    "unique_equipment_enhance_data": (
+        "equip_slot enhance_level needed_point total_point needed_mana rank",
+        [],
+        "REAL",
+    ),
+}
+
+
+def columns(table):
+    """`(column, type)` of every column of `table`, status columns last"""
+    names, status, status_type = TABLES[table]
+    for column in names.split():
+        name, _, column_type = column.partition(":")
+        yield name, column_type or "INTEGER"
+    for column in status:
+        for name in pm.STATUS_NAMES:
+            yield column.format(name), status_type
+
+
+def insert(db, table, **values):
+    """Insert a row, columns not given are 0 or empty strings"""
+    row = {
+        name: "" if column_type == "TEXT" else 0
+        for name, column_type in columns(table)
+    }
+    row.update(values)
+    db.execute(
+        f"INSERT INTO {table} ({', '.join(row)}) VALUES ({', '.join('?' * len(row))})",
+        list(row.values()),
+    )
+
+
+def write_database(path):
+    db = sqlite3.connect(path)
+    for table in TABLES:
+        declared = ", ".join(f"{name} {t}" for name, t in columns(table))
+        db.execute(f"CREATE TABLE {table} ({declared})")
+
+    insert(
+        db,
+        "unit_status_coefficient",
+        coefficient_id=1,
+        hp_coefficient=0.1,
+        atk_coefficient=4.5,
+        magic_str_coefficient=4.5,
+        def_coefficient=4.5,
+        magic_def_coefficient=4.5,
+        skill_lv_coefficient=10,
+        exskill_evolution_coefficient=200,
+        overall_coefficient=1,
+    )
+    insert(db, "unit_data", unit_id=UNIT_ID, unit_name="ユイ", rarity=1)
+    for rank in (1, 2):
+        insert(
+            db,
+            "unit_promotion",
+            unit_id=UNIT_ID,
+            promotion_level=rank,
+            equip_slot_1=EQUIPMENT_ID,
+            equip_slot_2=999999,
+            equip_slot_3=999999,
+            equip_slot_4=999999,
+            equip_slot_5=999999,
+            equip_slot_6=999999,
+        )
+    for rank in (1, 2):
+        insert(
+            db,
+            "unit_promotion_status",
+            unit_id=UNIT_ID,
+            promotion_level=rank,
+            hp=30 * (rank - 1),
+        )
+    for rarity in range(1, 6):
+        insert(
+            db,
+            "unit_rarity",
+            unit_id=UNIT_ID,
+            rarity=rarity,
+            hp=100 * rarity,
+            hp_growth=12.5,
+            atk=10 * rarity,
+            atk_growth=1.5,
+        )
+    insert(
+        db,
+        "unit_skill_data",
+        unit_id=UNIT_ID,
+        union_burst=1001001,
+        main_skill_1=1001002,
+        ex_skill_1=1001003,
+    )
+    insert(
+        db,
+        "chara_story_status",
+        story_id=1001001,
+        status_type_1=1,
+        status_rate_1=100,
+        chara_id_1=UNIT_ID // 100,
+    )
+    insert(db, "equipment_data", equipment_id=EQUIPMENT_ID, promotion_level=1, hp=50)
+    insert(db, "equipment_enhance_rate", equipment_id=EQUIPMENT_ID, hp=5)
+    insert(db, "equipment_enhance_data", promotion_level=1, equipment_enhance_level=1)
+    insert(db, "unit_unique_equip", unit_id=UNIT_ID, equip_slot=1, equip_id=UNIQUE_EQUIPMENT_ID)
+    insert(db, "unique_equipment_data", equipment_id=UNIQUE_EQUIPMENT_ID, hp=100)
+    insert(db, "unique_equipment_enhance_rate", equipment_id=UNIQUE_EQUIPMENT_ID, hp=10)
+    for level in range(1, 6):
+        insert(db, "unique_equipment_enhance_data", equip_slot=1, enhance_level=level)
+
+    db.commit()
+    db.close()
+
+
+@pytest.fixture
+def data_manager(tmp_path):
+    path = tmp_path / "master.db"
+    write_database(path)
+    return pm.DataManager(f"sqlite://{path}")
+
+
+@pytest.fixture
+def cache(data_manager):
+    return data_manager.unit_cache(UNIT_ID)
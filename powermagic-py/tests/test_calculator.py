@@ -0,0 +1,94 @@
+import numpy as np
+import pytest
+
+import powermagic_py as pm
+
+from conftest import UNIT_ID
+
+LEVEL = 10
+
+
+def test_power(cache):
+    assert cache.unit_id == UNIT_ID
+    assert (cache.max_rarity, cache.max_rank) == (5, 2)
+
+    calculator = cache.calculator()
+    power = calculator.power()
+    assert power > 0
+
+    calculator.set_all_level(LEVEL).set_rank(2).wear_all_equipments(1)
+    assert calculator.power() > power
+
+    assert pm.POWER_FORMULAS[-1] == "current"
+    assert calculator.power(formula="current") == calculator.power()
+    for formula in pm.POWER_FORMULAS:
+        assert calculator.power(formula=formula) > 0
+    with pytest.raises(ValueError):
+        calculator.power(formula="future")
+
+
+def test_status_shapes(cache):
+    calculator = cache.max_calculator(LEVEL)
+
+    status = calculator.status()
+    assert isinstance(status, dict)
+    assert list(status) == pm.STATUS_NAMES
+    assert all(isinstance(value, int) for value in status.values())
+
+    array = calculator.status(array=True)
+    assert isinstance(array, np.ndarray)
+    assert array.shape == (len(pm.STATUS_NAMES),)
+    assert array.dtype == np.int64
+    assert array.tolist() == list(status.values())
+
+
+def test_breakdown(cache):
+    calculator = cache.max_calculator(LEVEL)
+    breakdown = calculator.breakdown()
+
+    assert set(breakdown) == {
+        "base",
+        "equipment",
+        "story",
+        "status",
+        "status_power",
+        "skill_power",
+        "skill",
+        "power",
+    }
+    assert breakdown["power"] == calculator.power()
+    assert breakdown["status"] == calculator.status()
+    for name in pm.STATUS_NAMES:
+        parts = (breakdown[part][name] for part in ("base", "equipment", "story"))
+        assert sum(parts) == breakdown["status"][name]
+    assert breakdown["power"] == pytest.approx(
+        sum(breakdown["status_power"].values()) + breakdown["skill"]
+    )
+
+    arrays = calculator.breakdown(array=True)
+    for part in ("base", "equipment", "story", "status", "status_power"):
+        assert arrays[part].shape == (len(pm.STATUS_NAMES),)
+        assert arrays[part].tolist() == list(breakdown[part].values())
+    assert arrays["status_power"].dtype == np.float64
+
+
+def test_invalid_state(cache):
+    assert issubclass(pm.InvalidStateError, ValueError)
+
+    calculator = cache.calculator()
+    for change in (
+        lambda: calculator.set_rarity(6),
+        lambda: calculator.set_rank(0),
+        lambda: calculator.set_skill_level(calculator.level + 1),
+        lambda: calculator.watch_story(9999, 1),
+    ):
+        with pytest.raises(ValueError):
+            change()
+
+    # Skills above the unit level only show when calculating
+    calculator.set_all_level(LEVEL).set_level(LEVEL - 1)
+    for calculate in (calculator.power, calculator.status, calculator.breakdown):
+        with pytest.raises(pm.InvalidStateError, match="level"):
+            calculate()
+    with pytest.raises(ValueError):
+        calculator.power(formula="rarity-6")
@@ -0,0 +1,123 @@
+use std::any::Any;
+
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+
+use powermagic::error::{DataError, Error};
+use powermagic::unit::ParsePowerFormulaError;
+
+/// Error of a request, answered as [`ErrorBody`]
+#[derive(thiserror::Error, Debug)]
+pub enum ApiError {
+    /// Body, path or query can't be read
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("Unit {unit_id} is in the roster more than once")]
+    DuplicateUnit { unit_id: i64 },
+    /// Level the calculator accepts but the game doesn't, like an equipment
+    /// enhanced past its max or a unit past the level cap
+    #[error("{field} of unit {unit_id} is {value}, out of {min}-{max} range")]
+    OutOfRange {
+        unit_id: i64,
+        field: String,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+    #[error(transparent)]
+    Powermagic(#[from] Error),
+}
+
+/// JSON body of every error response
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    /// Kind of the error, like `invalid_state`
+    pub error: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_id: Option<i64>,
+}
+
+impl ApiError {
+    fn kind(&self) -> (StatusCode, &'static str) {
+        match self {
+            ApiError::InvalidRequest(_) => (StatusCode::BAD_REQUEST, "invalid_request"),
+            ApiError::DuplicateUnit { .. } => (StatusCode::BAD_REQUEST, "duplicate_unit"),
+            ApiError::OutOfRange { .. } => (StatusCode::UNPROCESSABLE_ENTITY, "invalid_state"),
+            ApiError::Powermagic(error) => match error {
+                Error::Data(DataError::UnitNotFound { .. })
+                | Error::Data(DataError::UnitNameNotFound { .. }) => {
+                    (StatusCode::NOT_FOUND, "unit_not_found")
+                }
+                Error::State(_) => (StatusCode::UNPROCESSABLE_ENTITY, "invalid_state"),
+                Error::Calculation(_) => (StatusCode::UNPROCESSABLE_ENTITY, "calculation"),
+                Error::Database(_)
+                | Error::Schema(_)
+                | Error::Data(_)
+                | Error::Snapshot(_)
+                | Error::Dump(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database"),
+            },
+        }
+    }
+
+    fn unit_id(&self) -> Option<i64> {
+        match self {
+            ApiError::InvalidRequest(_) => None,
+            ApiError::DuplicateUnit { unit_id } | ApiError::OutOfRange { unit_id, .. } => {
+                Some(*unit_id)
+            }
+            ApiError::Powermagic(error) => error.unit_id(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = self.kind();
+        let body = ErrorBody {
+            error,
+            unit_id: self.unit_id(),
+            message: self.to_string(),
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+macro_rules! invalid_request {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for ApiError {
+                fn from(error: $error) -> Self {
+                    ApiError::InvalidRequest(error.to_string())
+                }
+            }
+        )*
+    };
+}
+
+invalid_request!(
+    JsonRejection,
+    PathRejection,
+    QueryRejection,
+    ParsePowerFormulaError
+);
+
+/// Answer of a handler that panicked, the server keeps running
+pub fn panic_response(payload: Box<dyn Any + Send + 'static>) -> Response {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let body = ErrorBody {
+        error: "internal",
+        message,
+        unit_id: None,
+    };
+
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+}
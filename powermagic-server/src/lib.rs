@@ -0,0 +1,4 @@
+//! Routes of the power server, the binary opens the database and serves them
+
+mod error;
+pub mod routes;
@@ -0,0 +1,59 @@
+//! Local HTTP server answering power queries in JSON
+//!
+//! The master database is loaded once at start. Statuses are arrays in the
+//! order of `GET /status_names`, errors are `{"error", "message", "unit_id"}`
+//! objects.
+//!
+//! - `GET /units?name=`: units whose name contains `name`
+//! - `GET /units/{unit_id}/caps`: rarity, rank and equipment caps, and the
+//!   max power at the level cap
+//! - `POST /power?formula=`: power of a unit state
+//! - `POST /breakdown`: power of a unit state split into its parts
+//! - `POST /roster`: total power and status of an array of unit states
+
+use std::sync::Arc;
+
+use powermagic::manager::DataManager;
+use powermagic::region::Region;
+use powermagic_server::routes::{self, AppState};
+use sqlx::sqlite::SqlitePoolOptions;
+
+const USAGE: &str = "Usage: powermagic-server <database> [region] [address] [level]";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let database = match args.first() {
+        Some(database) => database,
+        None => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let region: Option<Region> = args.get(1).map(|region| region.parse()).transpose()?;
+    let address = args.get(2).map_or("127.0.0.1:3000", |address| address);
+
+    let pool = SqlitePoolOptions::new().connect(database).await?;
+    let mut data_manager = match region {
+        Some(region) => DataManager::with_pool_in_region(pool, region).await?,
+        None => DataManager::with_pool(pool).await?,
+    };
+    let level = match args.get(3) {
+        Some(level) => level.parse()?,
+        None => data_manager
+            .max_unit_level()
+            .await
+            .map_err(|error| format!("{}: {}, pass the level as argument", database, error))?,
+    };
+    data_manager.preload().await?;
+
+    let state = Arc::new(AppState {
+        data_manager,
+        level,
+    });
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+    axum::serve(listener, routes::router(state)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tower_http::catch_panic::CatchPanicLayer;
+
+use powermagic::compare::UnitCaps;
+use powermagic::error::Error;
+use powermagic::manager::DataManager;
+use powermagic::unit::*;
+
+use crate::error::{panic_response, ApiError};
+
+/// Most states accepted by `/roster`
+const MAX_ROSTER: usize = 1000;
+
+pub struct AppState {
+    pub data_manager: DataManager,
+    /// Level cap of the server, used for caps
+    pub level: i32,
+}
+
+type AppResult<T> = Result<Json<T>, ApiError>;
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/status_names", get(status_names))
+        .route("/units", get(search_units))
+        .route("/units/{unit_id}/caps", get(caps))
+        .route("/power", post(power))
+        .route("/breakdown", post(breakdown))
+        .route("/roster", post(roster))
+        .layer(CatchPanicLayer::custom(panic_response))
+        .with_state(state)
+}
+
+/// Names of the statuses, in the order of every status array
+async fn status_names() -> Json<[&'static str; 17]> {
+    Json(STATUS_NAMES)
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct UnitSummary {
+    unit_id: i64,
+    unit_name: String,
+}
+
+/// `GET /units?name=`, units whose name contains `name`
+async fn search_units(
+    State(state): State<Arc<AppState>>,
+    query: Result<Query<SearchQuery>, QueryRejection>,
+) -> AppResult<Vec<UnitSummary>> {
+    let Query(query) = query?;
+    if query.name.trim().is_empty() {
+        return Err(ApiError::InvalidRequest("name is empty".to_string()));
+    }
+
+    let units = state.data_manager.search_units(&query.name).await?;
+
+    Ok(Json(
+        units
+            .into_iter()
+            .map(|unit| UnitSummary {
+                unit_id: unit.unit_id,
+                unit_name: unit.unit_name,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Serialize)]
+struct CapsResponse {
+    unit_id: i64,
+    level: i32,
+    #[serde(flatten)]
+    caps: UnitCaps,
+    /// Power of [`UnitCache::max_state`] at the level cap
+    max_power: f64,
+}
+
+/// `GET /units/{unit_id}/caps`
+async fn caps(
+    State(state): State<Arc<AppState>>,
+    unit_id: Result<Path<i64>, PathRejection>,
+) -> AppResult<CapsResponse> {
+    let Path(unit_id) = unit_id?;
+    let cache = state.data_manager.shared_unit_cache(unit_id).await?;
+    let max_state = cache.max_state(state.level);
+
+    Ok(Json(CapsResponse {
+        unit_id,
+        level: state.level,
+        caps: UnitCaps::of(&cache),
+        max_power: BorrowedUnitCalculator::new(&cache, &max_state)
+            .try_power()
+            .map_err(Error::from)?,
+    }))
+}
+
+/// Cache of the unit of `unit_state`, after checking the state fits it
+async fn checked_cache(
+    state: &AppState,
+    unit_state: &UnitState,
+) -> Result<Arc<UnitCache>, ApiError> {
+    let cache = state.data_manager.shared_unit_cache(unit_state.id).await?;
+    unit_state.validate(&cache).map_err(Error::from)?;
+    check_levels(&cache, unit_state, state.level)?;
+
+    Ok(cache)
+}
+
+/// Check the levels [`UnitState::validate`] leaves to the caller, the unit
+/// and its skills can't go past the level cap
+fn check_levels(cache: &UnitCache, unit_state: &UnitState, level: i32) -> Result<(), ApiError> {
+    let out_of_range = |field: String, value: i64, min: i64, max: i64| {
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(ApiError::OutOfRange {
+                unit_id: cache.unit_id,
+                field,
+                value,
+                min,
+                max,
+            })
+        }
+    };
+
+    out_of_range(
+        "Level".to_string(),
+        unit_state.level as i64,
+        1,
+        level as i64,
+    )?;
+    let skill = &unit_state.skill;
+    let skills = skill
+        .union_burst
+        .iter()
+        .chain(&skill.main_skill)
+        .chain(&skill.ex_skill)
+        .chain(&skill.free_skill);
+    for skill in skills {
+        out_of_range(
+            format!("Level of skill {}", skill.skill_id),
+            skill.skill_level as i64,
+            0,
+            level as i64,
+        )?;
+    }
+
+    let equipments = &cache.promotion[unit_state.promotion as usize - 1].equipments;
+    for (slot, (equip, equipment)) in unit_state.equip_slot.iter().zip(equipments).enumerate() {
+        if let (
+            EquipSlot::Equipped {
+                enhancement_level, ..
+            },
+            Some(equipment),
+        ) = (equip, equipment)
+        {
+            out_of_range(
+                format!("Level of equipment slot {}", slot + 1),
+                *enhancement_level as i64,
+                0,
+                equipment.max_enhance_level as i64,
+            )?;
+        }
+    }
+
+    if let (
+        Some(EquipSlot::Equipped {
+            enhancement_level, ..
+        }),
+        Some(equipment),
+    ) = (unit_state.unique_equip_slot.first(), &cache.unique_equip)
+    {
+        out_of_range(
+            "Level of unique equipment".to_string(),
+            *enhancement_level as i64,
+            0,
+            equipment.max_enhancement_level as i64,
+        )?;
+    }
+
+    for (story_group_id, group) in unit_state.story.iter() {
+        out_of_range(
+            format!("Watched stories of group {}", story_group_id),
+            group.watched as i64,
+            0,
+            cache.story[story_group_id].0.len() as i64,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct PowerQuery {
+    /// Older version of the formula, like `rarity-6`
+    formula: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PowerResponse {
+    unit_id: i64,
+    power: f64,
+}
+
+/// `POST /power?formula=` with a [`UnitState`]
+async fn power(
+    State(state): State<Arc<AppState>>,
+    query: Result<Query<PowerQuery>, QueryRejection>,
+    unit_state: Result<Json<UnitState>, JsonRejection>,
+) -> AppResult<PowerResponse> {
+    let Query(query) = query?;
+    let Json(unit_state) = unit_state?;
+    let formula = query
+        .formula
+        .map(|formula| formula.parse::<PowerFormula>())
+        .transpose()?;
+
+    let cache = checked_cache(&state, &unit_state).await?;
+    let calculator = BorrowedUnitCalculator::new(&cache, &unit_state);
+    let power = match formula {
+        Some(formula) => calculator.try_power_with(formula),
+        None => calculator.try_power(),
+    }
+    .map_err(Error::from)?;
+
+    Ok(Json(PowerResponse {
+        unit_id: unit_state.id,
+        power,
+    }))
+}
+
+/// `POST /breakdown` with a [`UnitState`]
+async fn breakdown(
+    State(state): State<Arc<AppState>>,
+    unit_state: Result<Json<UnitState>, JsonRejection>,
+) -> AppResult<PowerBreakdown> {
+    let Json(unit_state) = unit_state?;
+    let cache = checked_cache(&state, &unit_state).await?;
+
+    Ok(Json(
+        BorrowedUnitCalculator::new(&cache, &unit_state).breakdown(),
+    ))
+}
+
+#[derive(Debug, Serialize)]
+struct RosterResponse {
+    count: usize,
+    power: f64,
+    status: UnitStatus<i64>,
+    units: Vec<PowerResponse>,
+}
+
+/// `POST /roster` with an array of [`UnitState`], one per unit
+async fn roster(
+    State(state): State<Arc<AppState>>,
+    unit_states: Result<Json<Vec<UnitState>>, JsonRejection>,
+) -> AppResult<RosterResponse> {
+    let Json(unit_states) = unit_states?;
+    if unit_states.len() > MAX_ROSTER {
+        return Err(ApiError::InvalidRequest(format!(
+            "roster has {} units, at most {} are accepted",
+            unit_states.len(),
+            MAX_ROSTER
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut response = RosterResponse {
+        count: unit_states.len(),
+        power: 0.0,
+        status: UnitStatus::zeros(),
+        units: Vec::with_capacity(unit_states.len()),
+    };
+    for unit_state in unit_states.iter() {
+        if !seen.insert(unit_state.id) {
+            return Err(ApiError::DuplicateUnit {
+                unit_id: unit_state.id,
+            });
+        }
+
+        let cache = checked_cache(&state, unit_state).await?;
+        let calculator = BorrowedUnitCalculator::new(&cache, unit_state);
+        let power = calculator.try_power().map_err(Error::from)?;
+
+        response.power += power;
+        response.status += calculator.param();
+        response.units.push(PowerResponse {
+            unit_id: unit_state.id,
+            power,
+        });
+    }
+
+    Ok(Json(response))
+}
@@ -0,0 +1,328 @@
+#[path = "../../powermagic/tests/common/mod.rs"]
+mod common;
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use powermagic::manager::DataManager;
+use powermagic_server::routes::{self, AppState};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{add_unit, dump, insert, source, Dump, UNIT_ID};
+
+const OTHER_UNIT_ID: i64 = 100201;
+const UNIQUE_EQUIPMENT_ID: i64 = 130011;
+const LEVEL: i32 = 10;
+
+/// The fixture unit with a unique equipment, and a unit of a single rank
+fn tables() -> Dump {
+    let mut tables = dump();
+    add_unit(&mut tables, OTHER_UNIT_ID, 999999);
+    insert(
+        &mut tables,
+        "unit_unique_equip",
+        json!({"unit_id": UNIT_ID, "equip_slot": 1, "equip_id": UNIQUE_EQUIPMENT_ID}),
+    );
+    insert(
+        &mut tables,
+        "unique_equipment_data",
+        json!({"equipment_id": UNIQUE_EQUIPMENT_ID, "hp": 100}),
+    );
+    insert(
+        &mut tables,
+        "unique_equipment_enhance_rate",
+        json!({"equipment_id": UNIQUE_EQUIPMENT_ID, "hp": 10}),
+    );
+    for level in 1..=5 {
+        insert(
+            &mut tables,
+            "unique_equipment_enhance_data",
+            json!({"equip_slot": 1, "enhance_level": level}),
+        );
+    }
+
+    tables
+}
+
+/// Router over the fixture, and the max state of each unit as JSON
+async fn app() -> (Router, Value, Value) {
+    let data_manager = DataManager::with_source(Arc::new(source(&tables()).await))
+        .await
+        .unwrap();
+
+    let mut states = vec![];
+    for unit_id in [UNIT_ID, OTHER_UNIT_ID] {
+        let cache = data_manager.unit_cache(unit_id).await.unwrap();
+        states.push(serde_json::to_value(cache.max_state(LEVEL)).unwrap());
+    }
+    let other = states.pop().unwrap();
+    let state = states.pop().unwrap();
+    let app = routes::router(Arc::new(AppState {
+        data_manager,
+        level: LEVEL,
+    }));
+
+    (app, state, other)
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    send(app, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+async fn post(app: &Router, uri: &str, body: &Value) -> (StatusCode, Value) {
+    let request = Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    send(app, request).await
+}
+
+#[tokio::test]
+async fn power_rejects_unset_unique_equip_slot() {
+    let (app, state, _) = app().await;
+    let mut unset = state.clone();
+    unset["unique_equip_slot"] = json!(["None"]);
+
+    for uri in ["/power", "/power?formula=rarity-6"] {
+        let (status, body) = post(&app, uri, &state).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+        assert!(body["power"].as_f64().unwrap() > 0.0, "{}: {}", uri, body);
+
+        let (status, body) = post(&app, uri, &unset).await;
+        assert_eq!(
+            status,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{}: {}",
+            uri,
+            body
+        );
+        assert_eq!(body["error"], "invalid_state", "{}: {}", uri, body);
+        assert_eq!(body["unit_id"], UNIT_ID, "{}: {}", uri, body);
+    }
+
+    let (status, body) = post(&app, "/power?formula=future", &state).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "invalid_request");
+}
+
+#[tokio::test]
+async fn search_units_by_name() {
+    let (app, _, _) = app().await;
+
+    let (status, body) = get(&app, "/units?name=ユ").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body, json!([{"unit_id": UNIT_ID, "unit_name": "ユイ"}]));
+
+    let (status, body) = get(&app, "/units?name=unit").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body,
+        json!([{"unit_id": OTHER_UNIT_ID, "unit_name": "unit 100201"}])
+    );
+
+    let (_, body) = get(&app, "/units?name=nobody").await;
+    assert_eq!(body, json!([]));
+
+    for uri in ["/units?name=%20", "/units"] {
+        let (status, body) = get(&app, uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", uri, body);
+        assert_eq!(body["error"], "invalid_request", "{}: {}", uri, body);
+    }
+}
+
+#[tokio::test]
+async fn caps_and_max_power() {
+    let (app, state, _) = app().await;
+
+    let (status, body) = get(&app, &format!("/units/{}/caps", UNIT_ID)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, power) = post(&app, "/power", &state).await;
+    assert_eq!(
+        body,
+        json!({
+            "unit_id": UNIT_ID,
+            "level": LEVEL,
+            "rarity": 5,
+            "rank": 2,
+            "rarity_6": false,
+            "unique_equipment": [UNIQUE_EQUIPMENT_ID, 5],
+            "max_power": power["power"],
+        })
+    );
+
+    let (status, body) = get(&app, "/units/999999/caps").await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+    assert_eq!(body["error"], "unit_not_found");
+    assert_eq!(body["unit_id"], 999999);
+
+    let (status, body) = get(&app, "/units/yui/caps").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "invalid_request");
+}
+
+#[tokio::test]
+async fn breakdown_adds_up_to_the_power() {
+    let (app, state, _) = app().await;
+
+    let (status, body) = post(&app, "/breakdown", &state).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, power) = post(&app, "/power", &state).await;
+    assert_eq!(body["power"], power["power"]);
+
+    let status_of = |part: &str| -> Vec<i64> {
+        body[part]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_i64().unwrap())
+            .collect()
+    };
+    let total = status_of("status");
+    assert_eq!(total.len(), 17);
+    for (i, value) in total.iter().enumerate() {
+        assert_eq!(
+            status_of("base")[i] + status_of("equipment")[i] + status_of("story")[i],
+            *value,
+            "status {}",
+            i
+        );
+    }
+    let status_power: f64 = body["status_power"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| value.as_f64().unwrap())
+        .sum();
+    let skill = body["skill"].as_f64().unwrap();
+    assert!((status_power + skill - body["power"].as_f64().unwrap()).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn roster_sums_its_units() {
+    let (app, state, other) = app().await;
+
+    let (status, body) = post(&app, "/roster", &json!([state, other])).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["count"], 2);
+
+    let (_, power) = post(&app, "/power", &state).await;
+    let (_, other_power) = post(&app, "/power", &other).await;
+    assert_eq!(body["units"], json!([power, other_power]));
+    assert_eq!(
+        body["power"].as_f64().unwrap(),
+        power["power"].as_f64().unwrap() + other_power["power"].as_f64().unwrap()
+    );
+
+    let (_, breakdown) = post(&app, "/breakdown", &state).await;
+    let (_, other_breakdown) = post(&app, "/breakdown", &other).await;
+    let sum: Vec<i64> = (0..17)
+        .map(|i| {
+            breakdown["status"][i].as_i64().unwrap()
+                + other_breakdown["status"][i].as_i64().unwrap()
+        })
+        .collect();
+    assert_eq!(body["status"], json!(sum));
+
+    let (status, body) = post(&app, "/roster", &json!([])).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["count"], 0);
+    assert_eq!(body["power"], 0.0);
+}
+
+#[tokio::test]
+async fn roster_rejects_duplicate_units() {
+    let (app, state, other) = app().await;
+
+    let (status, body) = post(&app, "/roster", &json!([state, other, state])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(
+        body,
+        json!({
+            "error": "duplicate_unit",
+            "message": format!("Unit {} is in the roster more than once", UNIT_ID),
+            "unit_id": UNIT_ID,
+        })
+    );
+}
+
+#[tokio::test]
+async fn levels_out_of_range() {
+    let (app, state, other) = app().await;
+
+    let mut past_cap = state.clone();
+    past_cap["level"] = json!(LEVEL + 1);
+    let mut skill_past_cap = past_cap.clone();
+    skill_past_cap["skill"]["union_burst"][0]["skill_level"] = json!(LEVEL + 1);
+    let mut level_0 = state.clone();
+    level_0["level"] = json!(0);
+    for kind in ["union_burst", "main_skill", "ex_skill"] {
+        level_0["skill"][kind][0]["skill_level"] = json!(0);
+    }
+    let mut equipment = state.clone();
+    equipment["equip_slot"][0]["Equipped"]["enhancement_level"] = json!(6);
+    let mut unique_equipment = state.clone();
+    unique_equipment["unique_equip_slot"][0]["Equipped"]["enhancement_level"] = json!(6);
+
+    let cases = [
+        (
+            past_cap,
+            format!("Level of unit {} is 11, out of 1-10 range", UNIT_ID),
+        ),
+        (
+            skill_past_cap,
+            format!("Level of unit {} is 11, out of 1-10 range", UNIT_ID),
+        ),
+        (
+            level_0,
+            format!("Level of unit {} is 0, out of 1-10 range", UNIT_ID),
+        ),
+        (
+            equipment,
+            format!(
+                "Level of equipment slot 1 of unit {} is 6, out of 0-5 range",
+                UNIT_ID
+            ),
+        ),
+        (
+            unique_equipment,
+            format!(
+                "Level of unique equipment of unit {} is 6, out of 0-5 range",
+                UNIT_ID
+            ),
+        ),
+    ];
+    for (unit_state, message) in cases {
+        let expected = json!({
+            "error": "invalid_state",
+            "message": message,
+            "unit_id": UNIT_ID,
+        });
+        for (uri, body) in [
+            ("/power", unit_state.clone()),
+            ("/breakdown", unit_state.clone()),
+            ("/roster", json!([other, unit_state])),
+        ] {
+            let (status, body) = post(&app, uri, &body).await;
+            assert_eq!(
+                status,
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "{}: {}",
+                uri,
+                body
+            );
+            assert_eq!(body, expected, "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use powermagic::changelog;
+use powermagic::manager::DataManager;
+
+/// Usage: changelog <old database> <new database> [old level] [new level]
+///
+/// Without levels, the highest level of `experience_unit` in each database is
+/// used. Exits with 1 if some units failed to load.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("Usage: changelog <old database> <new database> [old level] [new level]");
+        std::process::exit(2);
+    }
+
+    let mut old = DataManager::new(&args[0]).await?;
+    let mut new = DataManager::new(&args[1]).await?;
+
+    let old_level = common::level(&args[0], &old, args.get(2)).await?;
+    let new_level = common::level(&args[1], &new, args.get(3)).await?;
+
+    old.preload().await?;
+    new.preload().await?;
+
+    let changelog = changelog::diff(&old, old_level, &new, new_level).await?;
+    print!("{}", changelog);
+
+    if !changelog.failed_units.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+//! Arguments shared by the binaries
+
+use powermagic::manager::DataManager;
+
+/// Level given as argument, else the level cap of the database
+pub async fn level(
+    database: &str,
+    data_manager: &DataManager,
+    level: Option<&String>,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    Ok(match level {
+        Some(level) => level.parse()?,
+        None => data_manager
+            .max_unit_level()
+            .await
+            .map_err(|error| format!("{}: {}, pass the levels as arguments", database, error))?,
+    })
+}
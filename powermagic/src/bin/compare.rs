@@ -0,0 +1,43 @@
+mod common;
+
+use powermagic::compare;
+use powermagic::manager::DataManager;
+use powermagic::region::Region;
+
+const USAGE: &str =
+    "Usage: compare <left database> <left region> <right database> <right region> [left level] [right level]";
+
+/// Open a server's database, with its level cap unless `level` is given
+async fn open(
+    database: &str,
+    region: &str,
+    level: Option<&String>,
+) -> Result<(DataManager, i32), Box<dyn std::error::Error>> {
+    let region: Region = region.parse()?;
+    let mut data_manager = DataManager::new_in_region(database, region).await?;
+    let level = common::level(database, &data_manager, level).await?;
+    data_manager.preload().await?;
+
+    Ok((data_manager, level))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 4 {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    }
+
+    let (left, left_level) = open(&args[0], &args[1], args.get(4)).await?;
+    let (right, right_level) = open(&args[2], &args[3], args.get(5)).await?;
+
+    let comparison = compare::compare_servers(&left, left_level, &right, right_level).await?;
+    print!("{}", comparison);
+
+    if !comparison.failed_units.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
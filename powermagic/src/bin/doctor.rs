@@ -0,0 +1,21 @@
+use powermagic::doctor;
+use powermagic::region::Region;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "powermagic.db".to_string());
+    let region: Option<Region> = args.next().map(|region| region.parse()).transpose()?;
+
+    let report = match region {
+        Some(region) => doctor::diagnose_database_in_region(&path, region).await?,
+        None => doctor::diagnose_database(&path).await?,
+    };
+    print!("{}", report);
+
+    if !report.is_ok() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Blocking wrapper of [`crate::manager::DataManager`]
+//!
+//! Loading unit data is async, calculating is not. [`DataManager`] runs the
+//! loaders on its own single threaded runtime, so scripts and FFI callers
+//! don't have to set one up:
+//!
+//! ```no_run
+//! use powermagic::blocking::DataManager;
+//! use powermagic::unit::*;
+//!
+//! let data_manager = DataManager::new("sqlite://powermagic.db")?;
+//! let cache = data_manager.unit_cache(100101)?;
+//! let power = UnitCalculator::new(&cache).set_all_level(100).power();
+//! # Ok::<(), powermagic::error::Error>(())
+//! ```
+//!
+//! Its methods block the current thread and panic when called from async
+//! code, use the async manager there.
+
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use tokio::runtime::Runtime;
+
+use crate::error::Error;
+use crate::manager;
+use crate::model;
+use crate::region::{Region, RegionProfile};
+use crate::source::DataSource;
+use crate::timeline::{GameTime, Release};
+use crate::unit::{UnitCache, UnitData, UnitSnapshot};
+
+/// [`manager::DataManager`] with blocking loaders
+///
+/// Synchronous methods of the async manager, like
+/// [`manager::DataManager::set_as_of`], are reached through `Deref`.
+pub struct DataManager {
+    // Dropped before the runtime its pool was opened on
+    inner: manager::DataManager,
+    runtime: Runtime,
+}
+
+impl std::fmt::Debug for DataManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+fn runtime() -> Result<Runtime, Error> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(sqlx::Error::Io)?;
+
+    Ok(runtime)
+}
+
+// Constructor
+impl DataManager {
+    pub fn new(connection: &str) -> Result<Self, Error> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(manager::DataManager::new(connection))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Open the database of a server
+    pub fn new_in_region(connection: &str, region: Region) -> Result<Self, Error> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(manager::DataManager::new_in_region(connection, region))?;
+        Ok(Self { inner, runtime })
+    }
+
+    pub fn with_profile(
+        source: Arc<dyn DataSource>,
+        profile: RegionProfile,
+    ) -> Result<Self, Error> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(manager::DataManager::with_profile(source, profile))?;
+        Ok(Self { inner, runtime })
+    }
+}
+
+// Loaders
+impl DataManager {
+    /// See [`manager::DataManager::preload`]
+    pub fn preload(&mut self) -> Result<(), Error> {
+        self.runtime.block_on(self.inner.preload())
+    }
+
+    pub fn unit_ids(&self) -> Result<Vec<i64>, Error> {
+        self.runtime.block_on(self.inner.unit_ids())
+    }
+
+    pub fn unit_data(&self, unit_id: i64) -> Result<UnitData, Error> {
+        self.runtime.block_on(self.inner.unit_data(unit_id))
+    }
+
+    pub fn unit_cache(&self, unit_id: i64) -> Result<UnitCache, Error> {
+        self.runtime.block_on(self.inner.unit_cache(unit_id))
+    }
+
+    pub fn unit_caches(&self) -> Result<HashMap<i64, UnitCache>, Error> {
+        self.runtime.block_on(self.inner.unit_caches())
+    }
+
+    pub fn shared_unit_cache(&self, unit_id: i64) -> Result<Arc<UnitCache>, Error> {
+        self.runtime.block_on(self.inner.shared_unit_cache(unit_id))
+    }
+
+    pub fn find_units_by_name(&self, name: &str) -> Result<Vec<model::UnitData>, Error> {
+        self.runtime.block_on(self.inner.find_units_by_name(name))
+    }
+
+    pub fn unit_id_by_name(&self, name: &str) -> Result<i64, Error> {
+        self.runtime.block_on(self.inner.unit_id_by_name(name))
+    }
+
+    pub fn units_at(&self, time: GameTime) -> Result<Vec<model::UnitData>, Error> {
+        self.runtime.block_on(self.inner.units_at(time))
+    }
+
+    pub fn release_timeline(&self) -> Result<Vec<Release>, Error> {
+        self.runtime.block_on(self.inner.release_timeline())
+    }
+
+    pub fn snapshot(&self, source_version: &str) -> Result<UnitSnapshot, Error> {
+        self.runtime.block_on(self.inner.snapshot(source_version))
+    }
+}
+
+impl Deref for DataManager {
+    type Target = manager::DataManager;
+
+    fn deref(&self) -> &manager::DataManager {
+        &self.inner
+    }
+}
+
+impl DerefMut for DataManager {
+    fn deref_mut(&mut self) -> &mut manager::DataManager {
+        &mut self.inner
+    }
+}
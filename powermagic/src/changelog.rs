@@ -0,0 +1,548 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::error::Error;
+use crate::manager::DataManager;
+use crate::model;
+use crate::unit::*;
+
+/// A status that differs between two versions
+#[derive(Debug, Clone)]
+pub struct StatusChange {
+    pub status: &'static str,
+    pub old: f64,
+    pub new: f64,
+}
+
+impl fmt::Display for StatusChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} -> {}", self.status, self.old, self.new)
+    }
+}
+
+fn status_changes(old: &UnitStatus<f64>, new: &UnitStatus<f64>) -> Vec<StatusChange> {
+    STATUS_NAMES
+        .iter()
+        .zip(old.iter().zip(new.iter()))
+        .filter(|(_, (old, new))| old != new)
+        .map(|(status, (old, new))| StatusChange {
+            status,
+            old: *old,
+            new: *new,
+        })
+        .collect()
+}
+
+fn write_status_changes(f: &mut fmt::Formatter<'_>, changes: &[StatusChange]) -> fmt::Result {
+    for (i, change) in changes.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", change)?;
+    }
+
+    Ok(())
+}
+
+/// Change of a unit that exists in both versions
+#[derive(Debug, Clone)]
+pub enum UnitChange {
+    NewRank {
+        rank: i64,
+        equipments: [i64; 6],
+    },
+    EquipmentChanged {
+        rank: i64,
+        /// Slot index, from 1
+        slot: usize,
+        old: i64,
+        new: i64,
+    },
+    NewRarity {
+        rarity: i64,
+    },
+    RarityStatus {
+        rarity: i64,
+        changes: Vec<StatusChange>,
+    },
+    RarityGrowth {
+        rarity: i64,
+        changes: Vec<StatusChange>,
+    },
+    PromotionStatus {
+        rank: i64,
+        changes: Vec<StatusChange>,
+    },
+    PromotionBonus {
+        rank: i64,
+        changes: Vec<StatusChange>,
+    },
+    NewUniqueEquipment {
+        equipment_id: i64,
+    },
+    /// Rarity 6 unlocked, or levels added to its slots
+    Rarity6 {
+        /// Number of levels of each slot, `None` if rarity 6 was not unlocked
+        old: Option<[usize; 3]>,
+        new: [usize; 3],
+    },
+}
+
+impl fmt::Display for UnitChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitChange::NewRank { rank, equipments } => {
+                write!(f, "new rank {}, equipments {:?}", rank, equipments)
+            }
+            UnitChange::EquipmentChanged {
+                rank,
+                slot,
+                old,
+                new,
+            } => write!(
+                f,
+                "rank {} slot {} equipment {} -> {}",
+                rank, slot, old, new
+            ),
+            UnitChange::NewRarity { rarity } => write!(f, "new rarity {}", rarity),
+            UnitChange::RarityStatus { rarity, changes } => {
+                write!(f, "rarity {} status: ", rarity)?;
+                write_status_changes(f, changes)
+            }
+            UnitChange::RarityGrowth { rarity, changes } => {
+                write!(f, "rarity {} growth: ", rarity)?;
+                write_status_changes(f, changes)
+            }
+            UnitChange::PromotionStatus { rank, changes } => {
+                write!(f, "rank {} status: ", rank)?;
+                write_status_changes(f, changes)
+            }
+            UnitChange::PromotionBonus { rank, changes } => {
+                write!(f, "rank {} bonus: ", rank)?;
+                write_status_changes(f, changes)
+            }
+            UnitChange::NewUniqueEquipment { equipment_id } => {
+                write!(f, "new unique equipment {}", equipment_id)
+            }
+            UnitChange::Rarity6 { old: None, new } => {
+                write!(f, "rarity 6 unlocked, slot levels {:?}", new)
+            }
+            UnitChange::Rarity6 {
+                old: Some(old),
+                new,
+            } => {
+                write!(f, "rarity 6 slot levels {:?} -> {:?}", old, new)
+            }
+        }
+    }
+}
+
+/// Power of a unit in its max state, see [`UnitCache::max_state`]
+#[derive(Debug, Clone, Copy)]
+pub struct PowerChange {
+    pub old: f64,
+    pub new: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnitChangelog {
+    pub unit_id: i64,
+    pub unit_name: String,
+    pub changes: Vec<UnitChange>,
+    pub power: PowerChange,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewUnit {
+    pub unit_id: i64,
+    pub unit_name: String,
+    /// Power in the max state
+    pub power: f64,
+}
+
+/// Unit left out of a report because one of the databases can't load it
+#[derive(Debug)]
+pub struct UnitFailure {
+    pub unit_id: i64,
+    pub unit_name: String,
+    /// Database that failed, `old` or `new` in a changelog and the server in
+    /// a comparison
+    pub database: String,
+    pub error: Error,
+}
+
+impl fmt::Display for UnitFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}: {}",
+            self.unit_id, self.unit_name, self.database, self.error
+        )
+    }
+}
+
+/// A field of the status coefficient that differs between two versions
+#[derive(Debug, Clone)]
+pub struct CoefficientChange {
+    pub name: String,
+    pub old: f64,
+    pub new: f64,
+}
+
+/// Differences between two versions of a master database
+#[derive(Debug, Default)]
+pub struct Changelog {
+    /// Level of the max state used for the power of each version
+    pub old_level: i32,
+    pub new_level: i32,
+    pub new_units: Vec<NewUnit>,
+    pub removed_units: Vec<i64>,
+    pub coefficient: Vec<CoefficientChange>,
+    /// Units found in both versions, changed or not
+    pub units: Vec<UnitChangelog>,
+    /// Units of the new version that failed to load in either version
+    pub failed_units: Vec<UnitFailure>,
+}
+
+impl Changelog {
+    pub fn changed_units(&self) -> impl Iterator<Item = &UnitChangelog> {
+        self.units.iter().filter(|unit| !unit.changes.is_empty())
+    }
+}
+
+impl fmt::Display for Changelog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.coefficient.is_empty() {
+            writeln!(f, "Status coefficient:")?;
+            for change in self.coefficient.iter() {
+                writeln!(f, "  {} {} -> {}", change.name, change.old, change.new)?;
+            }
+        }
+
+        if !self.new_units.is_empty() {
+            writeln!(f, "New units:")?;
+            for unit in self.new_units.iter() {
+                writeln!(
+                    f,
+                    "  {} {}: power {}",
+                    unit.unit_id,
+                    unit.unit_name,
+                    unit.power.cy_round::<i64>()
+                )?;
+            }
+        }
+
+        if !self.removed_units.is_empty() {
+            writeln!(f, "Removed units: {:?}", self.removed_units)?;
+        }
+
+        let mut changed = self.changed_units().peekable();
+        if changed.peek().is_some() {
+            writeln!(f, "Changed units:")?;
+            for unit in changed {
+                writeln!(f, "  {} {}:", unit.unit_id, unit.unit_name)?;
+                for change in unit.changes.iter() {
+                    writeln!(f, "    {}", change)?;
+                }
+            }
+        }
+
+        if self.old_level == self.new_level {
+            writeln!(f, "Power at level {}:", self.new_level)?;
+        } else {
+            writeln!(
+                f,
+                "Power at level {} -> {}:",
+                self.old_level, self.new_level
+            )?;
+        }
+        for unit in self.units.iter() {
+            let old = unit.power.old.cy_round::<i64>();
+            let new = unit.power.new.cy_round::<i64>();
+            writeln!(
+                f,
+                "  {} {}: {} -> {} ({:+})",
+                unit.unit_id,
+                unit.unit_name,
+                old,
+                new,
+                new - old
+            )?;
+        }
+
+        if !self.failed_units.is_empty() {
+            writeln!(f, "Failed units:")?;
+            for unit in self.failed_units.iter() {
+                writeln!(f, "  {}", unit)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn coefficient_changes(
+    old: &model::UnitStatusCoefficient,
+    new: &model::UnitStatusCoefficient,
+) -> Vec<CoefficientChange> {
+    let mut changes: Vec<CoefficientChange> =
+        status_changes(&old.status_coefficient(), &new.status_coefficient())
+            .into_iter()
+            .map(|change| CoefficientChange {
+                name: format!("{}_coefficient", change.status),
+                old: change.old,
+                new: change.new,
+            })
+            .collect();
+
+    let fields = [
+        (
+            "skill_lv_coefficient",
+            old.skill_lv_coefficient,
+            new.skill_lv_coefficient,
+        ),
+        (
+            "overall_coefficient",
+            old.overall_coefficient,
+            new.overall_coefficient,
+        ),
+        (
+            "exskill_evolution_coefficient",
+            old.exskill_evolution_coefficient as f64,
+            new.exskill_evolution_coefficient as f64,
+        ),
+        (
+            "skill1_evolution_coefficient",
+            old.skill1_evolution_coefficient as f64,
+            new.skill1_evolution_coefficient as f64,
+        ),
+        (
+            "skill1_evolution_slv_coefficient",
+            old.skill1_evolution_slv_coefficient,
+            new.skill1_evolution_slv_coefficient,
+        ),
+        (
+            "ub_evolution_coefficient",
+            old.ub_evolution_coefficient as f64,
+            new.ub_evolution_coefficient as f64,
+        ),
+        (
+            "ub_evolution_slv_coefficient",
+            old.ub_evolution_slv_coefficient,
+            new.ub_evolution_slv_coefficient,
+        ),
+    ];
+    for (name, old, new) in fields {
+        if old != new {
+            changes.push(CoefficientChange {
+                name: name.to_string(),
+                old,
+                new,
+            });
+        }
+    }
+
+    changes
+}
+
+fn rarity_6_levels(unit: &UnitData) -> Option<[usize; 3]> {
+    unit.unlock_rarity_6
+        .as_ref()
+        .map(|slots| [slots[0].len(), slots[1].len(), slots[2].len()])
+}
+
+fn unit_changes(old: &UnitData, new: &UnitData) -> Vec<UnitChange> {
+    let mut changes = vec![];
+
+    for (i, promotion) in new.promotion.iter().enumerate() {
+        let rank = promotion.promotion.promotion_level;
+        let old_promotion = match old.promotion.get(i) {
+            Some(old_promotion) => old_promotion,
+            None => {
+                changes.push(UnitChange::NewRank {
+                    rank,
+                    equipments: promotion.promotion.equip_slot,
+                });
+                continue;
+            }
+        };
+
+        for (slot, (old, new)) in old_promotion
+            .promotion
+            .equip_slot
+            .iter()
+            .zip(promotion.promotion.equip_slot.iter())
+            .enumerate()
+        {
+            if old != new {
+                changes.push(UnitChange::EquipmentChanged {
+                    rank,
+                    slot: slot + 1,
+                    old: *old,
+                    new: *new,
+                });
+            }
+        }
+
+        let old_status = old_promotion.status.as_ref().map(|s| s.status());
+        let new_status = promotion.status.as_ref().map(|s| s.status());
+        let changed = status_changes(
+            &old_status.unwrap_or_else(UnitStatus::zeros),
+            &new_status.unwrap_or_else(UnitStatus::zeros),
+        );
+        if !changed.is_empty() {
+            changes.push(UnitChange::PromotionStatus {
+                rank,
+                changes: changed,
+            });
+        }
+
+        let old_bonus = old_promotion.bonus.as_ref().map(|b| b.status());
+        let new_bonus = promotion.bonus.as_ref().map(|b| b.status());
+        let changed = status_changes(
+            &old_bonus.unwrap_or_else(UnitStatus::zeros),
+            &new_bonus.unwrap_or_else(UnitStatus::zeros),
+        );
+        if !changed.is_empty() {
+            changes.push(UnitChange::PromotionBonus {
+                rank,
+                changes: changed,
+            });
+        }
+    }
+
+    for rarity in new.rarity.iter() {
+        let old_rarity = match old.rarity.iter().find(|r| r.rarity == rarity.rarity) {
+            Some(old_rarity) => old_rarity,
+            None => {
+                changes.push(UnitChange::NewRarity {
+                    rarity: rarity.rarity,
+                });
+                continue;
+            }
+        };
+
+        let changed = status_changes(&old_rarity.status(), &rarity.status());
+        if !changed.is_empty() {
+            changes.push(UnitChange::RarityStatus {
+                rarity: rarity.rarity,
+                changes: changed,
+            });
+        }
+
+        let changed = status_changes(&old_rarity.status_growth(), &rarity.status_growth());
+        if !changed.is_empty() {
+            changes.push(UnitChange::RarityGrowth {
+                rarity: rarity.rarity,
+                changes: changed,
+            });
+        }
+    }
+
+    for equip in new.unique_equip.iter() {
+        let existed = old
+            .unique_equip
+            .iter()
+            .any(|old| old.equip_id == equip.equip_id);
+        if !existed && equip.equip_id != 999999 {
+            changes.push(UnitChange::NewUniqueEquipment {
+                equipment_id: equip.equip_id,
+            });
+        }
+    }
+
+    let old_levels = rarity_6_levels(old);
+    if let Some(new_levels) = rarity_6_levels(new) {
+        if old_levels != Some(new_levels) {
+            changes.push(UnitChange::Rarity6 {
+                old: old_levels,
+                new: new_levels,
+            });
+        }
+    }
+
+    changes
+}
+
+/// Compare two versions of the master data
+///
+/// Powers are those of [`UnitCache::max_state`] at the level of each version,
+/// usually its level cap, with the status coefficient of each version. Units
+/// that fail to load are listed in [`Changelog::failed_units`].
+pub async fn diff(
+    old: &DataManager,
+    old_level: i32,
+    new: &DataManager,
+    new_level: i32,
+) -> Result<Changelog, Error> {
+    let names = new.unit_names().await?;
+    let name_of = |unit_id: i64| names.get(&unit_id).cloned().unwrap_or_default();
+
+    let old_ids: HashSet<i64> = old.unit_ids().await?.into_iter().collect();
+    let new_ids = new.unit_ids().await?;
+
+    let mut changelog = Changelog {
+        old_level,
+        new_level,
+        coefficient: coefficient_changes(&old.status_coefficient, &new.status_coefficient),
+        ..Default::default()
+    };
+
+    for unit_id in new_ids.iter().copied() {
+        let unit_name = name_of(unit_id);
+        let failure = |database: &str, error: Error| UnitFailure {
+            unit_id,
+            unit_name: unit_name.clone(),
+            database: database.to_string(),
+            error,
+        };
+
+        if !old_ids.contains(&unit_id) {
+            match new.max_power(unit_id, new_level).await {
+                Ok((_, power)) => changelog.new_units.push(NewUnit {
+                    unit_id,
+                    unit_name,
+                    power,
+                }),
+                Err(error) => changelog.failed_units.push(failure("new", error)),
+            }
+            continue;
+        }
+
+        let old_unit = unit_with_power(old, unit_id, old_level).await;
+        let new_unit = unit_with_power(new, unit_id, new_level).await;
+        match (old_unit, new_unit) {
+            (Ok((old_unit, old_power)), Ok((new_unit, new_power))) => {
+                changelog.units.push(UnitChangelog {
+                    unit_id,
+                    unit_name,
+                    changes: unit_changes(&old_unit, &new_unit),
+                    power: PowerChange {
+                        old: old_power,
+                        new: new_power,
+                    },
+                })
+            }
+            (Err(error), _) => changelog.failed_units.push(failure("old", error)),
+            (_, Err(error)) => changelog.failed_units.push(failure("new", error)),
+        }
+    }
+
+    let new_ids: HashSet<i64> = new_ids.into_iter().collect();
+    changelog.removed_units = old_ids.difference(&new_ids).copied().collect();
+    changelog.removed_units.sort_unstable();
+
+    Ok(changelog)
+}
+
+/// Data of a unit and its max power in one version
+async fn unit_with_power(
+    data_manager: &DataManager,
+    unit_id: i64,
+    level: i32,
+) -> Result<(UnitData, f64), Error> {
+    let unit = data_manager.unit_data(unit_id).await?;
+    let (_, power) = data_manager.max_power(unit_id, level).await?;
+
+    Ok((unit, power))
+}
@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::changelog::UnitFailure;
+use crate::error::Error;
+use crate::manager::DataManager;
+use crate::region::Region;
+use crate::unit::*;
+
+/// What a unit can reach on a server
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct UnitCaps {
+    pub rarity: usize,
+    pub rank: usize,
+    pub rarity_6: bool,
+    /// Unique equipment id and its max enhancement level
+    pub unique_equipment: Option<(i64, i32)>,
+}
+
+impl UnitCaps {
+    pub fn of(cache: &UnitCache) -> Self {
+        Self {
+            rarity: cache.rarity.len(),
+            rank: cache.promotion.len(),
+            rarity_6: cache.unlock_rarity_6.is_some(),
+            unique_equipment: cache
+                .unique_equip
+                .as_ref()
+                .map(|equipment| (equipment.id, equipment.max_enhancement_level)),
+        }
+    }
+}
+
+impl fmt::Display for UnitCaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rarity {}, rank {}", self.rarity, self.rank)?;
+        if self.rarity_6 {
+            write!(f, ", rarity 6 slots")?;
+        }
+        match self.unique_equipment {
+            Some((id, level)) => write!(f, ", unique equipment {} lv {}", id, level),
+            None => write!(f, ", no unique equipment"),
+        }
+    }
+}
+
+/// Equipments of a rank on both servers, `None` if the rank doesn't exist
+#[derive(Debug, Clone)]
+pub struct RankEquipments {
+    pub rank: usize,
+    pub left: Option<Vec<i64>>,
+    pub right: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnitComparison {
+    pub unit_id: i64,
+    pub left_name: String,
+    pub right_name: String,
+    pub left: UnitCaps,
+    pub right: UnitCaps,
+    /// Only ranks whose equipments differ
+    pub equipments: Vec<RankEquipments>,
+    /// Power in the max state of each server, see [`UnitCache::max_state`]
+    pub left_power: f64,
+    pub right_power: f64,
+}
+
+impl UnitComparison {
+    pub fn is_same(&self) -> bool {
+        self.left == self.right && self.equipments.is_empty()
+    }
+}
+
+/// Comparison of the units found on two servers
+#[derive(Debug, Default)]
+pub struct ServerComparison {
+    pub left_region: Option<Region>,
+    pub right_region: Option<Region>,
+    /// Level of the max state on each server
+    pub left_level: i32,
+    pub right_level: i32,
+    pub units: Vec<UnitComparison>,
+    pub left_only: Vec<i64>,
+    pub right_only: Vec<i64>,
+    /// Units on both servers that failed to load on either of them
+    pub failed_units: Vec<UnitFailure>,
+}
+
+fn server_name(region: Option<Region>, default: &str) -> String {
+    region.map_or_else(|| default.to_string(), |region| region.to_string())
+}
+
+fn equipment_list(equipments: &Option<Vec<i64>>) -> String {
+    match equipments {
+        Some(equipments) => format!("{:?}", equipments),
+        None => "-".to_string(),
+    }
+}
+
+impl fmt::Display for ServerComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let left = server_name(self.left_region, "left");
+        let right = server_name(self.right_region, "right");
+        writeln!(
+            f,
+            "{} (lv {}) / {} (lv {}): {} units on both",
+            left,
+            self.left_level,
+            right,
+            self.right_level,
+            self.units.len() + self.failed_units.len()
+        )?;
+
+        for unit in self.units.iter() {
+            let left_power = unit.left_power.cy_round::<i64>();
+            let right_power = unit.right_power.cy_round::<i64>();
+            writeln!(
+                f,
+                "{} {} / {}: power {} / {} ({:+})",
+                unit.unit_id,
+                unit.left_name,
+                unit.right_name,
+                left_power,
+                right_power,
+                right_power - left_power
+            )?;
+            if unit.is_same() {
+                continue;
+            }
+
+            if unit.left != unit.right {
+                writeln!(f, "  {}: {}", left, unit.left)?;
+                writeln!(f, "  {}: {}", right, unit.right)?;
+            }
+            for rank in unit.equipments.iter() {
+                writeln!(
+                    f,
+                    "  rank {} equipments {} / {}",
+                    rank.rank,
+                    equipment_list(&rank.left),
+                    equipment_list(&rank.right)
+                )?;
+            }
+        }
+
+        if !self.left_only.is_empty() {
+            writeln!(f, "Only on {}: {:?}", left, self.left_only)?;
+        }
+        if !self.right_only.is_empty() {
+            writeln!(f, "Only on {}: {:?}", right, self.right_only)?;
+        }
+        if !self.failed_units.is_empty() {
+            writeln!(f, "Failed units:")?;
+            for unit in self.failed_units.iter() {
+                writeln!(f, "  {}", unit)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn rank_equipments(left: &UnitCache, right: &UnitCache) -> Vec<RankEquipments> {
+    let equipments = |cache: &UnitCache, rank: usize| {
+        cache.promotion.get(rank).map(|promotion| {
+            promotion
+                .equipments
+                .iter()
+                .map(|equipment| equipment.as_ref().map_or(999999, |e| e.id))
+                .collect::<Vec<i64>>()
+        })
+    };
+
+    (0..left.promotion.len().max(right.promotion.len()))
+        .map(|rank| RankEquipments {
+            rank: rank + 1,
+            left: equipments(left, rank),
+            right: equipments(right, rank),
+        })
+        .filter(|rank| rank.left != rank.right)
+        .collect()
+}
+
+/// Compare the units of two servers, matched by unit id
+///
+/// Each server is taken at its own level cap, so `left_level` and
+/// `right_level` usually differ. Units that fail to load are listed in
+/// [`ServerComparison::failed_units`].
+pub async fn compare_servers(
+    left: &DataManager,
+    left_level: i32,
+    right: &DataManager,
+    right_level: i32,
+) -> Result<ServerComparison, Error> {
+    let left_names = left.unit_names().await?;
+    let right_names = right.unit_names().await?;
+
+    let left_ids = left.unit_ids().await?;
+    let right_ids: HashSet<i64> = right.unit_ids().await?.into_iter().collect();
+
+    let mut comparison = ServerComparison {
+        left_region: left.region(),
+        right_region: right.region(),
+        left_level,
+        right_level,
+        ..Default::default()
+    };
+
+    for unit_id in left_ids.iter().copied() {
+        if !right_ids.contains(&unit_id) {
+            comparison.left_only.push(unit_id);
+            continue;
+        }
+
+        let left_name = left_names.get(&unit_id).cloned().unwrap_or_default();
+        let right_name = right_names.get(&unit_id).cloned().unwrap_or_default();
+        let left_unit = left.max_power(unit_id, left_level).await;
+        let right_unit = right.max_power(unit_id, right_level).await;
+        match (left_unit, right_unit) {
+            (Ok((left_cache, left_power)), Ok((right_cache, right_power))) => {
+                comparison.units.push(UnitComparison {
+                    unit_id,
+                    left_name,
+                    right_name,
+                    left: UnitCaps::of(&left_cache),
+                    right: UnitCaps::of(&right_cache),
+                    equipments: rank_equipments(&left_cache, &right_cache),
+                    left_power,
+                    right_power,
+                })
+            }
+            (Err(error), _) => comparison.failed_units.push(UnitFailure {
+                unit_id,
+                unit_name: left_name,
+                database: server_name(left.region(), "left"),
+                error,
+            }),
+            (_, Err(error)) => comparison.failed_units.push(UnitFailure {
+                unit_id,
+                unit_name: left_name,
+                database: server_name(right.region(), "right"),
+                error,
+            }),
+        }
+    }
+
+    let left_ids: HashSet<i64> = left_ids.into_iter().collect();
+    comparison.right_only = right_ids.difference(&left_ids).copied().collect();
+    comparison.right_only.sort_unstable();
+
+    Ok(comparison)
+}
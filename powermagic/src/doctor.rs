@@ -0,0 +1,606 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use sqlx::sqlite::{SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+
+use crate::error::Error;
+use crate::model::TABLES;
+use crate::region::{Region, Schema};
+
+/// Something in the database the loaders would fail or panic on
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub table: &'static str,
+    pub unit_id: Option<i64>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit_id {
+            Some(unit_id) => write!(f, "[{}] unit {}: {}", self.table, unit_id, self.message),
+            None => write!(f, "[{}] {}", self.table, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub problems: Vec<Problem>,
+    /// Number of units found in `unit_promotion`
+    pub units: usize,
+}
+
+impl DoctorReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn problem(&mut self, table: &'static str, unit_id: Option<i64>, message: String) {
+        self.problems.push(Problem {
+            table,
+            unit_id,
+            message,
+        });
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "No problem found in {} units", self.units);
+        }
+
+        let plural = if self.problems.len() == 1 { "" } else { "s" };
+        writeln!(
+            f,
+            "{} problem{} found in {} units",
+            self.problems.len(),
+            plural,
+            self.units
+        )?;
+        for problem in self.problems.iter() {
+            writeln!(f, "{}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check a database, `connection` is the same as for [`crate::manager::DataManager::new`]
+pub async fn diagnose_database(connection: &str) -> Result<DoctorReport, Error> {
+    diagnose_database_with_schema(connection, Schema::Current).await
+}
+
+/// Check the database of a server, see [`crate::manager::DataManager::new_in_region`]
+pub async fn diagnose_database_in_region(
+    connection: &str,
+    region: Region,
+) -> Result<DoctorReport, Error> {
+    diagnose_database_with_schema(connection, region.profile().schema).await
+}
+
+async fn diagnose_database_with_schema(
+    connection: &str,
+    schema: Schema,
+) -> Result<DoctorReport, Error> {
+    let pool = SqlitePoolOptions::new().connect(connection).await?;
+    let report = diagnose_with_schema(&pool, schema).await;
+    pool.close().await;
+
+    Ok(report)
+}
+
+/// Check everything the loaders assume about the master database
+///
+/// Every check runs even if an earlier one failed, checks that need a missing
+/// table or column are skipped since that is already reported.
+pub async fn diagnose(pool: &sqlx::Pool<sqlx::Sqlite>) -> DoctorReport {
+    diagnose_with_schema(pool, Schema::Current).await
+}
+
+/// Same as [`diagnose`], tables that are optional in `schema` may be missing
+pub async fn diagnose_with_schema(pool: &sqlx::Pool<sqlx::Sqlite>, schema: Schema) -> DoctorReport {
+    let mut doctor = Doctor {
+        pool,
+        schema,
+        report: DoctorReport::default(),
+        complete: HashSet::new(),
+    };
+
+    doctor.check_columns().await;
+    doctor.check_coefficient().await;
+    doctor.check_promotion().await;
+    doctor.check_equipment().await;
+    doctor.check_unique_equipment().await;
+    doctor.check_rarity_6().await;
+    doctor.check_skill().await;
+    doctor.check_story().await;
+
+    doctor.report
+}
+
+struct Doctor<'a> {
+    pool: &'a sqlx::Pool<sqlx::Sqlite>,
+    schema: Schema,
+    report: DoctorReport,
+    /// Tables that have every required column
+    complete: HashSet<&'static str>,
+}
+
+impl Doctor<'_> {
+    async fn check_columns(&mut self) {
+        for table in TABLES.iter() {
+            let columns: HashSet<String> = match sqlx::query(&format!(
+                "SELECT name FROM pragma_table_info('{}')",
+                table.name
+            ))
+            .fetch_all(self.pool)
+            .await
+            {
+                Ok(rows) => rows
+                    .iter()
+                    .filter_map(|row| row.try_get::<String, _>("name").ok())
+                    .collect(),
+                Err(error) => {
+                    self.report.problem(table.name, None, error.to_string());
+                    continue;
+                }
+            };
+
+            if columns.is_empty() {
+                if self.schema.is_optional(table.name) {
+                    continue;
+                }
+                self.report
+                    .problem(table.name, None, "table does not exist".to_string());
+                continue;
+            }
+
+            let missing: Vec<String> = table
+                .required()
+                .into_iter()
+                .map(|(column, _)| column)
+                .filter(|column| !columns.contains(column))
+                .collect();
+            if missing.is_empty() {
+                self.complete.insert(table.name);
+            }
+            for column in missing.into_iter() {
+                self.report
+                    .problem(table.name, None, format!("column {} is missing", column));
+            }
+        }
+    }
+
+    /// Rows of a complete table, `None` if it can't be read
+    async fn rows(&mut self, table: &'static str, query: &str) -> Option<Vec<SqliteRow>> {
+        if !self.complete.contains(table) {
+            return None;
+        }
+
+        match sqlx::query(query).fetch_all(self.pool).await {
+            Ok(rows) => Some(rows),
+            Err(error) => {
+                self.report.problem(table, None, error.to_string());
+                None
+            }
+        }
+    }
+
+    /// Integer column of a row, `NULL` and wrong types are reported
+    fn int(&mut self, table: &'static str, row: &SqliteRow, column: &str) -> Option<i64> {
+        match row.try_get::<Option<i64>, _>(column) {
+            Ok(Some(value)) => Some(value),
+            Ok(None) => {
+                self.report
+                    .problem(table, None, format!("column {} has a NULL value", column));
+                None
+            }
+            Err(error) => {
+                self.report.problem(table, None, error.to_string());
+                None
+            }
+        }
+    }
+
+    async fn ids(&mut self, table: &'static str, column: &str) -> Option<HashSet<i64>> {
+        let rows = self
+            .rows(table, &format!("SELECT {} FROM {}", column, table))
+            .await?;
+
+        Some(
+            rows.iter()
+                .filter_map(|row| self.int(table, row, column))
+                .collect(),
+        )
+    }
+
+    async fn check_coefficient(&mut self) {
+        const TABLE: &str = "unit_status_coefficient";
+        if let Some(rows) = self
+            .rows(TABLE, "SELECT * FROM unit_status_coefficient")
+            .await
+        {
+            if rows.is_empty() {
+                self.report
+                    .problem(TABLE, None, "table has no row".to_string());
+            }
+        }
+    }
+
+    /// Ranks must be 1, 2, 3... as they are indexed by `promotion_level - 1`
+    async fn check_promotion(&mut self) {
+        let promotion = match self
+            .rows(
+                "unit_promotion",
+                "SELECT unit_id, promotion_level FROM unit_promotion",
+            )
+            .await
+        {
+            Some(rows) => self.ranks("unit_promotion", &rows),
+            None => return,
+        };
+        self.report.units = promotion.len();
+
+        for (unit_id, ranks) in promotion.iter() {
+            let contiguous = ranks.iter().copied().eq(1..=ranks.len() as i64);
+            if !contiguous {
+                self.report.problem(
+                    "unit_promotion",
+                    Some(*unit_id),
+                    format!("ranks {:?} are not 1 to {}", ranks, ranks.len()),
+                );
+            }
+        }
+
+        for table in ["unit_promotion_status", "promotion_bonus"] {
+            let rows = match self
+                .rows(
+                    table,
+                    &format!("SELECT unit_id, promotion_level FROM {}", table),
+                )
+                .await
+            {
+                Some(rows) => rows,
+                None => continue,
+            };
+            let status = self.ranks(table, &rows);
+
+            for (unit_id, ranks) in status.iter() {
+                let promotion_ranks = match promotion.get(unit_id) {
+                    Some(promotion_ranks) => promotion_ranks,
+                    None => {
+                        self.report.problem(
+                            table,
+                            Some(*unit_id),
+                            "unit has no unit_promotion row".to_string(),
+                        );
+                        continue;
+                    }
+                };
+
+                for rank in ranks.difference(promotion_ranks) {
+                    self.report.problem(
+                        table,
+                        Some(*unit_id),
+                        format!("rank {} is not in unit_promotion", rank),
+                    );
+                }
+            }
+
+            if table != "unit_promotion_status" {
+                continue;
+            }
+
+            // The first rank has no promotion status
+            for (unit_id, promotion_ranks) in promotion.iter() {
+                let ranks = status.get(unit_id);
+                for rank in promotion_ranks.iter().filter(|rank| **rank > 1) {
+                    if !ranks.is_some_and(|ranks| ranks.contains(rank)) {
+                        self.report.problem(
+                            table,
+                            Some(*unit_id),
+                            format!("rank {} has no unit_promotion_status row", rank),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn ranks(&mut self, table: &'static str, rows: &[SqliteRow]) -> BTreeMap<i64, BTreeSet<i64>> {
+        let mut ranks: BTreeMap<i64, BTreeSet<i64>> = BTreeMap::new();
+        for row in rows.iter() {
+            let unit_id = self.int(table, row, "unit_id");
+            let rank = self.int(table, row, "promotion_level");
+            if let (Some(unit_id), Some(rank)) = (unit_id, rank) {
+                if !ranks.entry(unit_id).or_default().insert(rank) {
+                    self.report.problem(
+                        table,
+                        Some(unit_id),
+                        format!("rank {} has more than one row", rank),
+                    );
+                }
+            }
+        }
+
+        ranks
+    }
+
+    async fn check_equipment(&mut self) {
+        let rows = match self
+            .rows(
+                "unit_promotion",
+                "SELECT * FROM unit_promotion ORDER BY unit_id, promotion_level",
+            )
+            .await
+        {
+            Some(rows) => rows,
+            None => return,
+        };
+        let equipment_data = self.ids("equipment_data", "equipment_id").await;
+        let enhance_rate = self.ids("equipment_enhance_rate", "equipment_id").await;
+
+        let mut reported = HashSet::new();
+        for row in rows.iter() {
+            let unit_id = match self.int("unit_promotion", row, "unit_id") {
+                Some(unit_id) => unit_id,
+                None => continue,
+            };
+            for slot in 1..=6 {
+                let equipment_id =
+                    match self.int("unit_promotion", row, &format!("equip_slot_{}", slot)) {
+                        Some(equipment_id) => equipment_id,
+                        None => continue,
+                    };
+                if equipment_id == 999999 || !reported.insert((unit_id, equipment_id)) {
+                    continue;
+                }
+
+                for (table, ids) in [
+                    ("equipment_data", &equipment_data),
+                    ("equipment_enhance_rate", &enhance_rate),
+                ] {
+                    if ids.as_ref().is_some_and(|ids| !ids.contains(&equipment_id)) {
+                        self.report.problem(
+                            table,
+                            Some(unit_id),
+                            format!("equipment {} does not exist", equipment_id),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    async fn check_unique_equipment(&mut self) {
+        const TABLE: &str = "unit_unique_equip";
+        let rows = match self
+            .rows(TABLE, "SELECT unit_id, equip_id FROM unit_unique_equip")
+            .await
+        {
+            Some(rows) => rows,
+            None => return,
+        };
+        let unique_equipment_data = self.ids("unique_equipment_data", "equipment_id").await;
+        let enhance_rate = self
+            .ids("unique_equipment_enhance_rate", "equipment_id")
+            .await;
+
+        let mut count: BTreeMap<i64, usize> = BTreeMap::new();
+        for row in rows.iter() {
+            let unit_id = self.int(TABLE, row, "unit_id");
+            let equipment_id = self.int(TABLE, row, "equip_id");
+            let (unit_id, equipment_id) = match (unit_id, equipment_id) {
+                (Some(unit_id), Some(equipment_id)) => (unit_id, equipment_id),
+                _ => continue,
+            };
+            *count.entry(unit_id).or_default() += 1;
+
+            for (table, ids) in [
+                ("unique_equipment_data", &unique_equipment_data),
+                ("unique_equipment_enhance_rate", &enhance_rate),
+            ] {
+                if ids.as_ref().is_some_and(|ids| !ids.contains(&equipment_id)) {
+                    self.report.problem(
+                        table,
+                        Some(unit_id),
+                        format!("unique equipment {} does not exist", equipment_id),
+                    );
+                }
+            }
+        }
+
+        for (unit_id, count) in count.into_iter().filter(|(_, count)| *count > 1) {
+            self.report.problem(
+                TABLE,
+                Some(unit_id),
+                format!("unit has {} unique equipments, expected at most 1", count),
+            );
+        }
+    }
+
+    /// Each slot must have levels 1, 2, 3... as they are indexed by `level - 1`
+    async fn check_rarity_6(&mut self) {
+        const TABLE: &str = "unlock_rarity_6";
+        let rows = match self
+            .rows(
+                TABLE,
+                "SELECT unit_id, slot_id, unlock_level FROM unlock_rarity_6 WHERE unlock_level != 0",
+            )
+            .await
+        {
+            Some(rows) => rows,
+            None => return,
+        };
+
+        let mut slots: BTreeMap<i64, BTreeMap<i64, BTreeSet<i64>>> = BTreeMap::new();
+        for row in rows.iter() {
+            let unit_id = self.int(TABLE, row, "unit_id");
+            let slot_id = self.int(TABLE, row, "slot_id");
+            let level = self.int(TABLE, row, "unlock_level");
+            if let (Some(unit_id), Some(slot_id), Some(level)) = (unit_id, slot_id, level) {
+                slots
+                    .entry(unit_id)
+                    .or_default()
+                    .entry(slot_id)
+                    .or_default()
+                    .insert(level);
+            }
+        }
+
+        for (unit_id, slots) in slots.iter() {
+            for slot_id in slots.keys().filter(|slot_id| !(1..=3).contains(*slot_id)) {
+                self.report.problem(
+                    TABLE,
+                    Some(*unit_id),
+                    format!("slot {} is out of 1-3 range", slot_id),
+                );
+            }
+
+            for slot_id in 1..=3 {
+                match slots.get(&slot_id) {
+                    None => self.report.problem(
+                        TABLE,
+                        Some(*unit_id),
+                        format!("slot {} has no row", slot_id),
+                    ),
+                    Some(levels) if !levels.iter().copied().eq(1..=levels.len() as i64) => {
+                        self.report.problem(
+                            TABLE,
+                            Some(*unit_id),
+                            format!(
+                                "slot {} levels {:?} are not 1 to {}",
+                                slot_id,
+                                levels,
+                                levels.len()
+                            ),
+                        )
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    async fn check_skill(&mut self) {
+        let units = match self.ids("unit_promotion", "unit_id").await {
+            Some(units) => units,
+            None => return,
+        };
+        let skill = match self.ids("unit_skill_data", "unit_id").await {
+            Some(skill) => skill,
+            None => return,
+        };
+
+        let mut missing: Vec<i64> = units.difference(&skill).copied().collect();
+        missing.sort_unstable();
+        for unit_id in missing {
+            self.report.problem(
+                "unit_skill_data",
+                Some(unit_id),
+                "unit has no skill data".to_string(),
+            );
+        }
+    }
+
+    /// Stories are grouped by `story_id / 1000`, and the first watched ones
+    /// of a group give their status
+    async fn check_story(&mut self) {
+        const TABLE: &str = "chara_story_status";
+        let rows = match self
+            .rows(TABLE, "SELECT * FROM chara_story_status ORDER BY story_id")
+            .await
+        {
+            Some(rows) => rows,
+            None => return,
+        };
+
+        let mut groups: BTreeMap<i64, Vec<(i64, Vec<i64>)>> = BTreeMap::new();
+        for row in rows.iter() {
+            let story_id = match self.int(TABLE, row, "story_id") {
+                Some(story_id) => story_id,
+                None => continue,
+            };
+
+            for i in 1..=5 {
+                let status_type = self.int(TABLE, row, &format!("status_type_{}", i));
+                if status_type.is_some_and(|status_type| !(0..=17).contains(&status_type)) {
+                    self.report.problem(
+                        TABLE,
+                        None,
+                        format!(
+                            "story {} has status type {} out of 1-17 range",
+                            story_id,
+                            status_type.unwrap()
+                        ),
+                    );
+                }
+            }
+
+            let chara_id: Vec<i64> = (1..=10)
+                .filter_map(|i| self.int(TABLE, row, &format!("chara_id_{}", i)))
+                .filter(|chara_id| *chara_id != 0)
+                .collect();
+            if chara_id.is_empty() {
+                self.report
+                    .problem(TABLE, None, format!("story {} has no chara", story_id));
+            }
+
+            if story_id < 1000 {
+                self.report.problem(
+                    TABLE,
+                    None,
+                    format!(
+                        "story {} has no group, expected group * 1000 + index",
+                        story_id
+                    ),
+                );
+                continue;
+            }
+
+            groups
+                .entry(story_id / 1000)
+                .or_default()
+                .push((story_id, chara_id));
+        }
+
+        for (story_group_id, stories) in groups.iter() {
+            let (first_story_id, first_chara_id) = &stories[0];
+            for (story_id, chara_id) in stories.iter().skip(1) {
+                let mut chara_id = chara_id.clone();
+                let mut first_chara_id = first_chara_id.clone();
+                chara_id.sort_unstable();
+                first_chara_id.sort_unstable();
+                if chara_id != first_chara_id {
+                    self.report.problem(
+                        TABLE,
+                        None,
+                        format!(
+                            "story {} has charas {:?}, but story {} of the same group has {:?}",
+                            story_id, chara_id, first_story_id, first_chara_id
+                        ),
+                    );
+                }
+            }
+
+            let index: Vec<i64> = stories
+                .iter()
+                .map(|(story_id, _)| story_id % 1000)
+                .collect();
+            let contiguous = index.windows(2).all(|pair| pair[1] == pair[0] + 1);
+            if !contiguous {
+                self.report.problem(
+                    TABLE,
+                    None,
+                    format!(
+                        "story group {} has gaps in its stories {:?}",
+                        story_group_id, index
+                    ),
+                );
+            }
+        }
+    }
+}
@@ -1,7 +1,215 @@
 use thiserror::Error;
 
+pub use crate::source::DumpError;
+pub use powermagic_core::error::{CalculationError, SnapshotError, StateError};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Every error of the crate, grouped by what went wrong
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("Equip {0} does not exist")]
-    EquipNotFound(i64),
-}
\ No newline at end of file
+    /// The database can't be opened or queried
+    #[error("Database error: {0}")]
+    Database(sqlx::Error),
+    /// The database doesn't have the tables or columns the loaders read
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+    /// Rows are missing or contradict each other
+    #[error(transparent)]
+    Data(#[from] DataError),
+    /// A unit state doesn't fit its unit
+    #[error(transparent)]
+    State(#[from] StateError),
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+    #[error(transparent)]
+    Snapshot(#[from] SnapshotError),
+    #[error(transparent)]
+    Dump(#[from] DumpError),
+}
+
+impl Error {
+    /// Error of a query on `table`, with the table added to schema errors
+    pub(crate) fn from_query(table: &str, error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::ColumnNotFound(column) if !column.contains('.') => {
+                SchemaError::ColumnNotFound {
+                    table: table.to_string(),
+                    column,
+                }
+                .into()
+            }
+            sqlx::Error::Database(database) if database.message().starts_with("no such table") => {
+                // A query can join several tables, the message names the missing one
+                let table = database
+                    .message()
+                    .strip_prefix("no such table: ")
+                    .unwrap_or(table);
+                SchemaError::TableNotFound {
+                    table: table.to_string(),
+                }
+                .into()
+            }
+            error => error.into(),
+        }
+    }
+
+    /// Add the unit to data errors raised while loading it
+    pub(crate) fn in_unit(self, unit_id: i64) -> Self {
+        match self {
+            Error::Data(error) => Error::Data(error.in_unit(unit_id)),
+            error => error,
+        }
+    }
+
+    /// Id of the unit the error is about, if any
+    pub fn unit_id(&self) -> Option<i64> {
+        match self {
+            Error::Data(error) => error.unit_id(),
+            Error::State(error) => Some(error.unit_id()),
+            Error::Calculation(CalculationError::NonFinitePower { unit_id, .. }) => Some(*unit_id),
+            _ => None,
+        }
+    }
+}
+
+/// Split `table.column`, as written by the row readers of [`crate::model`]
+fn split_column(name: &str) -> Option<(String, String)> {
+    let (table, column) = name.split_once('.')?;
+    Some((table.to_string(), column.to_string()))
+}
+
+impl From<powermagic_core::error::Error> for Error {
+    fn from(error: powermagic_core::error::Error) -> Self {
+        match error {
+            powermagic_core::error::Error::State(error) => Error::State(error),
+            powermagic_core::error::Error::Calculation(error) => Error::Calculation(error),
+            powermagic_core::error::Error::Snapshot(error) => Error::Snapshot(error),
+        }
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::ColumnNotFound(name) => match split_column(&name) {
+                Some((table, column)) => SchemaError::ColumnNotFound { table, column }.into(),
+                None => Error::Database(sqlx::Error::ColumnNotFound(name)),
+            },
+            sqlx::Error::ColumnDecode { index, source } => match split_column(&index) {
+                Some((table, column)) => SchemaError::InvalidColumn {
+                    table,
+                    column,
+                    message: source.to_string(),
+                }
+                .into(),
+                None => Error::Database(sqlx::Error::ColumnDecode { index, source }),
+            },
+            error => Error::Database(error),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("Table {table} does not exist")]
+    TableNotFound { table: String },
+    #[error("Column {table}.{column} does not exist")]
+    ColumnNotFound { table: String, column: String },
+    #[error("Column {table}.{column} can't be read: {message}")]
+    InvalidColumn {
+        table: String,
+        column: String,
+        message: String,
+    },
+}
+
+fn of_unit(unit_id: &Option<i64>) -> String {
+    match unit_id {
+        Some(unit_id) => format!(" of unit {}", unit_id),
+        None => String::new(),
+    }
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum DataError {
+    #[error("Unit {unit_id} does not exist")]
+    UnitNotFound { unit_id: i64 },
+    #[error("Unit named {name} does not exist")]
+    UnitNameNotFound { name: String },
+    #[error("Equipment {equipment_id}{} does not exist", of_unit(.unit_id))]
+    EquipmentNotFound {
+        unit_id: Option<i64>,
+        equipment_id: i64,
+    },
+    #[error("Enhance rate of equipment {equipment_id}{} does not exist", of_unit(.unit_id))]
+    EquipmentEnhanceRateNotFound {
+        unit_id: Option<i64>,
+        equipment_id: i64,
+    },
+    #[error("Enhance data of unique equipment {equipment_id}{} does not exist", of_unit(.unit_id))]
+    UniqueEquipmentEnhanceDataNotFound {
+        unit_id: Option<i64>,
+        equipment_id: i64,
+    },
+    #[error("Unit {unit_id} has more than one unique equipment")]
+    MoreThanOneUniqueEquipment { unit_id: i64 },
+    #[error("Promotion data of unit {unit_id} for rank {rank} is not found")]
+    MissingPromotion { unit_id: i64, rank: i64 },
+    #[error("Promotion data of unit {unit_id} at rank {rank} is for rank {found}")]
+    PromotionMismatch { unit_id: i64, rank: i64, found: i64 },
+    #[error("Rarity 6 data of unit {unit_id} for slot {slot} is not found")]
+    Rarity6SlotNotFound { unit_id: i64, slot: usize },
+    #[error("Rarity 6 slot {slot} of unit {unit_id} is out of 1-3 range")]
+    Rarity6SlotOutOfRange { unit_id: i64, slot: i64 },
+    #[error("Unit status coefficient does not exist")]
+    StatusCoefficientNotFound,
+    #[error("Unit status coefficient {coefficient_id} does not exist")]
+    StatusCoefficientIdNotFound { coefficient_id: i64 },
+    #[error("Level cap does not exist, experience_unit has no row")]
+    MaxUnitLevelNotFound,
+}
+
+impl DataError {
+    fn in_unit(self, unit: i64) -> Self {
+        match self {
+            DataError::EquipmentNotFound {
+                unit_id: None,
+                equipment_id,
+            } => DataError::EquipmentNotFound {
+                unit_id: Some(unit),
+                equipment_id,
+            },
+            DataError::EquipmentEnhanceRateNotFound {
+                unit_id: None,
+                equipment_id,
+            } => DataError::EquipmentEnhanceRateNotFound {
+                unit_id: Some(unit),
+                equipment_id,
+            },
+            DataError::UniqueEquipmentEnhanceDataNotFound {
+                unit_id: None,
+                equipment_id,
+            } => DataError::UniqueEquipmentEnhanceDataNotFound {
+                unit_id: Some(unit),
+                equipment_id,
+            },
+            error => error,
+        }
+    }
+
+    pub fn unit_id(&self) -> Option<i64> {
+        match self {
+            DataError::UnitNotFound { unit_id }
+            | DataError::MoreThanOneUniqueEquipment { unit_id }
+            | DataError::MissingPromotion { unit_id, .. }
+            | DataError::PromotionMismatch { unit_id, .. }
+            | DataError::Rarity6SlotNotFound { unit_id, .. }
+            | DataError::Rarity6SlotOutOfRange { unit_id, .. } => Some(*unit_id),
+            DataError::EquipmentNotFound { unit_id, .. }
+            | DataError::EquipmentEnhanceRateNotFound { unit_id, .. }
+            | DataError::UniqueEquipmentEnhanceDataNotFound { unit_id, .. } => *unit_id,
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "sqlite")]
+pub mod changelog;
+#[cfg(feature = "sqlite")]
+pub mod compare;
+#[cfg(feature = "sqlite")]
+pub mod doctor;
+#[cfg(feature = "sqlite")]
+pub mod error;
+#[cfg(feature = "sqlite")]
+pub mod manager;
+#[cfg(feature = "sqlite")]
+pub mod model;
+#[cfg(feature = "sqlite")]
+pub mod region;
+#[cfg(feature = "sqlite")]
+pub mod registry;
+#[cfg(feature = "sqlite")]
+pub mod reload;
+#[cfg(feature = "sqlite")]
+pub mod source;
+#[cfg(feature = "sqlite")]
+pub mod timeline;
+pub mod unit;
+
+#[cfg(not(feature = "sqlite"))]
+pub use powermagic_core::{error, model};
@@ -1,24 +1,23 @@
-pub mod error;
-mod manager;
-mod model;
-mod unit;
-
-use sqlx::sqlite::SqlitePoolOptions;
-use unit::*;
+use powermagic::manager;
+use powermagic::region::Region;
+use powermagic::unit::*;
 
+/// Usage: powermagic [database] [region] [unit name]
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let pool = SqlitePoolOptions::new().connect("powermagic.db").await?;
-    let data_manager = manager::DataManager::with_pool(pool.clone()).await?;
+    let mut args = std::env::args().skip(1);
+    let database = args.next().unwrap_or_else(|| "powermagic.db".to_string());
+    let region: Region = match args.next() {
+        Some(region) => region.parse()?,
+        None => Region::Tw,
+    };
+    let name = args.next().unwrap_or_else(|| "優衣（公主）".to_string());
 
-    let unit_data =
-        sqlx::query_as::<_, model::UnitData>("SELECT * FROM unit_data WHERE unit_name == $1")
-            .bind("優衣（公主）")
-            .fetch_one(&pool)
-            .await?;
+    let data_manager = manager::DataManager::new_in_region(&database, region).await?;
+    let unit_id = data_manager.unit_id_by_name(&name).await?;
 
-    let unit_cache = data_manager.unit_cache(unit_data.unit_id).await?;
-    let mut calculator = UnitCalculator::new(&unit_cache)
+    let unit_cache = data_manager.unit_cache(unit_id).await?;
+    let calculator = UnitCalculator::new(&unit_cache)
         .set_star(5)
         .set_all_level(190)
         .set_rank(20)
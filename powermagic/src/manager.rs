@@ -1,14 +1,27 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::error::{DataError, Error};
 use crate::model;
+use crate::region::{CoefficientRow, Region, RegionProfile};
+use crate::registry::UnitCacheRegistry;
+use crate::source::{DataSource, SqliteSource};
+use crate::timeline::GameTime;
+use crate::unit::PreloadedData;
 use sqlx::sqlite::SqlitePoolOptions;
-use tokio_stream::StreamExt;
 
 pub struct DataManager {
-    pub(crate) pool: sqlx::Pool<sqlx::Sqlite>,
+    pub(crate) source: Arc<dyn DataSource>,
+    /// Coefficient given to new unit caches
     pub status_coefficient: model::UnitStatusCoefficient,
+    /// Every row of `unit_status_coefficient`
+    pub status_coefficients: Vec<model::UnitStatusCoefficient>,
     pub equipment_enhance_data: HashMap<i64, Vec<model::EquipmentEnhanceData>>,
     pub unique_equipment_enhance_data: HashMap<i64, Vec<model::UniqueEquipmentEnhanceData>>,
+    pub(crate) preloaded: Option<PreloadedData>,
+    pub(crate) registry: UnitCacheRegistry,
+    pub(crate) profile: RegionProfile,
+    pub(crate) as_of: Option<GameTime>,
 }
 
 impl std::fmt::Debug for DataManager {
@@ -18,58 +31,61 @@ impl std::fmt::Debug for DataManager {
             format!("[{}]", &self.unique_equipment_enhance_data.len());
         f.debug_struct("DataManager")
             .field("status_coefficient", &self.status_coefficient)
+            .field("status_coefficients", &self.status_coefficients.len())
             .field("equipment_enhance_data", &equipment_enhance_data)
             .field(
                 "unique_equipment_enhance_data",
                 &unique_equipment_enhance_data,
             )
+            .field("preloaded", &self.preloaded.is_some())
+            .field("registry", &self.registry.len())
+            .field("profile", &self.profile)
+            .field("as_of", &self.as_of)
             .finish()
     }
 }
 
-#[derive(thiserror::Error, Debug)]
-pub enum DataManagerError {
-    #[error("Unlock rarity 6 data for slot {0} is not found")]
-    UnlockRarity6NotFound(usize),
-    #[error("Unlock rarity 6 slot {0} is out of 1-3 range")]
-    UnlockRarity6SlotOutOfRange(i64),
-    #[error("SQLite error {0}")]
-    Sqlite(#[from] sqlx::Error),
-    #[error("Equip {0} does not exist")]
-    EquipNotFound(i64),
-    #[error("Equip enhance data for {0} does not exist")]
-    EquipmentEnhanceData(i64),
-    #[error("Equip enhance data for {0} does not exist")]
-    UniqueEquipmentEnhanceData(i64),
-    #[error("Unit {0} has more than one unique equipment")]
-    InvalidUniqueEquipMoreThanOne(i64),
-}
-
 // Constructor
 impl DataManager {
-    pub async fn new(connection: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(connection: &str) -> Result<Self, Error> {
         let pool = SqlitePoolOptions::new().connect(connection).await?;
         Self::with_pool(pool).await
     }
 
-    pub async fn with_pool(
+    pub async fn with_pool(pool: sqlx::Pool<sqlx::Sqlite>) -> Result<Self, Error> {
+        Self::with_source(Arc::new(SqliteSource::new(pool))).await
+    }
+
+    /// Open the database of a server
+    pub async fn new_in_region(connection: &str, region: Region) -> Result<Self, Error> {
+        let pool = SqlitePoolOptions::new().connect(connection).await?;
+        Self::with_pool_in_region(pool, region).await
+    }
+
+    pub async fn with_pool_in_region(
         pool: sqlx::Pool<sqlx::Sqlite>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let status_coefficient = sqlx::query_as::<_, model::UnitStatusCoefficient>(
-            "SELECT * FROM unit_status_coefficient",
-        )
-        .fetch_one(&pool)
-        .await?;
+        region: Region,
+    ) -> Result<Self, Error> {
+        let profile = region.profile();
+        let source = SqliteSource::with_schema(pool, profile.schema);
+        Self::with_profile(Arc::new(source), profile).await
+    }
 
-        let mut equipment_enhance_data = HashMap::new();
-        let mut query = sqlx::query_as::<_, model::EquipmentEnhanceData>(
-            "SELECT * FROM equipment_enhance_data ORDER BY promotion_level, equipment_enhance_level",
-        )
-        .fetch(&pool);
+    pub async fn with_source(source: Arc<dyn DataSource>) -> Result<Self, Error> {
+        Self::with_profile(source, RegionProfile::default()).await
+    }
+
+    pub async fn with_profile(
+        source: Arc<dyn DataSource>,
+        profile: RegionProfile,
+    ) -> Result<Self, Error> {
+        let status_coefficients = source.unit_status_coefficient().await?;
+        let status_coefficient = profile.coefficient.select(&status_coefficients)?;
 
+        let mut equipment_enhance_data = HashMap::new();
         equipment_enhance_data.insert(0, vec![]);
 
-        while let Some(row) = query.try_next().await? {
+        for row in source.equipment_enhance_data().await?.into_iter() {
             equipment_enhance_data
                 .entry(row.promotion_level)
                 .or_insert_with(Vec::new)
@@ -77,12 +93,7 @@ impl DataManager {
         }
 
         let mut unique_equipment_enhance_data = HashMap::new();
-        let mut query = sqlx::query_as::<_, model::UniqueEquipmentEnhanceData>(
-            "SELECT * FROM unique_equipment_enhance_data ORDER BY equip_slot, enhance_level",
-        )
-        .fetch(&pool);
-
-        while let Some(row) = query.try_next().await? {
+        for row in source.unique_equipment_enhance_data().await?.into_iter() {
             unique_equipment_enhance_data
                 .entry(row.equip_slot)
                 .or_insert_with(Vec::new)
@@ -90,10 +101,130 @@ impl DataManager {
         }
 
         Ok(Self {
-            pool: pool.clone(),
+            source,
             status_coefficient,
+            status_coefficients,
             equipment_enhance_data,
             unique_equipment_enhance_data,
+            preloaded: None,
+            registry: UnitCacheRegistry::default(),
+            profile,
+            as_of: None,
         })
     }
+
+    pub fn source(&self) -> &Arc<dyn DataSource> {
+        &self.source
+    }
+
+    pub fn profile(&self) -> &RegionProfile {
+        &self.profile
+    }
+
+    pub fn region(&self) -> Option<Region> {
+        self.profile.region
+    }
+
+    /// Level cap of the server, the highest level of `experience_unit`
+    pub async fn max_unit_level(&self) -> Result<i32, Error> {
+        self.source.max_unit_level().await
+    }
+}
+
+// Status coefficient
+impl DataManager {
+    pub fn status_coefficient_by_id(
+        &self,
+        coefficient_id: i64,
+    ) -> Option<&model::UnitStatusCoefficient> {
+        self.status_coefficients
+            .iter()
+            .find(|row| row.coefficient_id == coefficient_id)
+    }
+
+    /// Row with the largest `coefficient_id`
+    pub fn latest_status_coefficient(&self) -> Option<&model::UnitStatusCoefficient> {
+        self.status_coefficients
+            .iter()
+            .max_by_key(|row| row.coefficient_id)
+    }
+
+    /// Use another row for new unit caches, shared unit caches are dropped
+    ///
+    /// Caches already built keep their coefficient, see
+    /// [`crate::unit::UnitCache::set_status_coefficient`] to change it.
+    pub fn select_status_coefficient(
+        &mut self,
+        coefficient: CoefficientRow,
+    ) -> Result<(), Error> {
+        self.status_coefficient = coefficient.select(&self.status_coefficients)?;
+        self.profile.coefficient = coefficient;
+        self.registry.clear();
+
+        Ok(())
+    }
+}
+
+// Name lookup
+impl DataManager {
+    /// Units whose name matches `name`, written the way of the region
+    pub async fn find_units_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Vec<model::UnitData>, Error> {
+        let names = self.profile.names;
+        let name = names.normalize(name);
+        let units = self
+            .source
+            .unit_data(None)
+            .await?
+            .into_iter()
+            .filter(|unit| names.normalize(&unit.unit_name) == name)
+            .filter(|unit| self.as_of.is_none_or(|time| unit.is_available_at(time)))
+            .collect();
+
+        Ok(units)
+    }
+
+    /// Units whose name contains `query`, in order of unit id
+    pub async fn search_units(&self, query: &str) -> Result<Vec<model::UnitData>, Error> {
+        let names = self.profile.names;
+        let query = names.normalize(query);
+        let mut units: Vec<model::UnitData> = self
+            .source
+            .unit_data(None)
+            .await?
+            .into_iter()
+            .filter(|unit| names.normalize(&unit.unit_name).contains(&query))
+            .filter(|unit| self.as_of.is_none_or(|time| unit.is_available_at(time)))
+            .collect();
+        units.sort_by_key(|unit| unit.unit_id);
+
+        Ok(units)
+    }
+
+    /// Name of every unit by id, as written in the database
+    pub async fn unit_names(&self) -> Result<HashMap<i64, String>, Error> {
+        Ok(self
+            .source
+            .unit_data(None)
+            .await?
+            .into_iter()
+            .map(|unit| (unit.unit_id, unit.unit_name))
+            .collect())
+    }
+
+    /// Id of the first unit named `name`
+    pub async fn unit_id_by_name(&self, name: &str) -> Result<i64, Error> {
+        self.find_units_by_name(name)
+            .await?
+            .first()
+            .map(|unit| unit.unit_id)
+            .ok_or_else(|| {
+                DataError::UnitNameNotFound {
+                    name: name.to_string(),
+                }
+                .into()
+            })
+    }
 }
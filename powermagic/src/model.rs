@@ -1,36 +1,81 @@
 use derive_macro::impl_status;
-use sqlx::Row;
+use powermagic_core::unit::STATUS_NAMES;
+use sqlx::sqlite::{Sqlite, SqliteRow};
+use sqlx::Row as _;
+
+pub use powermagic_core::model::{CharaStoryStatus, UnitStatusCoefficient};
+
+/// Get a column by name, errors name the table and the column
+fn column<'r, T>(row: &'r SqliteRow, table: &str, column: &str) -> Result<T, sqlx::Error>
+where
+    T: sqlx::Decode<'r, Sqlite> + sqlx::Type<Sqlite>,
+{
+    row.try_get(column).map_err(|error| match error {
+        sqlx::Error::ColumnNotFound(_) => {
+            sqlx::Error::ColumnNotFound(format!("{}.{}", table, column))
+        }
+        sqlx::Error::ColumnDecode { source, .. } => sqlx::Error::ColumnDecode {
+            index: format!("{}.{}", table, column),
+            source,
+        },
+        error => error,
+    })
+}
+
+/// Get a column added by a later game version, `None` if the table doesn't
+/// have it yet
+fn optional_column<'r, T>(
+    row: &'r SqliteRow,
+    table: &str,
+    name: &str,
+) -> Result<Option<T>, sqlx::Error>
+where
+    T: sqlx::Decode<'r, Sqlite> + sqlx::Type<Sqlite>,
+{
+    match column::<Option<T>>(row, table, name) {
+        Err(sqlx::Error::ColumnNotFound(_)) => Ok(None),
+        result => result,
+    }
+}
 
 // use crate::data::StatusParam;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
-#[impl_status("{}_coefficient")]
-pub struct UnitStatusCoefficient {
-    pub coefficient_id: i64,
-    pub hp_coefficient: f64,
-    pub atk_coefficient: f64,
-    pub magic_str_coefficient: f64,
-    pub def_coefficient: f64,
-    pub magic_def_coefficient: f64,
-    pub physical_critical_coefficient: f64,
-    pub magic_critical_coefficient: f64,
-    pub wave_hp_recovery_coefficient: f64,
-    pub wave_energy_recovery_coefficient: f64,
-    pub dodge_coefficient: f64,
-    pub physical_penetrate_coefficient: f64,
-    pub magic_penetrate_coefficient: f64,
-    pub life_steal_coefficient: f64,
-    pub hp_recovery_rate_coefficient: f64,
-    pub energy_recovery_rate_coefficient: f64,
-    pub energy_reduce_rate_coefficient: f64,
-    pub skill_lv_coefficient: f64,
-    pub exskill_evolution_coefficient: i64,
-    pub overall_coefficient: f64,
-    pub accuracy_coefficient: f64,
-    pub skill1_evolution_coefficient: i64,
-    pub skill1_evolution_slv_coefficient: f64,
-    pub ub_evolution_coefficient: i64,
-    pub ub_evolution_slv_coefficient: f64,
+/// Row of a type defined in `powermagic_core`, which can't implement
+/// `FromRow` itself
+pub(crate) struct Row<T>(pub T);
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Row<UnitStatusCoefficient> {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        const TABLE: &str = "unit_status_coefficient";
+
+        Ok(Row(UnitStatusCoefficient {
+            coefficient_id: column(row, TABLE, "coefficient_id")?,
+            hp_coefficient: column(row, TABLE, "hp_coefficient")?,
+            atk_coefficient: column(row, TABLE, "atk_coefficient")?,
+            magic_str_coefficient: column(row, TABLE, "magic_str_coefficient")?,
+            def_coefficient: column(row, TABLE, "def_coefficient")?,
+            magic_def_coefficient: column(row, TABLE, "magic_def_coefficient")?,
+            physical_critical_coefficient: column(row, TABLE, "physical_critical_coefficient")?,
+            magic_critical_coefficient: column(row, TABLE, "magic_critical_coefficient")?,
+            wave_hp_recovery_coefficient: column(row, TABLE, "wave_hp_recovery_coefficient")?,
+            wave_energy_recovery_coefficient: column(row, TABLE, "wave_energy_recovery_coefficient")?,
+            dodge_coefficient: column(row, TABLE, "dodge_coefficient")?,
+            physical_penetrate_coefficient: column(row, TABLE, "physical_penetrate_coefficient")?,
+            magic_penetrate_coefficient: column(row, TABLE, "magic_penetrate_coefficient")?,
+            life_steal_coefficient: column(row, TABLE, "life_steal_coefficient")?,
+            hp_recovery_rate_coefficient: column(row, TABLE, "hp_recovery_rate_coefficient")?,
+            energy_recovery_rate_coefficient: column(row, TABLE, "energy_recovery_rate_coefficient")?,
+            energy_reduce_rate_coefficient: column(row, TABLE, "energy_reduce_rate_coefficient")?,
+            skill_lv_coefficient: column(row, TABLE, "skill_lv_coefficient")?,
+            exskill_evolution_coefficient: column(row, TABLE, "exskill_evolution_coefficient")?,
+            overall_coefficient: column(row, TABLE, "overall_coefficient")?,
+            accuracy_coefficient: column(row, TABLE, "accuracy_coefficient")?,
+            skill1_evolution_coefficient: column(row, TABLE, "skill1_evolution_coefficient")?,
+            skill1_evolution_slv_coefficient: column(row, TABLE, "skill1_evolution_slv_coefficient")?,
+            ub_evolution_coefficient: column(row, TABLE, "ub_evolution_coefficient")?,
+            ub_evolution_slv_coefficient: column(row, TABLE, "ub_evolution_slv_coefficient")?,
+        }))
+    }
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -243,7 +288,7 @@ pub struct EquipmentEnhanceRate {
 }
 
 impl EquipmentEnhanceRate {
-    pub const ENHANCE_LV_OFFSET: i32 = 0;
+    pub const ENHANCE_LV_OFFSET: i32 = powermagic_core::unit::EquipmentCache::ENHANCE_LV_OFFSET;
 }
 
 #[derive(Debug, Clone)]
@@ -261,23 +306,18 @@ pub struct UnitPromotion {
 
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for UnitPromotion {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
-        let len = row.len();
-        if len != 8 {
-            return Err(sqlx::Error::ColumnNotFound(
-                format!("UnitPromotion has {} lines, expected 8", len),
-            ));
-        }
+        const TABLE: &str = "unit_promotion";
 
         Ok(UnitPromotion {
-            unit_id: row.try_get("unit_id")?,
-            promotion_level: row.try_get("promotion_level")?,
+            unit_id: column(row, TABLE, "unit_id")?,
+            promotion_level: column(row, TABLE, "promotion_level")?,
             equip_slot: [
-                row.try_get("equip_slot_1")?,
-                row.try_get("equip_slot_2")?,
-                row.try_get("equip_slot_3")?,
-                row.try_get("equip_slot_4")?,
-                row.try_get("equip_slot_5")?,
-                row.try_get("equip_slot_6")?,
+                column(row, TABLE, "equip_slot_1")?,
+                column(row, TABLE, "equip_slot_2")?,
+                column(row, TABLE, "equip_slot_3")?,
+                column(row, TABLE, "equip_slot_4")?,
+                column(row, TABLE, "equip_slot_5")?,
+                column(row, TABLE, "equip_slot_6")?,
             ],
         })
     }
@@ -359,7 +399,7 @@ pub struct UniqueEquipmentEnhanceRate {
 }
 
 impl UniqueEquipmentEnhanceRate {
-    pub const ENHANCE_LV_OFFSET: i32 = -1;
+    pub const ENHANCE_LV_OFFSET: i32 = powermagic_core::unit::UniqueEquipmentCache::ENHANCE_LV_OFFSET;
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -374,7 +414,8 @@ pub struct UnitSkillData {
     pub unit_id: i64,
     // pub union_burst: i64,
     pub union_burst: [i64; 1],
-    pub union_burst_evolution: i64,
+    /// Not in older databases
+    pub union_burst_evolution: Option<i64>,
     // pub main_skill_1: i64,
     // pub main_skill_2: i64,
     // pub main_skill_3: i64,
@@ -398,7 +439,8 @@ pub struct UnitSkillData {
     // pub ex_skill_evolution_5: i64,
     pub ex_skill: Vec<i64>,
     pub ex_skill_evolution: Vec<i64>,
-    pub sp_union_burst: i64,
+    /// Not in older databases
+    pub sp_union_burst: Option<i64>,
     // pub sp_skill_1: i64,
     // pub sp_skill_2: i64,
     // pub sp_skill_3: i64,
@@ -413,19 +455,12 @@ pub struct UnitSkillData {
     pub sp_skill_evolution: Vec<i64>,
 }
 
-impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for UnitSkillData
-{
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for UnitSkillData {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        const TABLE: &str = "unit_skill_data";
 
-        let len = row.len();
-        if len != 33 {
-            return Err(sqlx::Error::ColumnNotFound(
-                format!("UnitSkillData has {} lines, expected 33", len),
-            ));
-        }
-
-        let unit_id = row.try_get("unit_id")?;
-        let union_burst = [row.try_get("union_burst")?];
+        let unit_id = column(row, TABLE, "unit_id")?;
+        let union_burst = [column(row, TABLE, "union_burst")?];
 
         let trim_zeros = |vector: &mut Vec<i64>| {
             while !vector.is_empty() && *vector.last().unwrap() == 0 {
@@ -433,51 +468,31 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for UnitSkillData
             }
         };
 
-        let mut main_skill = Vec::new();
-        for i in 1..=10 {
-            let skill_id = row.try_get(&*format!("main_skill_{}", i))?;
-            main_skill.push(skill_id);
-        }
-        trim_zeros(&mut main_skill);
-
-        let mut ex_skill = Vec::new();
-        for i in 1..=5 {
-            let skill_id = row.try_get(&*format!("ex_skill_{}", i))?;
-            ex_skill.push(skill_id);
-        }
-        trim_zeros(&mut ex_skill);
-
-        let mut ex_skill_evolution = Vec::new();
-        for i in 1..=5 {
-            let skill_id = row.try_get(&*format!("ex_skill_evolution_{}", i))?;
-            ex_skill_evolution.push(skill_id);
-        }
-        trim_zeros(&mut ex_skill_evolution);
+        // Skill columns added later are read as 0, like an empty skill
+        let skills = |name: &str, count: usize, required: bool| {
+            let mut skills = Vec::new();
+            for i in 1..=count {
+                let name = format!("{}_{}", name, i);
+                let skill_id = if required {
+                    column(row, TABLE, &name)?
+                } else {
+                    optional_column(row, TABLE, &name)?.unwrap_or(0)
+                };
+                skills.push(skill_id);
+            }
+            trim_zeros(&mut skills);
 
-        let sp_union_burst = row.try_get("sp_union_burst")?;
+            Ok::<_, sqlx::Error>(skills)
+        };
 
-        let mut sp_skill = Vec::new();
-        for i in 1..=5 {
-            let skill_id = row.try_get(&*format!("sp_skill_{}", i))?;
-                sp_skill.push(skill_id);
-        }        
-        trim_zeros(&mut sp_skill);
-        
-        let union_burst_evolution = row.try_get("union_burst_evolution")?;
-
-        let mut main_skill_evolution = Vec::new();
-        for i in 1..=2 {
-            let skill_id = row.try_get(&*format!("main_skill_evolution_{}", i))?;
-            main_skill_evolution.push(skill_id);
-        }
-        trim_zeros(&mut main_skill_evolution);
-        
-        let mut sp_skill_evolution = Vec::new();
-        for i in 1..=2 {
-            let skill_id = row.try_get(&*format!("sp_skill_evolution_{}", i))?;
-            sp_skill_evolution.push(skill_id);
-        }
-        trim_zeros(&mut sp_skill_evolution);
+        let main_skill = skills("main_skill", 10, true)?;
+        let ex_skill = skills("ex_skill", 5, true)?;
+        let ex_skill_evolution = skills("ex_skill_evolution", 5, true)?;
+        let sp_union_burst = optional_column(row, TABLE, "sp_union_burst")?;
+        let sp_skill = skills("sp_skill", 5, false)?;
+        let union_burst_evolution = optional_column(row, TABLE, "union_burst_evolution")?;
+        let main_skill_evolution = skills("main_skill_evolution", 2, false)?;
+        let sp_skill_evolution = skills("sp_skill_evolution", 2, false)?;
 
         Ok(UnitSkillData {
             unit_id,
@@ -494,51 +509,17 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for UnitSkillData
     }
 }
 
-
-#[derive(Debug, Clone)]
-pub struct CharaStoryStatus {
-    pub story_id: i64,
-    pub unlock_story_name: String,
-    // pub status_type_1: i64,
-    // pub status_rate_1: i64,
-    // pub status_type_2: i64,
-    // pub status_rate_2: i64,
-    // pub status_type_3: i64,
-    // pub status_rate_3: i64,
-    // pub status_type_4: i64,
-    // pub status_rate_4: i64,
-    // pub status_type_5: i64,
-    // pub status_rate_5: i64,
-    pub status: Vec<(i64, i64)>,
-    // pub chara_id_1: i64,
-    // pub chara_id_2: i64,
-    // pub chara_id_3: i64,
-    // pub chara_id_4: i64,
-    // pub chara_id_5: i64,
-    // pub chara_id_6: i64,
-    // pub chara_id_7: i64,
-    // pub chara_id_8: i64,
-    // pub chara_id_9: i64,
-    // pub chara_id_10: i64,
-    pub chara_id: Vec<i64>,
-}
-
-impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for CharaStoryStatus {
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Row<CharaStoryStatus> {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
-        let len = row.len();
-        if len != 22 {
-            return Err(sqlx::Error::ColumnNotFound(
-                format!("CharaStoryStatus has {} lines, expected 22", len),
-            ));
-        }
+        const TABLE: &str = "chara_story_status";
 
-        let story_id = row.try_get("story_id")?;
-        let unlock_story_name = row.try_get("unlock_story_name")?;
+        let story_id = column(row, TABLE, "story_id")?;
+        let unlock_story_name = column(row, TABLE, "unlock_story_name")?;
 
         let mut status = Vec::new();
         for i in 1..=5 {
-            let status_type = row.try_get(&*format!("status_type_{}", i))?;
-            let status_rate = row.try_get(&*format!("status_rate_{}", i))?;
+            let status_type = column(row, TABLE, &format!("status_type_{}", i))?;
+            let status_rate = column(row, TABLE, &format!("status_rate_{}", i))?;
             if status_type != 0 {
                 status.push((status_type, status_rate));
             }
@@ -546,17 +527,367 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for CharaStoryStatus {
 
         let mut chara_id = Vec::new();
         for i in 1..=10 {
-            let chara_id_i = row.try_get(&*format!("chara_id_{}", i))?;
+            let chara_id_i = column(row, TABLE, &format!("chara_id_{}", i))?;
             if chara_id_i != 0 {
                 chara_id.push(chara_id_i);
             }
         }
 
-        Ok(CharaStoryStatus {
+        Ok(Row(CharaStoryStatus {
             story_id,
             unlock_story_name,
             status,
             chara_id,
-        })
+        }))
+    }
+}
+
+/// Type a column is decoded as by the row readers of this module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColumnType {
+    /// Declared type in SQLite, its affinity converts the values stored
+    pub fn sql_name(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Real => "REAL",
+            ColumnType::Text => "TEXT",
+        }
+    }
+}
+
+/// Columns the row readers of this module decode from a table
+#[derive(Debug)]
+pub struct TableColumns {
+    pub name: &'static str,
+    pub columns: &'static [(&'static str, ColumnType)],
+    /// Formats of the status columns, like the arguments of `impl_status`
+    pub status: &'static [&'static str],
+    pub status_type: ColumnType,
+    /// Columns read with `optional_column`, missing from older databases
+    pub optional: &'static [(&'static str, ColumnType)],
+}
+
+impl TableColumns {
+    /// Columns every row reader of the table needs, status columns last
+    pub fn required(&self) -> Vec<(String, ColumnType)> {
+        let status = self.status.iter().flat_map(|format| {
+            STATUS_NAMES
+                .iter()
+                .map(move |status| (format.replace("{}", status), self.status_type))
+        });
+
+        self.columns
+            .iter()
+            .map(|(column, column_type)| (column.to_string(), *column_type))
+            .chain(status)
+            .collect()
+    }
+
+    /// Type of a required or optional column, `None` if it's not read
+    pub fn column_type(&self, column: &str) -> Option<ColumnType> {
+        self.required()
+            .into_iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, column_type)| column_type)
+            .or_else(|| {
+                self.optional
+                    .iter()
+                    .find(|(name, _)| *name == column)
+                    .map(|(_, column_type)| *column_type)
+            })
+    }
+
+    pub fn get(table: &str) -> Option<&'static TableColumns> {
+        TABLES.iter().find(|columns| columns.name == table)
     }
 }
+
+/// Every table read by the loaders, with the columns of the structs above
+pub const TABLES: [TableColumns; 16] = {
+    use ColumnType::{Integer, Real, Text};
+
+    [
+        TableColumns {
+            name: "unit_status_coefficient",
+            columns: &[
+                ("coefficient_id", Integer),
+                ("skill_lv_coefficient", Real),
+                ("exskill_evolution_coefficient", Integer),
+                ("overall_coefficient", Real),
+                ("skill1_evolution_coefficient", Integer),
+                ("skill1_evolution_slv_coefficient", Real),
+                ("ub_evolution_coefficient", Integer),
+                ("ub_evolution_slv_coefficient", Real),
+            ],
+            status: &["{}_coefficient"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unit_data",
+            columns: &[
+                ("unit_id", Integer),
+                ("unit_name", Text),
+                ("kana", Text),
+                ("prefab_id", Integer),
+                ("prefab_id_battle", Integer),
+                ("is_limited", Integer),
+                ("rarity", Integer),
+                ("motion_type", Integer),
+                ("se_type", Integer),
+                ("move_speed", Integer),
+                ("search_area_width", Integer),
+                ("atk_type", Integer),
+                ("normal_atk_cast_time", Real),
+                ("cutin_1", Integer),
+                ("cutin_2", Integer),
+                ("cutin1_star6", Integer),
+                ("cutin2_star6", Integer),
+                ("guild_id", Integer),
+                ("exskill_display", Integer),
+                ("comment", Text),
+                ("only_disp_owned", Integer),
+                ("start_time", Text),
+                ("end_time", Text),
+                ("original_unit_id", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unit_promotion",
+            columns: &[
+                ("unit_id", Integer),
+                ("promotion_level", Integer),
+                ("equip_slot_1", Integer),
+                ("equip_slot_2", Integer),
+                ("equip_slot_3", Integer),
+                ("equip_slot_4", Integer),
+                ("equip_slot_5", Integer),
+                ("equip_slot_6", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unit_promotion_status",
+            columns: &[("unit_id", Integer), ("promotion_level", Integer)],
+            status: &["{}"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "promotion_bonus",
+            columns: &[("unit_id", Integer), ("promotion_level", Integer)],
+            status: &["{}"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unit_rarity",
+            columns: &[
+                ("unit_id", Integer),
+                ("rarity", Integer),
+                ("unit_material_id", Integer),
+                ("consume_num", Integer),
+                ("consume_gold", Integer),
+            ],
+            status: &["{}", "{}_growth"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unlock_rarity_6",
+            columns: &[
+                ("unit_id", Integer),
+                ("slot_id", Integer),
+                ("unlock_level", Integer),
+                ("unlock_flag", Integer),
+                ("consume_gold", Integer),
+                ("material_type", Integer),
+                ("material_id", Integer),
+                ("material_count", Integer),
+            ],
+            status: &["{}"],
+            status_type: Integer,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unit_unique_equip",
+            columns: &[
+                ("unit_id", Integer),
+                ("equip_slot", Integer),
+                ("equip_id", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unit_skill_data",
+            columns: &[
+                ("unit_id", Integer),
+                ("union_burst", Integer),
+                ("main_skill_1", Integer),
+                ("main_skill_2", Integer),
+                ("main_skill_3", Integer),
+                ("main_skill_4", Integer),
+                ("main_skill_5", Integer),
+                ("main_skill_6", Integer),
+                ("main_skill_7", Integer),
+                ("main_skill_8", Integer),
+                ("main_skill_9", Integer),
+                ("main_skill_10", Integer),
+                ("ex_skill_1", Integer),
+                ("ex_skill_2", Integer),
+                ("ex_skill_3", Integer),
+                ("ex_skill_4", Integer),
+                ("ex_skill_5", Integer),
+                ("ex_skill_evolution_1", Integer),
+                ("ex_skill_evolution_2", Integer),
+                ("ex_skill_evolution_3", Integer),
+                ("ex_skill_evolution_4", Integer),
+                ("ex_skill_evolution_5", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[
+                ("sp_union_burst", Integer),
+                ("union_burst_evolution", Integer),
+                ("sp_skill_1", Integer),
+                ("sp_skill_2", Integer),
+                ("sp_skill_3", Integer),
+                ("sp_skill_4", Integer),
+                ("sp_skill_5", Integer),
+                ("main_skill_evolution_1", Integer),
+                ("main_skill_evolution_2", Integer),
+                ("sp_skill_evolution_1", Integer),
+                ("sp_skill_evolution_2", Integer),
+            ],
+        },
+        TableColumns {
+            name: "chara_story_status",
+            columns: &[
+                ("story_id", Integer),
+                ("unlock_story_name", Text),
+                ("status_type_1", Integer),
+                ("status_rate_1", Integer),
+                ("status_type_2", Integer),
+                ("status_rate_2", Integer),
+                ("status_type_3", Integer),
+                ("status_rate_3", Integer),
+                ("status_type_4", Integer),
+                ("status_rate_4", Integer),
+                ("status_type_5", Integer),
+                ("status_rate_5", Integer),
+                ("chara_id_1", Integer),
+                ("chara_id_2", Integer),
+                ("chara_id_3", Integer),
+                ("chara_id_4", Integer),
+                ("chara_id_5", Integer),
+                ("chara_id_6", Integer),
+                ("chara_id_7", Integer),
+                ("chara_id_8", Integer),
+                ("chara_id_9", Integer),
+                ("chara_id_10", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "equipment_data",
+            columns: &[
+                ("equipment_id", Integer),
+                ("equipment_name", Text),
+                ("description", Text),
+                ("promotion_level", Integer),
+                ("craft_flg", Integer),
+                ("equipment_enhance_point", Integer),
+                ("sale_price", Integer),
+                ("require_level", Integer),
+                ("enable_donation", Integer),
+                ("display_item", Integer),
+                ("item_type", Integer),
+            ],
+            status: &["{}"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "equipment_enhance_rate",
+            columns: &[
+                ("equipment_id", Integer),
+                ("equipment_name", Text),
+                ("description", Text),
+                ("promotion_level", Integer),
+            ],
+            status: &["{}"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "equipment_enhance_data",
+            columns: &[
+                ("promotion_level", Integer),
+                ("equipment_enhance_level", Integer),
+                ("needed_point", Integer),
+                ("total_point", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unique_equipment_data",
+            columns: &[
+                ("equipment_id", Integer),
+                ("equipment_name", Text),
+                ("description", Text),
+                ("promotion_level", Integer),
+                ("craft_flg", Integer),
+                ("equipment_enhance_point", Integer),
+                ("sale_price", Integer),
+                ("require_level", Integer),
+                ("enable_donation", Integer),
+            ],
+            status: &["{}"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unique_equipment_enhance_rate",
+            columns: &[
+                ("equipment_id", Integer),
+                ("equipment_name", Text),
+                ("description", Text),
+                ("promotion_level", Integer),
+            ],
+            status: &["{}"],
+            status_type: Real,
+            optional: &[],
+        },
+        TableColumns {
+            name: "unique_equipment_enhance_data",
+            columns: &[
+                ("equip_slot", Integer),
+                ("enhance_level", Integer),
+                ("needed_point", Integer),
+                ("total_point", Integer),
+                ("needed_mana", Integer),
+                ("rank", Integer),
+            ],
+            status: &[],
+            status_type: Real,
+            optional: &[],
+        },
+    ]
+};
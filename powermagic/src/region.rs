@@ -0,0 +1,231 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{DataError, Error};
+use crate::model::UnitStatusCoefficient;
+
+/// Game server a master database comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    Jp,
+    Tw,
+    Cn,
+    Global,
+}
+
+impl Region {
+    pub const ALL: [Region; 4] = [Region::Jp, Region::Tw, Region::Cn, Region::Global];
+
+    /// Settings of the server, lagging servers already ship the coefficient
+    /// rows of features they don't have yet, so they keep the first row
+    pub fn profile(self) -> RegionProfile {
+        match self {
+            Region::Jp => RegionProfile {
+                region: Some(self),
+                schema: Schema::Current,
+                coefficient: CoefficientRow::Latest,
+                names: NameStyle::Kana,
+            },
+            Region::Tw | Region::Cn => RegionProfile {
+                region: Some(self),
+                schema: Schema::Lagging,
+                coefficient: CoefficientRow::First,
+                names: NameStyle::FullWidth,
+            },
+            Region::Global => RegionProfile {
+                region: Some(self),
+                schema: Schema::Lagging,
+                coefficient: CoefficientRow::First,
+                names: NameStyle::Latin,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Region::Jp => "jp",
+            Region::Tw => "tw",
+            Region::Cn => "cn",
+            Region::Global => "global",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown region {0}, expected jp, tw, cn or global")]
+pub struct ParseRegionError(String);
+
+impl FromStr for Region {
+    type Err = ParseRegionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jp" | "ja" => Ok(Region::Jp),
+            "tw" => Ok(Region::Tw),
+            "cn" | "zh" => Ok(Region::Cn),
+            "global" | "en" => Ok(Region::Global),
+            _ => Err(ParseRegionError(s.to_string())),
+        }
+    }
+}
+
+/// Tables a master database may lack, the tables it has use the same
+/// columns in every server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Schema {
+    /// Every table the loaders read exists
+    #[default]
+    Current,
+    /// Tables of features the server doesn't have yet, like rarity 6 or unique
+    /// equipments, may be missing and are read as empty
+    Lagging,
+}
+
+impl Schema {
+    /// Whether a table may be missing from the database
+    pub fn is_optional(&self, table: &str) -> bool {
+        match self {
+            Schema::Current => false,
+            Schema::Lagging => matches!(
+                table,
+                "unlock_rarity_6"
+                    | "unit_unique_equip"
+                    | "unique_equipment_data"
+                    | "unique_equipment_enhance_rate"
+                    | "unique_equipment_enhance_data"
+            ),
+        }
+    }
+}
+
+/// How unit names are written in `unit_data`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NameStyle {
+    /// Names are compared as they are
+    #[default]
+    Exact,
+    /// Names use full-width brackets and no space, e.g. `優衣（公主）`
+    FullWidth,
+    /// Same as `FullWidth` with half-width katakana written full-width, e.g.
+    /// `ﾕｲ(ﾌﾟﾘﾝｾｽ)` is `ユイ（プリンセス）`
+    Kana,
+    /// Names use ASCII brackets and are compared ignoring case, e.g. `Yui (Princess)`
+    Latin,
+}
+
+impl NameStyle {
+    /// Name written the way the database does, for comparison
+    pub fn normalize(&self, name: &str) -> String {
+        let name = name.trim();
+        match self {
+            NameStyle::Exact => name.to_string(),
+            NameStyle::FullWidth => full_width_brackets(name).collect(),
+            NameStyle::Kana => {
+                let mut normalized = String::new();
+                for c in full_width_brackets(name) {
+                    // Sound marks are separate characters in half-width
+                    let voiced = match (c, normalized.chars().last()) {
+                        ('ﾞ', Some('ウ')) => Some('ヴ'),
+                        ('ﾞ', Some(last)) if VOICED.contains(last) => {
+                            char::from_u32(last as u32 + 1)
+                        }
+                        ('ﾟ', Some(last)) if SEMI_VOICED.contains(last) => {
+                            char::from_u32(last as u32 + 2)
+                        }
+                        _ => None,
+                    };
+                    match voiced {
+                        Some(voiced) => {
+                            normalized.pop();
+                            normalized.push(voiced);
+                        }
+                        None => normalized.push(full_width_kana(c)),
+                    }
+                }
+                normalized
+            }
+            NameStyle::Latin => name
+                .chars()
+                .map(|c| match c {
+                    '（' => '(',
+                    '）' => ')',
+                    '　' => ' ',
+                    c => c,
+                })
+                .collect::<String>()
+                .to_lowercase(),
+        }
+    }
+}
+
+/// Katakana followed by the code point of their voiced form
+const VOICED: &str = "カキクケコサシスセソタチツテトハヒフヘホ";
+/// Katakana followed by their voiced then semi-voiced forms
+const SEMI_VOICED: &str = "ハヒフヘホ";
+/// Full-width forms of `｡` to `ﾟ`, U+FF61 to U+FF9F
+const HALF_WIDTH_KANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+
+fn full_width_brackets(name: &str) -> impl Iterator<Item = char> + '_ {
+    name.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '(' => '（',
+            ')' => '）',
+            c => c,
+        })
+}
+
+fn full_width_kana(c: char) -> char {
+    match (c as u32).checked_sub(0xFF61) {
+        Some(index) => HALF_WIDTH_KANA.chars().nth(index as usize).unwrap_or(c),
+        None => c,
+    }
+}
+
+/// Row of `unit_status_coefficient` used for power
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CoefficientRow {
+    /// Row with the smallest `coefficient_id`, the first one sources return
+    #[default]
+    First,
+    /// Row with the largest `coefficient_id`
+    Latest,
+    Id(i64),
+}
+
+impl CoefficientRow {
+    pub fn select(
+        &self,
+        rows: &[UnitStatusCoefficient],
+    ) -> Result<UnitStatusCoefficient, Error> {
+        let row = match self {
+            CoefficientRow::First => rows.first(),
+            CoefficientRow::Latest => rows.iter().max_by_key(|row| row.coefficient_id),
+            CoefficientRow::Id(id) => {
+                return rows
+                    .iter()
+                    .find(|row| row.coefficient_id == *id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        DataError::StatusCoefficientIdNotFound { coefficient_id: *id }.into()
+                    })
+            }
+        };
+
+        row.cloned()
+            .ok_or_else(|| DataError::StatusCoefficientNotFound.into())
+    }
+}
+
+/// Settings that differ between the master databases of each server
+#[derive(Debug, Clone, Default)]
+pub struct RegionProfile {
+    /// `None` for an unknown server
+    pub region: Option<Region>,
+    pub schema: Schema,
+    pub coefficient: CoefficientRow,
+    pub names: NameStyle,
+}
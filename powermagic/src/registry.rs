@@ -0,0 +1,212 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+use crate::error::Error;
+use crate::manager::DataManager;
+use crate::unit::UnitCache;
+
+/// Unit caches shared between tasks
+///
+/// Each unit is loaded once, concurrent requests for the same unit wait for
+/// the same load. With a capacity set, the least recently used units are
+/// evicted; callers still holding an `Arc` keep their cache alive.
+#[derive(Debug, Default)]
+pub struct UnitCacheRegistry {
+    capacity: Option<usize>,
+    entries: Mutex<RegistryEntries>,
+}
+
+#[derive(Debug)]
+struct RegistryEntry {
+    cell: Arc<OnceCell<Arc<UnitCache>>>,
+    /// Tick of the last use, its key in `RegistryEntries::recency`
+    tick: u64,
+    loaded: bool,
+}
+
+#[derive(Debug, Default)]
+struct RegistryEntries {
+    entries: HashMap<i64, RegistryEntry>,
+    /// Unit ids from the least to the most recently used
+    recency: BTreeMap<u64, i64>,
+    tick: u64,
+    /// Number of entries whose load finished
+    loaded: usize,
+}
+
+impl RegistryEntries {
+    /// Cell of a unit, made the most recently used
+    fn touch(&mut self, unit_id: i64) -> Arc<OnceCell<Arc<UnitCache>>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = self
+            .entries
+            .entry(unit_id)
+            .or_insert_with(|| RegistryEntry {
+                cell: Default::default(),
+                tick,
+                loaded: false,
+            });
+        self.recency.remove(&entry.tick);
+        entry.tick = tick;
+        self.recency.insert(tick, unit_id);
+
+        entry.cell.clone()
+    }
+
+    /// Count the entry of `cell` as loaded, unless it was removed meanwhile
+    fn set_loaded(&mut self, unit_id: i64, cell: &Arc<OnceCell<Arc<UnitCache>>>) {
+        if let Some(entry) = self.entries.get_mut(&unit_id) {
+            if Arc::ptr_eq(&entry.cell, cell) && !entry.loaded {
+                entry.loaded = true;
+                self.loaded += 1;
+            }
+        }
+    }
+
+    fn remove(&mut self, unit_id: i64) -> Option<RegistryEntry> {
+        let entry = self.entries.remove(&unit_id)?;
+        self.recency.remove(&entry.tick);
+        if entry.loaded {
+            self.loaded -= 1;
+        }
+
+        Some(entry)
+    }
+
+    /// Evict least recently used units over capacity, never `keep`
+    ///
+    /// Units still loading are neither counted nor evicted, their tasks are
+    /// waiting on them.
+    fn evict(&mut self, capacity: usize, keep: Option<i64>) {
+        let over = self.loaded.saturating_sub(capacity.max(1));
+        let evicted: Vec<i64> = self
+            .recency
+            .values()
+            .filter(|unit_id| Some(**unit_id) != keep && self.entries[*unit_id].loaded)
+            .take(over)
+            .copied()
+            .collect();
+        for unit_id in evicted {
+            self.remove(unit_id);
+        }
+    }
+}
+
+impl UnitCacheRegistry {
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(RegistryEntries::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Change the capacity, evicting units over it right away
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+        if let Some(capacity) = capacity {
+            self.entries.lock().unwrap().evict(capacity, None);
+        }
+    }
+
+    /// Get the cache of a unit, loading it with `load` if it's not there
+    pub async fn get_or_load<F>(
+        &self,
+        unit_id: i64,
+        load: F,
+    ) -> Result<Arc<UnitCache>, Error>
+    where
+        F: Future<Output = Result<UnitCache, Error>>,
+    {
+        let cell = self.entries.lock().unwrap().touch(unit_id);
+
+        let result = cell
+            .get_or_try_init(|| async { load.await.map(Arc::new) })
+            .await
+            .cloned();
+
+        let mut entries = self.entries.lock().unwrap();
+        match result {
+            Ok(cache) => {
+                entries.set_loaded(unit_id, &cell);
+                if let Some(capacity) = self.capacity {
+                    entries.evict(capacity, Some(unit_id));
+                }
+                Ok(cache)
+            }
+            Err(error) => {
+                // Don't keep failed units around, the next request retries
+                let failed = entries.entries.get(&unit_id).is_some_and(|entry| {
+                    Arc::ptr_eq(&entry.cell, &cell) && !entry.cell.initialized()
+                });
+                if failed {
+                    entries.remove(unit_id);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Get the cache of a unit if it's already loaded
+    pub fn get(&self, unit_id: i64) -> Option<Arc<UnitCache>> {
+        let entries = self.entries.lock().unwrap();
+        entries.entries.get(&unit_id)?.cell.get().cloned()
+    }
+
+    pub fn remove(&self, unit_id: i64) -> Option<Arc<UnitCache>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(unit_id)?.cell.get().cloned()
+    }
+
+    pub fn clear(&self) {
+        *self.entries.lock().unwrap() = RegistryEntries::default();
+    }
+
+    /// Ids of units already loaded, from the least to the most recently used
+    pub fn unit_ids(&self) -> Vec<i64> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .recency
+            .values()
+            .filter(|unit_id| entries.entries[*unit_id].loaded)
+            .copied()
+            .collect()
+    }
+
+    /// Number of units already loaded, units still loading aren't counted
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().loaded
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl DataManager {
+    /// Shared cache of a unit, loaded at most once while it stays registered
+    pub async fn shared_unit_cache(
+        &self,
+        unit_id: i64,
+    ) -> Result<Arc<UnitCache>, Error> {
+        self.registry
+            .get_or_load(unit_id, self.unit_cache(unit_id))
+            .await
+    }
+
+    pub fn registry(&self) -> &UnitCacheRegistry {
+        &self.registry
+    }
+
+    /// Limit the number of shared unit caches, `None` for no limit
+    pub fn set_cache_capacity(&mut self, capacity: Option<usize>) {
+        self.registry.set_capacity(capacity);
+    }
+}
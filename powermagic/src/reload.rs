@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::error::Error;
+use crate::manager::DataManager;
+use crate::region::RegionProfile;
+use crate::source::SqliteSource;
+
+/// Modification time and size of the database file
+type FileStamp = (SystemTime, u64);
+
+fn file_stamp(path: &Path) -> std::io::Result<FileStamp> {
+    let metadata = std::fs::metadata(path)?;
+    Ok((metadata.modified()?, metadata.len()))
+}
+
+/// A [`DataManager`] rebuilt when its database file changes
+///
+/// Each manager is a preloaded snapshot of the database. [`Self::current`]
+/// hands out the latest one; a calculation holding it keeps using the same
+/// data while a reload swaps in a new manager. Unit caches shared through the
+/// registry of the old manager are loaded again into the new one before the
+/// swap, so callers never see a half-built manager. Reloads run one at a
+/// time, a rebuild of an older file can't be swapped in after a newer one.
+#[derive(Debug)]
+pub struct ReloadableDataManager {
+    path: PathBuf,
+    profile: RegionProfile,
+    current: RwLock<Arc<DataManager>>,
+    stamp: Mutex<Option<FileStamp>>,
+    generation: Mutex<u64>,
+    last_error: Mutex<Option<String>>,
+    /// Held from the start of a rebuild to its swap
+    reloading: tokio::sync::Mutex<()>,
+}
+
+impl ReloadableDataManager {
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        Self::open_with_profile(path, RegionProfile::default()).await
+    }
+
+    pub async fn open_with_profile(
+        path: impl Into<PathBuf>,
+        profile: RegionProfile,
+    ) -> Result<Self, Error> {
+        let path = path.into();
+        let stamp = file_stamp(&path).ok();
+        let data_manager = Self::load(&path, &profile).await?;
+
+        Ok(Self {
+            path,
+            profile,
+            current: RwLock::new(Arc::new(data_manager)),
+            stamp: Mutex::new(stamp),
+            generation: Mutex::new(0),
+            last_error: Mutex::new(None),
+            reloading: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    async fn load(path: &Path, profile: &RegionProfile) -> Result<DataManager, Error> {
+        let options = SqliteConnectOptions::new().filename(path).read_only(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        let source = SqliteSource::with_schema(pool, profile.schema);
+        let mut data_manager = DataManager::with_profile(Arc::new(source), profile.clone()).await?;
+        data_manager.preload().await?;
+
+        Ok(data_manager)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The latest manager, kept alive as long as the `Arc` is held
+    pub fn current(&self) -> Arc<DataManager> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Number of successful reloads
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+
+    /// Error of the last failed reload, cleared by a successful one
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    /// Rebuild the manager from the database file
+    ///
+    /// On error the current manager is kept. A reload started while another
+    /// one runs waits for it, then reads the file again.
+    pub async fn reload(&self) -> Result<(), Error> {
+        let _reloading = self.reloading.lock().await;
+        self.reload_locked().await
+    }
+
+    async fn reload_locked(&self) -> Result<(), Error> {
+        let stamp = file_stamp(&self.path).ok();
+        let result = self.rebuild().await;
+
+        match &result {
+            Ok(data_manager) => {
+                *self.current.write().unwrap() = data_manager.clone();
+                *self.stamp.lock().unwrap() = stamp;
+                *self.generation.lock().unwrap() += 1;
+                *self.last_error.lock().unwrap() = None;
+            }
+            Err(error) => *self.last_error.lock().unwrap() = Some(error.to_string()),
+        }
+
+        result.map(|_| ())
+    }
+
+    async fn rebuild(&self) -> Result<Arc<DataManager>, Error> {
+        let old = self.current();
+        let mut data_manager = Self::load(&self.path, &self.profile).await?;
+        data_manager.set_cache_capacity(old.registry().capacity());
+
+        // Units removed by the patch are dropped from the registry
+        let unit_ids = data_manager.unit_ids().await?;
+        for unit_id in old.registry().unit_ids() {
+            if unit_ids.binary_search(&unit_id).is_ok() {
+                data_manager.shared_unit_cache(unit_id).await?;
+            }
+        }
+
+        Ok(Arc::new(data_manager))
+    }
+
+    /// Whether the database file changed since the last load
+    pub fn is_changed(&self) -> bool {
+        match file_stamp(&self.path) {
+            Ok(stamp) => *self.stamp.lock().unwrap() != Some(stamp),
+            // The file is being replaced, wait for the new one
+            Err(_) => false,
+        }
+    }
+
+    /// Reload if the database file changed, returns whether it was reloaded
+    pub async fn reload_if_changed(&self) -> Result<bool, Error> {
+        if !self.is_changed() {
+            return Ok(false);
+        }
+
+        // A reload that was running may already have read the change
+        let _reloading = self.reloading.lock().await;
+        if !self.is_changed() {
+            return Ok(false);
+        }
+
+        self.reload_locked().await?;
+        Ok(true)
+    }
+
+    /// Check the database file every `interval` in a background task
+    ///
+    /// Failed reloads are retried on the next change of the file, see
+    /// [`Self::last_error`]. The task stops when the manager is dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let manager = match weak.upgrade() {
+                    Some(manager) => manager,
+                    None => break,
+                };
+
+                if manager.reload_if_changed().await.is_err() {
+                    // Don't retry the same broken file on every tick
+                    *manager.stamp.lock().unwrap() = file_stamp(&manager.path).ok();
+                }
+            }
+        })
+    }
+}
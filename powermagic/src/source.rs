@@ -0,0 +1,102 @@
+mod dump;
+mod memory;
+mod sqlite;
+
+pub use dump::{import_dump_dir, import_dump_dir_with_schema, DumpError, DUMP_TABLES};
+pub use memory::InMemorySource;
+pub use sqlite::SqliteSource;
+
+use async_trait::async_trait;
+
+use crate::error::Error;
+use crate::model;
+
+/// Master data queries needed by the unit loaders
+///
+/// Methods taking an `Option` id return every row for `None`. Rows are
+/// sorted the same way for every source, as documented on each method.
+#[async_trait]
+pub trait DataSource: Send + Sync {
+    /// Sorted by `coefficient_id`
+    async fn unit_status_coefficient(&self) -> Result<Vec<model::UnitStatusCoefficient>, Error>;
+
+    /// Sorted by `promotion_level`, `equipment_enhance_level`
+    async fn equipment_enhance_data(&self) -> Result<Vec<model::EquipmentEnhanceData>, Error>;
+
+    /// Sorted by `equip_slot`, `enhance_level`
+    async fn unique_equipment_enhance_data(
+        &self,
+    ) -> Result<Vec<model::UniqueEquipmentEnhanceData>, Error>;
+
+    /// Sorted by `unit_id`
+    async fn unit_data(&self, unit_id: Option<i64>) -> Result<Vec<model::UnitData>, Error>;
+
+    /// Ids of units that have both rarity and promotion rows, sorted
+    async fn unit_ids(&self) -> Result<Vec<i64>, Error>;
+
+    /// Sorted by `unit_id`, `promotion_level`
+    async fn unit_promotion(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitPromotion>, Error>;
+
+    /// Sorted by `unit_id`, `promotion_level`
+    async fn unit_promotion_status(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitPromotionStatus>, Error>;
+
+    async fn promotion_bonus(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::PromotionBonus>, Error>;
+
+    /// Sorted by `unit_id`, `rarity`
+    async fn unit_rarity(&self, unit_id: Option<i64>) -> Result<Vec<model::UnitRarity>, Error>;
+
+    /// Rows with `unlock_level != 0`, sorted by `unit_id`, `slot_id`, `unlock_level`
+    async fn unlock_rarity_6(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnlockRarity6>, Error>;
+
+    /// Sorted by `unit_id`, `equip_slot`
+    async fn unit_unique_equip(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitUniqueEquip>, Error>;
+
+    async fn unit_skill_data(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitSkillData>, Error>;
+
+    /// Stories giving a bonus to `chara_id`, sorted by `story_id`
+    async fn chara_story_status(
+        &self,
+        chara_id: Option<i64>,
+    ) -> Result<Vec<model::CharaStoryStatus>, Error>;
+
+    async fn equipment_data(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::EquipmentData>, Error>;
+
+    async fn equipment_enhance_rate(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::EquipmentEnhanceRate>, Error>;
+
+    async fn unique_equipment_data(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::UniqueEquipmentData>, Error>;
+
+    async fn unique_equipment_enhance_rate(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::UniqueEquipmentEnhanceRate>, Error>;
+
+    /// Highest `unit_level` of `experience_unit`, the level cap of the server
+    async fn max_unit_level(&self) -> Result<i32, Error>;
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use super::{InMemorySource, SqliteSource};
+use crate::error::Error;
+use crate::model::{ColumnType, TableColumns};
+use crate::region::Schema;
+
+/// Tables read by [`InMemorySource::from_dump_dir`]
+pub const DUMP_TABLES: [&str; 16] = [
+    "unit_status_coefficient",
+    "equipment_enhance_data",
+    "unique_equipment_enhance_data",
+    "unit_data",
+    "unit_promotion",
+    "unit_promotion_status",
+    "promotion_bonus",
+    "unit_rarity",
+    "unlock_rarity_6",
+    "unit_unique_equip",
+    "unit_skill_data",
+    "chara_story_status",
+    "equipment_data",
+    "equipment_enhance_rate",
+    "unique_equipment_data",
+    "unique_equipment_enhance_rate",
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum DumpError {
+    #[error("IO error on {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("JSON error in {0}: {1}")]
+    Json(PathBuf, serde_json::Error),
+    #[error("CSV error in {0}: {1}")]
+    Csv(PathBuf, csv::Error),
+    #[error("{0} is not an array of objects")]
+    InvalidJson(PathBuf),
+    #[error("Table {0} has no dump in {1}")]
+    TableNotFound(String, PathBuf),
+}
+
+/// Value of a dumped cell
+#[derive(Debug, Clone, PartialEq)]
+enum DumpValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+#[derive(Debug, Default)]
+struct DumpTable {
+    columns: Vec<String>,
+    rows: Vec<Vec<DumpValue>>,
+}
+
+impl DumpTable {
+    fn from_json(path: &Path) -> Result<Self, DumpError> {
+        let text = std::fs::read_to_string(path).map_err(|e| DumpError::Io(path.into(), e))?;
+        let value: serde_json::Value =
+            serde_json::from_str(&text).map_err(|e| DumpError::Json(path.into(), e))?;
+        let objects = match value {
+            serde_json::Value::Array(objects) => objects,
+            _ => return Err(DumpError::InvalidJson(path.into())),
+        };
+
+        let mut table = DumpTable::default();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for object in objects.into_iter() {
+            let object = match object {
+                serde_json::Value::Object(object) => object,
+                _ => return Err(DumpError::InvalidJson(path.into())),
+            };
+
+            let mut row = vec![DumpValue::Null; table.columns.len()];
+            for (column, value) in object.into_iter() {
+                let column_index = *index.entry(column.clone()).or_insert_with(|| {
+                    table.columns.push(column);
+                    table.columns.len() - 1
+                });
+                if column_index >= row.len() {
+                    row.resize(column_index + 1, DumpValue::Null);
+                }
+                row[column_index] = DumpValue::from_json(value);
+            }
+            table.rows.push(row);
+        }
+
+        // Rows before a column first appeared are missing it
+        let len = table.columns.len();
+        table
+            .rows
+            .iter_mut()
+            .for_each(|row| row.resize(len, DumpValue::Null));
+
+        Ok(table)
+    }
+
+    /// Cells are kept as text, converted by the type of their column
+    fn from_csv(path: &Path, spec: &TableColumns) -> Result<Self, DumpError> {
+        let mut reader =
+            csv::Reader::from_path(path).map_err(|e| DumpError::Csv(path.into(), e))?;
+        let columns: Vec<String> = reader
+            .headers()
+            .map_err(|e| DumpError::Csv(path.into(), e))?
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        let column_types: Vec<Option<ColumnType>> = columns
+            .iter()
+            .map(|column| spec.column_type(column))
+            .collect();
+
+        let mut rows = vec![];
+        for record in reader.records() {
+            let record = record.map_err(|e| DumpError::Csv(path.into(), e))?;
+            rows.push(
+                record
+                    .iter()
+                    .zip(column_types.iter())
+                    .map(|(value, column_type)| DumpValue::from_csv(value, *column_type))
+                    .collect(),
+            );
+        }
+
+        Ok(DumpTable { columns, rows })
+    }
+}
+
+impl DumpValue {
+    fn from_json(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => DumpValue::Null,
+            serde_json::Value::Bool(value) => DumpValue::Integer(value as i64),
+            serde_json::Value::Number(number) => match number.as_i64() {
+                Some(value) => DumpValue::Integer(value),
+                None => DumpValue::Real(number.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(value) => DumpValue::Text(value),
+            value => DumpValue::Text(value.to_string()),
+        }
+    }
+
+    fn from_csv(value: &str, column_type: Option<ColumnType>) -> Self {
+        match column_type {
+            // CSV can't tell an empty string from no value, only text
+            // columns have empty strings
+            Some(ColumnType::Integer) | Some(ColumnType::Real) if value.is_empty() => {
+                DumpValue::Null
+            }
+            _ => DumpValue::Text(value.to_string()),
+        }
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Import the dumps of [`DUMP_TABLES`] from `dir` into an in-memory SQLite
+/// database, every table is required
+///
+/// Each table is read from `<table>.json`, an array of objects, or
+/// `<table>.csv` with a header row. Columns are declared with the types the
+/// row readers of [`crate::model`] decode, and SQLite converts the values to
+/// them like in the master database.
+pub async fn import_dump_dir(dir: impl AsRef<Path>) -> Result<sqlx::Pool<sqlx::Sqlite>, Error> {
+    import_dump_dir_with_schema(dir, Schema::Current).await
+}
+
+/// Same as [`import_dump_dir`], tables `schema` allows to be missing are
+/// created empty when they have no dump
+pub async fn import_dump_dir_with_schema(
+    dir: impl AsRef<Path>,
+    schema: Schema,
+) -> Result<sqlx::Pool<sqlx::Sqlite>, Error> {
+    let dir = dir.as_ref();
+
+    // Every connection to `:memory:` is a new database, keep a single one
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await?;
+
+    for table_name in DUMP_TABLES.iter() {
+        let spec = TableColumns::get(table_name).expect("dumped tables are in model::TABLES");
+        let json = dir.join(format!("{}.json", table_name));
+        let csv = dir.join(format!("{}.csv", table_name));
+        let table = if json.is_file() {
+            DumpTable::from_json(&json)?
+        } else if csv.is_file() {
+            DumpTable::from_csv(&csv, spec)?
+        } else if schema.is_optional(table_name) {
+            DumpTable::default()
+        } else {
+            return Err(DumpError::TableNotFound(table_name.to_string(), dir.into()).into());
+        };
+
+        import_table(&pool, spec, table).await?;
+    }
+
+    Ok(pool)
+}
+
+async fn import_table(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    spec: &TableColumns,
+    table: DumpTable,
+) -> Result<(), Error> {
+    // An empty dump has no column to tell, create the table the loaders expect
+    let columns: Vec<String> = if table.columns.is_empty() {
+        spec.required()
+            .iter()
+            .map(|(column, column_type)| format!("{} {}", quote(column), column_type.sql_name()))
+            .collect()
+    } else {
+        table
+            .columns
+            .iter()
+            .map(|column| match spec.column_type(column) {
+                Some(column_type) => format!("{} {}", quote(column), column_type.sql_name()),
+                None => quote(column),
+            })
+            .collect()
+    };
+    let create = format!(
+        "CREATE TABLE {} ({})",
+        quote(spec.name),
+        columns.join(", ")
+    );
+    sqlx::query(&create).execute(pool).await?;
+
+    if table.columns.is_empty() {
+        return Ok(());
+    }
+
+    let names: Vec<String> = table.columns.iter().map(|column| quote(column)).collect();
+    let placeholders: Vec<&str> = table.columns.iter().map(|_| "?").collect();
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote(spec.name),
+        names.join(", "),
+        placeholders.join(", ")
+    );
+
+    let mut transaction = pool.begin().await?;
+    for row in table.rows.into_iter() {
+        let mut query = sqlx::query(&insert);
+        for value in row.into_iter() {
+            query = match value {
+                DumpValue::Null => query.bind(None::<i64>),
+                DumpValue::Integer(value) => query.bind(value),
+                DumpValue::Real(value) => query.bind(value),
+                DumpValue::Text(value) => query.bind(value),
+            };
+        }
+        query.execute(&mut transaction).await?;
+    }
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+impl InMemorySource {
+    /// Load master data from a directory of per-table JSON or CSV dumps,
+    /// see [`import_dump_dir`]
+    pub async fn from_dump_dir(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_dump_dir_with_schema(dir, Schema::Current).await
+    }
+
+    /// Same as [`Self::from_dump_dir`], see [`import_dump_dir_with_schema`]
+    pub async fn from_dump_dir_with_schema(
+        dir: impl AsRef<Path>,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        let pool = import_dump_dir_with_schema(dir, schema).await?;
+        let source = InMemorySource::load(&SqliteSource::new(pool.clone())).await?;
+        pool.close().await;
+
+        Ok(source)
+    }
+}
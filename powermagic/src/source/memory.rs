@@ -0,0 +1,285 @@
+use async_trait::async_trait;
+
+use super::DataSource;
+use crate::error::{DataError, Error, SchemaError};
+use crate::model;
+
+/// Master data held in memory, one `Vec` of rows per table
+///
+/// Rows don't need to be sorted, queries sort them like [`super::SqliteSource`].
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySource {
+    pub unit_status_coefficient: Vec<model::UnitStatusCoefficient>,
+    pub equipment_enhance_data: Vec<model::EquipmentEnhanceData>,
+    pub unique_equipment_enhance_data: Vec<model::UniqueEquipmentEnhanceData>,
+    pub unit_data: Vec<model::UnitData>,
+    pub unit_promotion: Vec<model::UnitPromotion>,
+    pub unit_promotion_status: Vec<model::UnitPromotionStatus>,
+    pub promotion_bonus: Vec<model::PromotionBonus>,
+    pub unit_rarity: Vec<model::UnitRarity>,
+    pub unlock_rarity_6: Vec<model::UnlockRarity6>,
+    pub unit_unique_equip: Vec<model::UnitUniqueEquip>,
+    pub unit_skill_data: Vec<model::UnitSkillData>,
+    pub chara_story_status: Vec<model::CharaStoryStatus>,
+    pub equipment_data: Vec<model::EquipmentData>,
+    pub equipment_enhance_rate: Vec<model::EquipmentEnhanceRate>,
+    pub unique_equipment_data: Vec<model::UniqueEquipmentData>,
+    pub unique_equipment_enhance_rate: Vec<model::UniqueEquipmentEnhanceRate>,
+    /// Level cap, `None` if the copied source has no `experience_unit`
+    pub max_unit_level: Option<i32>,
+}
+
+impl InMemorySource {
+    /// Copy every table of another source
+    pub async fn load(source: &dyn DataSource) -> Result<Self, Error> {
+        Ok(Self {
+            unit_status_coefficient: source.unit_status_coefficient().await?,
+            equipment_enhance_data: source.equipment_enhance_data().await?,
+            unique_equipment_enhance_data: source.unique_equipment_enhance_data().await?,
+            unit_data: source.unit_data(None).await?,
+            unit_promotion: source.unit_promotion(None).await?,
+            unit_promotion_status: source.unit_promotion_status(None).await?,
+            promotion_bonus: source.promotion_bonus(None).await?,
+            unit_rarity: source.unit_rarity(None).await?,
+            unlock_rarity_6: source.unlock_rarity_6(None).await?,
+            unit_unique_equip: source.unit_unique_equip(None).await?,
+            unit_skill_data: source.unit_skill_data(None).await?,
+            chara_story_status: source.chara_story_status(None).await?,
+            equipment_data: source.equipment_data(None).await?,
+            equipment_enhance_rate: source.equipment_enhance_rate(None).await?,
+            unique_equipment_data: source.unique_equipment_data(None).await?,
+            unique_equipment_enhance_rate: source.unique_equipment_enhance_rate(None).await?,
+            max_unit_level: match source.max_unit_level().await {
+                Ok(level) => Some(level),
+                Err(Error::Schema(SchemaError::TableNotFound { .. }))
+                | Err(Error::Data(DataError::MaxUnitLevelNotFound)) => None,
+                Err(error) => return Err(error),
+            },
+        })
+    }
+}
+
+/// Rows matching `id`, or all rows for `None`, sorted by `key`
+fn select<T, K>(rows: &[T], id: Option<i64>, row_id: fn(&T) -> i64, key: fn(&T) -> K) -> Vec<T>
+where
+    T: Clone,
+    K: Ord,
+{
+    let mut rows: Vec<T> = rows
+        .iter()
+        .filter(|row| id.is_none_or(|id| row_id(row) == id))
+        .cloned()
+        .collect();
+    rows.sort_by_key(key);
+
+    rows
+}
+
+#[async_trait]
+impl DataSource for InMemorySource {
+    async fn unit_status_coefficient(&self) -> Result<Vec<model::UnitStatusCoefficient>, Error> {
+        Ok(select(
+            &self.unit_status_coefficient,
+            None,
+            |_| 0,
+            |row| row.coefficient_id,
+        ))
+    }
+
+    async fn equipment_enhance_data(&self) -> Result<Vec<model::EquipmentEnhanceData>, Error> {
+        Ok(select(
+            &self.equipment_enhance_data,
+            None,
+            |_| 0,
+            |row| (row.promotion_level, row.equipment_enhance_level),
+        ))
+    }
+
+    async fn unique_equipment_enhance_data(
+        &self,
+    ) -> Result<Vec<model::UniqueEquipmentEnhanceData>, Error> {
+        Ok(select(
+            &self.unique_equipment_enhance_data,
+            None,
+            |_| 0,
+            |row| (row.equip_slot, row.enhance_level),
+        ))
+    }
+
+    async fn unit_data(&self, unit_id: Option<i64>) -> Result<Vec<model::UnitData>, Error> {
+        Ok(select(
+            &self.unit_data,
+            unit_id,
+            |row| row.unit_id,
+            |row| row.unit_id,
+        ))
+    }
+
+    async fn unit_ids(&self) -> Result<Vec<i64>, Error> {
+        let mut unit_ids: Vec<i64> = self
+            .unit_rarity
+            .iter()
+            .map(|row| row.unit_id)
+            .filter(|unit_id| {
+                self.unit_promotion
+                    .iter()
+                    .any(|row| row.unit_id == *unit_id)
+            })
+            .collect();
+        unit_ids.sort_unstable();
+        unit_ids.dedup();
+
+        Ok(unit_ids)
+    }
+
+    async fn unit_promotion(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitPromotion>, Error> {
+        Ok(select(
+            &self.unit_promotion,
+            unit_id,
+            |row| row.unit_id,
+            |row| (row.unit_id, row.promotion_level),
+        ))
+    }
+
+    async fn unit_promotion_status(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitPromotionStatus>, Error> {
+        Ok(select(
+            &self.unit_promotion_status,
+            unit_id,
+            |row| row.unit_id,
+            |row| (row.unit_id, row.promotion_level),
+        ))
+    }
+
+    async fn promotion_bonus(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::PromotionBonus>, Error> {
+        Ok(select(
+            &self.promotion_bonus,
+            unit_id,
+            |row| row.unit_id,
+            |row| (row.unit_id, row.promotion_level),
+        ))
+    }
+
+    async fn unit_rarity(&self, unit_id: Option<i64>) -> Result<Vec<model::UnitRarity>, Error> {
+        Ok(select(
+            &self.unit_rarity,
+            unit_id,
+            |row| row.unit_id,
+            |row| (row.unit_id, row.rarity),
+        ))
+    }
+
+    async fn unlock_rarity_6(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnlockRarity6>, Error> {
+        let mut rows = select(
+            &self.unlock_rarity_6,
+            unit_id,
+            |row| row.unit_id,
+            |row| (row.unit_id, row.slot_id, row.unlock_level),
+        );
+        rows.retain(|row| row.unlock_level != 0);
+
+        Ok(rows)
+    }
+
+    async fn unit_unique_equip(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitUniqueEquip>, Error> {
+        Ok(select(
+            &self.unit_unique_equip,
+            unit_id,
+            |row| row.unit_id,
+            |row| (row.unit_id, row.equip_slot),
+        ))
+    }
+
+    async fn unit_skill_data(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitSkillData>, Error> {
+        Ok(select(
+            &self.unit_skill_data,
+            unit_id,
+            |row| row.unit_id,
+            |row| row.unit_id,
+        ))
+    }
+
+    async fn chara_story_status(
+        &self,
+        chara_id: Option<i64>,
+    ) -> Result<Vec<model::CharaStoryStatus>, Error> {
+        let mut rows: Vec<model::CharaStoryStatus> = self
+            .chara_story_status
+            .iter()
+            .filter(|row| chara_id.is_none_or(|chara_id| row.chara_id.contains(&chara_id)))
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| row.story_id);
+
+        Ok(rows)
+    }
+
+    async fn equipment_data(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::EquipmentData>, Error> {
+        Ok(select(
+            &self.equipment_data,
+            equipment_id,
+            |row| row.equipment_id,
+            |row| row.equipment_id,
+        ))
+    }
+
+    async fn equipment_enhance_rate(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::EquipmentEnhanceRate>, Error> {
+        Ok(select(
+            &self.equipment_enhance_rate,
+            equipment_id,
+            |row| row.equipment_id,
+            |row| row.equipment_id,
+        ))
+    }
+
+    async fn unique_equipment_data(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::UniqueEquipmentData>, Error> {
+        Ok(select(
+            &self.unique_equipment_data,
+            equipment_id,
+            |row| row.equipment_id,
+            |row| row.equipment_id,
+        ))
+    }
+
+    async fn unique_equipment_enhance_rate(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::UniqueEquipmentEnhanceRate>, Error> {
+        Ok(select(
+            &self.unique_equipment_enhance_rate,
+            equipment_id,
+            |row| row.equipment_id,
+            |row| row.equipment_id,
+        ))
+    }
+
+    async fn max_unit_level(&self) -> Result<i32, Error> {
+        Ok(self.max_unit_level.ok_or(DataError::MaxUnitLevelNotFound)?)
+    }
+}
@@ -0,0 +1,273 @@
+use async_trait::async_trait;
+use sqlx::sqlite::SqliteRow;
+
+use super::DataSource;
+use crate::error::{DataError, Error, SchemaError};
+use crate::model::{self, Row};
+use crate::region::Schema;
+
+/// Master data read from a SQLite database
+#[derive(Debug, Clone)]
+pub struct SqliteSource {
+    pool: sqlx::Pool<sqlx::Sqlite>,
+    schema: Schema,
+}
+
+impl SqliteSource {
+    pub fn new(pool: sqlx::Pool<sqlx::Sqlite>) -> Self {
+        Self::with_schema(pool, Schema::Current)
+    }
+
+    pub fn with_schema(pool: sqlx::Pool<sqlx::Sqlite>, schema: Schema) -> Self {
+        Self { pool, schema }
+    }
+
+    pub fn pool(&self) -> &sqlx::Pool<sqlx::Sqlite> {
+        &self.pool
+    }
+
+    pub fn schema(&self) -> Schema {
+        self.schema
+    }
+
+    /// Read a table missing from the schema as empty
+    fn rows_of<T>(&self, table: &str, rows: Result<Vec<T>, sqlx::Error>) -> Result<Vec<T>, Error> {
+        match rows.map_err(|error| Error::from_query(table, error)) {
+            Err(Error::Schema(SchemaError::TableNotFound { table }))
+                if self.schema.is_optional(&table) =>
+            {
+                Ok(vec![])
+            }
+            rows => rows,
+        }
+    }
+
+    /// `SELECT *` from a table, filtered on `column` if `id` is set
+    async fn fetch<T>(
+        &self,
+        table: &str,
+        column: &str,
+        id: Option<i64>,
+        order_by: &str,
+    ) -> Result<Vec<T>, Error>
+    where
+        T: for<'r> sqlx::FromRow<'r, SqliteRow> + Send + Unpin,
+    {
+        let order_by = if order_by.is_empty() {
+            String::new()
+        } else {
+            format!(" ORDER BY {}", order_by)
+        };
+
+        let rows = match id {
+            Some(id) => {
+                let query = format!("SELECT * FROM {} WHERE {} == $1{}", table, column, order_by);
+                sqlx::query_as::<_, T>(&query)
+                    .bind(id)
+                    .fetch_all(&self.pool)
+                    .await
+            }
+            None => {
+                let query = format!("SELECT * FROM {}{}", table, order_by);
+                sqlx::query_as::<_, T>(&query).fetch_all(&self.pool).await
+            }
+        };
+
+        self.rows_of(table, rows)
+    }
+}
+
+#[async_trait]
+impl DataSource for SqliteSource {
+    async fn unit_status_coefficient(&self) -> Result<Vec<model::UnitStatusCoefficient>, Error> {
+        let rows: Vec<Row<_>> = self
+            .fetch("unit_status_coefficient", "", None, "coefficient_id")
+            .await?;
+        Ok(rows.into_iter().map(|Row(row)| row).collect())
+    }
+
+    async fn equipment_enhance_data(&self) -> Result<Vec<model::EquipmentEnhanceData>, Error> {
+        self.fetch(
+            "equipment_enhance_data",
+            "",
+            None,
+            "promotion_level, equipment_enhance_level",
+        )
+        .await
+    }
+
+    async fn unique_equipment_enhance_data(
+        &self,
+    ) -> Result<Vec<model::UniqueEquipmentEnhanceData>, Error> {
+        self.fetch(
+            "unique_equipment_enhance_data",
+            "",
+            None,
+            "equip_slot, enhance_level",
+        )
+        .await
+    }
+
+    async fn unit_data(&self, unit_id: Option<i64>) -> Result<Vec<model::UnitData>, Error> {
+        self.fetch("unit_data", "unit_id", unit_id, "unit_id").await
+    }
+
+    async fn unit_ids(&self) -> Result<Vec<i64>, Error> {
+        let unit_ids = sqlx::query_scalar::<_, i64>(
+            "SELECT DISTINCT unit_id FROM unit_rarity WHERE unit_id IN (SELECT unit_id FROM unit_promotion) ORDER BY unit_id",
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        self.rows_of("unit_rarity", unit_ids)
+    }
+
+    async fn unit_promotion(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitPromotion>, Error> {
+        // RIGHT and FULL OUTER JOINs are not currently supported
+        self.fetch(
+            "unit_promotion",
+            "unit_id",
+            unit_id,
+            "unit_id, promotion_level ASC",
+        )
+        .await
+    }
+
+    async fn unit_promotion_status(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitPromotionStatus>, Error> {
+        self.fetch(
+            "unit_promotion_status",
+            "unit_id",
+            unit_id,
+            "unit_id, promotion_level ASC",
+        )
+        .await
+    }
+
+    async fn promotion_bonus(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::PromotionBonus>, Error> {
+        self.fetch("promotion_bonus", "unit_id", unit_id, "").await
+    }
+
+    async fn unit_rarity(&self, unit_id: Option<i64>) -> Result<Vec<model::UnitRarity>, Error> {
+        self.fetch("unit_rarity", "unit_id", unit_id, "unit_id, rarity ASC")
+            .await
+    }
+
+    async fn unlock_rarity_6(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnlockRarity6>, Error> {
+        let rows = match unit_id {
+            Some(unit_id) => sqlx::query_as::<_, model::UnlockRarity6>(
+                "SELECT * FROM unlock_rarity_6 WHERE unit_id == $1 AND unlock_level != 0 ORDER BY slot_id, unlock_level",
+            )
+            .bind(unit_id)
+            .fetch_all(&self.pool)
+            .await,
+            None => sqlx::query_as::<_, model::UnlockRarity6>(
+                "SELECT * FROM unlock_rarity_6 WHERE unlock_level != 0 ORDER BY unit_id, slot_id, unlock_level",
+            )
+            .fetch_all(&self.pool)
+            .await,
+        };
+
+        self.rows_of("unlock_rarity_6", rows)
+    }
+
+    async fn unit_unique_equip(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitUniqueEquip>, Error> {
+        self.fetch(
+            "unit_unique_equip",
+            "unit_id",
+            unit_id,
+            "unit_id, equip_slot ASC",
+        )
+        .await
+    }
+
+    async fn unit_skill_data(
+        &self,
+        unit_id: Option<i64>,
+    ) -> Result<Vec<model::UnitSkillData>, Error> {
+        self.fetch("unit_skill_data", "unit_id", unit_id, "").await
+    }
+
+    async fn chara_story_status(
+        &self,
+        chara_id: Option<i64>,
+    ) -> Result<Vec<model::CharaStoryStatus>, Error> {
+        let rows = match chara_id {
+            Some(chara_id) => sqlx::query_as::<_, Row<model::CharaStoryStatus>>(
+                "SELECT * FROM chara_story_status WHERE $1 in (chara_id_1, chara_id_2, chara_id_3, chara_id_4, chara_id_5, chara_id_6, chara_id_7, chara_id_8, chara_id_9, chara_id_10) ORDER BY story_id ASC",
+            )
+            .bind(chara_id)
+            .fetch_all(&self.pool)
+            .await,
+            None => sqlx::query_as::<_, Row<model::CharaStoryStatus>>(
+                "SELECT * FROM chara_story_status ORDER BY story_id ASC",
+            )
+            .fetch_all(&self.pool)
+            .await,
+        };
+
+        let rows = self.rows_of("chara_story_status", rows)?;
+        Ok(rows.into_iter().map(|Row(row)| row).collect())
+    }
+
+    async fn equipment_data(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::EquipmentData>, Error> {
+        self.fetch("equipment_data", "equipment_id", equipment_id, "")
+            .await
+    }
+
+    async fn equipment_enhance_rate(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::EquipmentEnhanceRate>, Error> {
+        self.fetch("equipment_enhance_rate", "equipment_id", equipment_id, "")
+            .await
+    }
+
+    async fn unique_equipment_data(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::UniqueEquipmentData>, Error> {
+        self.fetch("unique_equipment_data", "equipment_id", equipment_id, "")
+            .await
+    }
+
+    async fn unique_equipment_enhance_rate(
+        &self,
+        equipment_id: Option<i64>,
+    ) -> Result<Vec<model::UniqueEquipmentEnhanceRate>, Error> {
+        self.fetch(
+            "unique_equipment_enhance_rate",
+            "equipment_id",
+            equipment_id,
+            "",
+        )
+        .await
+    }
+
+    async fn max_unit_level(&self) -> Result<i32, Error> {
+        let level =
+            sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(unit_level) FROM experience_unit")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|error| Error::from_query("experience_unit", error))?;
+
+        Ok(level.ok_or(DataError::MaxUnitLevelNotFound)? as i32)
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::manager::DataManager;
+use crate::model;
+
+/// Time as written in the master database, like `2018/02/15 15:00:00`
+///
+/// Times are in the server's local time zone, so only compare times of the
+/// same server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl GameTime {
+    pub fn new(year: u16, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        }
+    }
+
+    pub fn and_hms(self, hour: u8, minute: u8, second: u8) -> Self {
+        Self {
+            hour,
+            minute,
+            second,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for GameTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}/{:02}/{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid time {0}, expected YYYY/MM/DD [HH:MM[:SS]]")]
+pub struct ParseGameTimeError(String);
+
+impl FromStr for GameTime {
+    type Err = ParseGameTimeError;
+
+    /// Parse `YYYY/MM/DD HH:MM:SS`, `-` is also accepted between date parts
+    /// and the time may be left out
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseGameTimeError(s.to_string());
+
+        let mut parts = s.trim().splitn(2, [' ', 'T']);
+        let date: Vec<&str> = parts.next().ok_or_else(error)?.split(['/', '-']).collect();
+        let time: Vec<&str> = match parts.next() {
+            Some(time) => time.trim().split(':').collect(),
+            None => vec![],
+        };
+        if date.len() != 3 || time.len() > 3 || time.len() == 1 {
+            return Err(error());
+        }
+
+        let number = |part: &str| part.parse::<u16>().map_err(|_| error());
+        let small = |part: Option<&&str>| match part {
+            Some(part) => u8::try_from(number(part)?).map_err(|_| error()),
+            None => Ok(0),
+        };
+
+        let time = GameTime {
+            year: number(date[0])?,
+            month: small(date.get(1))?,
+            day: small(date.get(2))?,
+            hour: small(time.first())?,
+            minute: small(time.get(1))?,
+            second: small(time.get(2))?,
+        };
+        if !(1..=12).contains(&time.month)
+            || !(1..=31).contains(&time.day)
+            || time.hour > 23
+            || time.minute > 59
+            || time.second > 59
+        {
+            return Err(error());
+        }
+
+        Ok(time)
+    }
+}
+
+impl model::UnitData {
+    pub fn start(&self) -> Option<GameTime> {
+        self.start_time.parse().ok()
+    }
+
+    pub fn end(&self) -> Option<GameTime> {
+        self.end_time.parse().ok()
+    }
+
+    /// Whether the unit was only available for a limited time
+    pub fn limited(&self) -> bool {
+        self.is_limited != 0
+    }
+
+    /// Whether the unit is released and not expired at `time`
+    ///
+    /// A unit whose start time can't be read is never available, one whose
+    /// end time can't be read never expires.
+    pub fn is_available_at(&self, time: GameTime) -> bool {
+        let started = self.start().is_some_and(|start| start <= time);
+        let expired = self.end().is_some_and(|end| end <= time);
+        started && !expired
+    }
+}
+
+/// Release of a unit, see [`DataManager::release_timeline`]
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub unit_id: i64,
+    pub unit_name: String,
+    pub start: GameTime,
+    pub end: Option<GameTime>,
+    pub is_limited: bool,
+}
+
+impl fmt::Display for Release {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.start, self.unit_id, self.unit_name)?;
+        if self.is_limited {
+            write!(f, " (limited)")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DataManager {
+    /// Only show units available at `time`, `None` to show every unit
+    ///
+    /// Affects [`DataManager::unit_ids`], [`DataManager::unit_data`] and
+    /// everything built on them, like unit caches. Shared unit caches are
+    /// dropped, since they may hold hidden units.
+    pub fn set_as_of(&mut self, time: Option<GameTime>) {
+        self.as_of = time;
+        self.registry.clear();
+    }
+
+    pub fn as_of(&self) -> Option<GameTime> {
+        self.as_of
+    }
+
+    /// Units available at `time`, ignoring the as of date of the manager
+    pub async fn units_at(&self, time: GameTime) -> Result<Vec<model::UnitData>, Error> {
+        Ok(self
+            .source
+            .unit_data(None)
+            .await?
+            .into_iter()
+            .filter(|unit| unit.is_available_at(time))
+            .collect())
+    }
+
+    /// Units in order of release, up to the as of date if it's set
+    ///
+    /// Units whose start time can't be read are left out.
+    pub async fn release_timeline(&self) -> Result<Vec<Release>, Error> {
+        let mut releases: Vec<Release> = self
+            .source
+            .unit_data(None)
+            .await?
+            .into_iter()
+            .filter_map(|unit| {
+                Some(Release {
+                    start: unit.start()?,
+                    end: unit.end(),
+                    is_limited: unit.limited(),
+                    unit_id: unit.unit_id,
+                    unit_name: unit.unit_name,
+                })
+            })
+            .filter(|release| self.as_of.is_none_or(|time| release.start <= time))
+            .collect();
+        releases.sort_by_key(|release| (release.start, release.unit_id));
+
+        Ok(releases)
+    }
+
+    /// Ids of units available at the as of date, `None` if it's not set
+    ///
+    /// Preloaded rows are used if there are any.
+    pub(crate) async fn visible_unit_ids(&self) -> Result<Option<HashSet<i64>>, Error> {
+        let time = match self.as_of {
+            Some(time) => time,
+            None => return Ok(None),
+        };
+
+        let visible = match &self.preloaded {
+            Some(preloaded) => preloaded
+                .units
+                .values()
+                .filter(|unit| unit.is_available_at(time))
+                .map(|unit| unit.unit_id)
+                .collect(),
+            None => self
+                .units_at(time)
+                .await?
+                .into_iter()
+                .map(|unit| unit.unit_id)
+                .collect(),
+        };
+
+        Ok(Some(visible))
+    }
+
+    /// Whether a unit is available at the as of date
+    ///
+    /// Preloaded rows are used if there are any.
+    pub(crate) async fn is_visible(&self, unit_id: i64) -> Result<bool, Error> {
+        let time = match self.as_of {
+            Some(time) => time,
+            None => return Ok(true),
+        };
+
+        if let Some(preloaded) = &self.preloaded {
+            return Ok(preloaded
+                .units
+                .get(&unit_id)
+                .is_some_and(|unit| unit.is_available_at(time)));
+        }
+
+        Ok(self
+            .source
+            .unit_data(Some(unit_id))
+            .await?
+            .iter()
+            .any(|unit| unit.is_available_at(time)))
+    }
+}
@@ -1,198 +1,87 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use super::data::*;
-use super::utils::*;
-use crate::manager::{DataManager, DataManagerError};
+use crate::error::{DataError, Error};
+use crate::manager::DataManager;
 use crate::model;
 use crate::unit::*;
 
-// TODO: conversion unit
-
-/// Cached data for faster power calculation
-#[derive(Debug)]
-pub struct UnitCache {
-    /// Id
-    pub unit_id: i64,
-    /// Skill
-    pub skill: UnitSkill,
-    /// Star
-    pub rarity: Vec<UnitRarityCache>,
-    /// Rank promotions
-    pub promotion: Vec<UnitPromotionCache>,
-    /// Union burst
-    pub unique_equip: Option<UniqueEquipmentCache>,
-    /// Unlock rarity 6 slot
-    pub unlock_rarity_6: Option<[Vec<UnlockRarity6Cache>; 3]>,
-    /// Stories
-    pub story: HashMap<i64, StoryData>,
-    pub status_coefficient: model::UnitStatusCoefficient,
-    pub status_coefficient_cache: UnitStatus<f64>,
-}
-
-impl UnitCache {
-    pub fn base_param(&self, rank: i32, level: i32, rarity: i32) -> UnitStatus<i64> {
-        let rarity = &self.rarity[rarity as usize - 1];
-        let rank_up = rank_up_bonus(rank);
-        let mut param = rarity.status;
-        param += rarity.growth * (level as f64);
-        param += rarity.growth.component_mul(&rank_up);
-
-        let promotion = &self.promotion[rank as usize - 1];
-        if promotion.status.is_some() {
-            param += promotion.status.unwrap();
-        }
-        if promotion.bonus.is_some() {
-            param += promotion.bonus.unwrap();
+impl DataManager {
+    async fn equipment_cache(
+        &self,
+        equipment_id: i64,
+    ) -> Result<Arc<EquipmentCache>, Error> {
+        match &self.preloaded {
+            Some(preloaded) => match preloaded.equipment.get(&equipment_id) {
+                Some(equipment) => Ok(equipment.clone()?),
+                None => Err(DataError::EquipmentNotFound {
+                    unit_id: None,
+                    equipment_id,
+                }
+                .into()),
+            },
+            None => Ok(Arc::new(self.equip_data(equipment_id).await?.cached())),
         }
-
-        param.map(|x| x.cy_round())
     }
 
-    pub fn equip_param(&self, rank: i32, equip: &[(bool, i32)]) -> UnitStatus<f64> {
-        let promotion = &self.promotion[rank as usize - 1];
-        let mut param = UnitStatus::zeros();
-        promotion.equipments.iter().zip(equip.iter()).for_each(
-            |(equipment, (equipped, enhance_level))| {
-                if !(*equipped) {
-                    return;
-                }
-
-                if equipment.is_none() {
-                    return;
+    async fn unique_equipment_cache(
+        &self,
+        unique_equipment_id: i64,
+    ) -> Result<UniqueEquipmentCache, Error> {
+        match &self.preloaded {
+            Some(preloaded) => match preloaded.unique_equipment.get(&unique_equipment_id) {
+                Some(equipment) => Ok(equipment.clone()?),
+                None => Err(DataError::EquipmentNotFound {
+                    unit_id: None,
+                    equipment_id: unique_equipment_id,
                 }
-
-                param += equipment.as_ref().unwrap().param(*enhance_level);
+                .into()),
             },
-        );
-
-        param
-    }
-
-    pub fn unique_equip_param(&self, enhancement_level: i32) -> UnitStatus<f64> {
-        self.unique_equip.as_ref().unwrap().param(enhancement_level)
-    }
-
-    pub fn unlock_rarity_6_param(&self, level: [i32; 3]) -> UnitStatus<i64> {
-        let mut param = UnitStatus::zeros();
-
-        self.unlock_rarity_6
-            .as_ref()
-            .unwrap()
-            .iter()
-            .zip(level)
-            .filter(|(_, l)| *l > 0)
-            .map(|(r, l)| &r[l as usize - 1])
-            .for_each(|r| {
-                param += r.status;
-            });
-
-        param
-    }
-
-    pub fn story_param(&self, watched_bonus: HashMap<i64, i64>) -> UnitStatus<i64> {
-        let mut param = UnitStatus::zeros();
-        for (group_id, stories) in self.story.iter() {
-            let watched = watched_bonus.get(group_id).unwrap_or(&0);
-            stories.param_append(*watched as usize, &mut param);
+            None => Ok(self.unique_equip_data(unique_equipment_id).await?.cached()),
         }
-
-        param
-    }
-
-    pub fn skill_level(&self, level: i32, rarity: i32, is_unique_equipped: bool) -> f64 {
-        skill_level(
-            &self.skill,
-            level,
-            &self.status_coefficient,
-            rarity,
-            rarity >= 6 || is_unique_equipped,
-        )
     }
-}
 
-#[derive(Debug)]
-pub struct UnlockRarity6Cache {
-    pub status: UnitStatus<i64>,
-}
-
-#[derive(Debug)]
-pub struct EquipmentCache {
-    pub id: i64,
-    pub data: UnitStatus<f64>,
-    pub enhance_rate: UnitStatus<f64>,
-    pub max_enhance_level: i32,
-}
-
-impl EquipmentCache {
-    pub fn param(&self, enhancement_level: i32) -> UnitStatus<f64> {
-        self.data
-            + (self.enhance_rate
-                * (enhancement_level + model::EquipmentEnhanceRate::ENHANCE_LV_OFFSET) as f64)
-                .map(|x| x.ceil())
-    }
-}
+    /// Ids of all units that have rarity and promotion data, and are
+    /// available at the as of date if it's set
+    pub async fn unit_ids(&self) -> Result<Vec<i64>, Error> {
+        let unit_ids = match &self.preloaded {
+            Some(preloaded) => preloaded.unit_ids(),
+            None => self.source.unit_ids().await?,
+        };
 
-impl Slot for EquipmentCache {
-    fn slot(&self) -> EquipSlot {
-        EquipSlot::Unequipped {
-            id: self.id,
-            max_enhancement_level: Some(self.max_enhance_level),
+        match self.visible_unit_ids().await? {
+            Some(visible) => Ok(unit_ids
+                .into_iter()
+                .filter(|unit_id| visible.contains(unit_id))
+                .collect()),
+            None => Ok(unit_ids),
         }
     }
-}
-
-#[derive(Debug)]
-pub struct UniqueEquipmentCache {
-    pub id: i64,
-    pub status: UnitStatus<f64>,
-    pub enhance_rate: UnitStatus<f64>,
-    pub max_enhancement_level: i32,
-}
-
-impl UniqueEquipmentCache {
-    pub fn param(&self, enhancement_level: i32) -> UnitStatus<f64> {
-        self.status
-            + (self.enhance_rate
-                * (enhancement_level + model::UniqueEquipmentEnhanceRate::ENHANCE_LV_OFFSET) as f64)
-                .map(|x| x.ceil())
-    }
-}
 
-impl Slot for UniqueEquipmentCache {
-    fn slot(&self) -> EquipSlot {
-        EquipSlot::Unequipped {
-            id: self.id,
-            max_enhancement_level: Some(self.max_enhancement_level),
+    /// Caches of all units, see [`DataManager::preload`] to make it fast
+    pub async fn unit_caches(&self) -> Result<HashMap<i64, UnitCache>, Error> {
+        let mut caches = HashMap::new();
+        for unit_id in self.unit_ids().await? {
+            caches.insert(unit_id, self.unit_cache(unit_id).await?);
         }
+
+        Ok(caches)
     }
-}
 
-#[derive(Debug)]
-pub struct UnitPromotionCache {
-    pub equipments: Vec<Option<EquipmentCache>>,
-    pub status: Option<UnitStatus<f64>>,
-    pub bonus: Option<UnitStatus<f64>>,
-}
+    /// Cache of a unit and the power of its [`UnitCache::max_state`] at `level`
+    pub async fn max_power(&self, unit_id: i64, level: i32) -> Result<(UnitCache, f64), Error> {
+        let cache = self.unit_cache(unit_id).await?;
+        let state = cache.max_state(level);
+        let power = BorrowedUnitCalculator::new(&cache, &state).try_power()?;
 
-impl UnitPromotionCache {
-    pub fn equipments_to_slots(&self) -> Vec<EquipSlot> {
-        self.equipments
-            .iter()
-            .map(|e| e.as_ref().map_or(EquipSlot::None, |e| e.slot()))
-            .collect()
+        Ok((cache, power))
     }
-}
 
-#[derive(Debug)]
-pub struct UnitRarityCache {
-    pub status: UnitStatus<f64>,
-    pub growth: UnitStatus<f64>,
-}
-
-impl DataManager {
-    pub async fn unit_cache(&self, unit_id: i64) -> Result<UnitCache, DataManagerError> {
+    pub async fn unit_cache(&self, unit_id: i64) -> Result<UnitCache, Error> {
         let unit_config = self.unit_data(unit_id).await?;
 
+        // Units often keep an equipment over several ranks
+        let mut equipments: HashMap<i64, Arc<EquipmentCache>> = HashMap::new();
         let mut promotion_cache = vec![];
         for promotion in unit_config.promotion.iter() {
             let promotion_status: Option<UnitStatus<f64>> =
@@ -204,8 +93,15 @@ impl DataManager {
             for equipment_id in promotion.promotion.equip_slot {
                 if equipment_id == 999999 {
                     equipment_status.push(None)
+                } else if let Some(equipment) = equipments.get(&equipment_id) {
+                    equipment_status.push(Some(equipment.clone()))
                 } else {
-                    equipment_status.push(Some(self.equip_data(equipment_id).await?.cached()));
+                    let equipment = self
+                        .equipment_cache(equipment_id)
+                        .await
+                        .map_err(|e| e.in_unit(unit_id))?;
+                    equipments.insert(equipment_id, equipment.clone());
+                    equipment_status.push(Some(equipment));
                 }
             }
 
@@ -218,14 +114,14 @@ impl DataManager {
 
         let unique_equip = if unit_config.unique_equip.len() == 1 {
             Some(
-                self.unique_equip_data(unit_config.unique_equip[0].equip_id)
-                    .await?
-                    .cached(),
+                self.unique_equipment_cache(unit_config.unique_equip[0].equip_id)
+                    .await
+                    .map_err(|e| e.in_unit(unit_id))?,
             )
         } else if unit_config.unique_equip.is_empty() {
             None
         } else {
-            return Err(DataManagerError::InvalidUniqueEquipMoreThanOne(unit_id))
+            return Err(DataError::MoreThanOneUniqueEquipment { unit_id }.into());
         };
 
         let unlock_rarity_6_status = if let Some(config_unlock_rarity_6) =
@@ -236,8 +132,11 @@ impl DataManager {
                 .iter_mut()
                 .zip(config_unlock_rarity_6.iter())
             {
-                *status = data_status
-                    .iter()
+                // Indexed by unlock level - 1
+                let mut levels: Vec<_> = data_status.iter().collect();
+                levels.sort_unstable_by_key(|(unlock_level, _)| **unlock_level);
+                *status = levels
+                    .into_iter()
                     .map(|(_, unlock)| unlock.cached())
                     .collect();
             }
@@ -289,3 +188,15 @@ impl super::data::EquipmentData {
         }
     }
 }
+
+impl DataManager {
+    /// Snapshot of every unit, `source_version` is the version of the master
+    /// database recorded in the header
+    pub async fn snapshot(&self, source_version: &str) -> Result<UnitSnapshot, Error> {
+        Ok(UnitSnapshot::new(
+            source_version,
+            self.status_coefficient.clone(),
+            self.unit_caches().await?,
+        ))
+    }
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
-use tokio_stream::StreamExt;
+use std::sync::Arc;
 
-use super::cache::UniqueEquipmentCache;
-use crate::manager::{DataManager, DataManagerError};
+use crate::error::{DataError, Error};
+use crate::manager::DataManager;
 use crate::model;
 use crate::unit::*;
 
@@ -15,10 +15,89 @@ pub struct UnitData {
     pub unique_equip: Vec<model::UnitUniqueEquip>,
     pub unlock_rarity_6: Option<[HashMap<i64, model::UnlockRarity6>; 3]>,
     pub skill_data: model::UnitSkillData,
-    pub stories: HashMap<i64, StoryData>,
+    pub stories: HashMap<i64, Arc<StoryData>>,
+}
+
+/// Rows of one unit, as loaded from the master database
+pub(crate) struct UnitDataRows {
+    pub promotion: Vec<model::UnitPromotion>,
+    pub promotion_status: Vec<model::UnitPromotionStatus>,
+    pub promotion_bonus: Vec<model::PromotionBonus>,
+    pub rarity: Vec<model::UnitRarity>,
+    pub unlock_rarity_6: Vec<model::UnlockRarity6>,
+    pub unique_equip: Vec<model::UnitUniqueEquip>,
+    pub skill_data: model::UnitSkillData,
 }
 
 impl UnitData {
+    /// Build unit data from its rows, sorted the same way as the queries in
+    /// [`DataManager::unit_data`]
+    pub(crate) fn assemble(
+        unit_id: i64,
+        rows: UnitDataRows,
+        stories: HashMap<i64, Arc<StoryData>>,
+    ) -> Result<UnitData, Error> {
+        let mut promotion: Vec<UnitPromotion> = rows
+            .promotion
+            .into_iter()
+            .map(|promotion| UnitPromotion {
+                promotion,
+                bonus: None,
+                status: None,
+            })
+            .collect();
+
+        for promotion_status in rows.promotion_status.into_iter() {
+            let rank = promotion_status.promotion_level;
+            promotion_of(unit_id, &mut promotion, rank)?.status = Some(promotion_status);
+        }
+
+        for promotion_bonus in rows.promotion_bonus.into_iter() {
+            let rank = promotion_bonus.promotion_level;
+            promotion_of(unit_id, &mut promotion, rank)?.bonus = Some(promotion_bonus);
+        }
+
+        let unlock_rarity_6 = if !rows.unlock_rarity_6.is_empty() {
+            let mut slots = [
+                HashMap::<i64, model::UnlockRarity6>::new(),
+                HashMap::<i64, model::UnlockRarity6>::new(),
+                HashMap::<i64, model::UnlockRarity6>::new(),
+            ];
+
+            for row in rows.unlock_rarity_6.into_iter() {
+                if !(1..=3).contains(&row.slot_id) {
+                    return Err(DataError::Rarity6SlotOutOfRange {
+                        unit_id,
+                        slot: row.slot_id,
+                    }
+                    .into());
+                }
+
+                slots[row.slot_id as usize - 1].insert(row.unlock_level, row);
+            }
+
+            for (i, slot) in slots.iter().enumerate() {
+                if slot.is_empty() {
+                    return Err(DataError::Rarity6SlotNotFound { unit_id, slot: i + 1 }.into());
+                }
+            }
+
+            Some(slots)
+        } else {
+            None
+        };
+
+        Ok(UnitData {
+            unit_id,
+            rarity: rows.rarity,
+            unlock_rarity_6,
+            promotion,
+            unique_equip: rows.unique_equip,
+            skill_data: rows.skill_data,
+            stories,
+        })
+    }
+
     pub fn base_param(&self, level: i32, rarity: i32, rank: i32) -> UnitStatus<i64> {
         let promotion = self.promotion[rank as usize - 1].clone();
         let rarity = self.rarity[rarity as usize - 1].clone();
@@ -41,52 +120,26 @@ impl UnitData {
     }
 }
 
-#[derive(Debug, Default)]
-pub struct StoryData(pub Vec<model::CharaStoryStatus>);
-
-impl StoryData {
-    pub fn param_append(&self, bonus_stories: usize, param: &mut UnitStatus<i64>) {
-        self.0
-            .iter()
-            .take(bonus_stories)
-            .flat_map(|status| &status.status)
-            .for_each(|(index, value)| {
-                param[*index as usize - 1] += value;
-            });
-    }
-
-    pub fn param(&self, bonus_stories: usize) -> UnitStatus<i64> {
-        let mut param = UnitStatus::<i64>::zeros();
-        self.0
-            .iter()
-            .take(bonus_stories)
-            .flat_map(|status| &status.status)
-            .for_each(|(index, value)| {
-                param[*index as usize - 1] += value;
-            });
-
-        param
-    }
-
-    pub fn param_all(&self) -> UnitStatus<i64> {
-        let mut param = UnitStatus::<i64>::zeros();
-        self.0
-            .iter()
-            .flat_map(|status| &status.status)
-            .for_each(|(index, value)| {
-                param[*index as usize - 1] += value;
-            });
-
-        param
+/// Promotion of a rank, the rows are sorted by rank without gaps
+fn promotion_of(
+    unit_id: i64,
+    promotion: &mut [UnitPromotion],
+    rank: i64,
+) -> Result<&mut UnitPromotion, DataError> {
+    let promotion = usize::try_from(rank - 1)
+        .ok()
+        .and_then(|index| promotion.get_mut(index))
+        .ok_or(DataError::MissingPromotion { unit_id, rank })?;
+    let found = promotion.promotion.promotion_level;
+    if found != rank {
+        return Err(DataError::PromotionMismatch {
+            unit_id,
+            rank,
+            found,
+        });
     }
 
-    pub fn story_group(&self) -> StoryGroup {
-        StoryGroup {
-            story_group_id: self.0[0].story_id / 1000,
-            total: self.0.len(),
-            watched: 0,
-        }
-    }
+    Ok(promotion)
 }
 
 #[derive(Debug, Clone)]
@@ -138,218 +191,158 @@ impl UniqueEquipmentData {
 }
 
 impl DataManager {
-    pub async fn equip_data(&self, equipment_id: i64) -> Result<EquipmentData, DataManagerError> {
+    pub async fn equip_data(&self, equipment_id: i64) -> Result<EquipmentData, Error> {
         if equipment_id == 999999i64 {
-            return Err(DataManagerError::EquipNotFound(equipment_id));
+            return Err(DataError::EquipmentNotFound {
+                unit_id: None,
+                equipment_id,
+            }
+            .into());
         }
 
-        let equipment_data = sqlx::query_as::<_, model::EquipmentData>(
-            "SELECT * FROM equipment_data WHERE equipment_id == $1",
-        )
-        .bind(equipment_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        let equipment_enhance_rate = sqlx::query_as::<_, model::EquipmentEnhanceRate>(
-            "SELECT * FROM equipment_enhance_rate WHERE equipment_id == $1",
-        )
-        .bind(equipment_id)
-        .fetch_one(&self.pool)
-        .await?;
+        let equipment_data = self
+            .source
+            .equipment_data(Some(equipment_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(DataError::EquipmentNotFound {
+                unit_id: None,
+                equipment_id,
+            })?;
+
+        let equipment_enhance_rate = self
+            .source
+            .equipment_enhance_rate(Some(equipment_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(DataError::EquipmentEnhanceRateNotFound {
+                unit_id: None,
+                equipment_id,
+            })?;
 
-        const EMPTY_VECTOR: Vec<model::EquipmentEnhanceData> = Vec::new();
-        let max_enhance_level = self
-            .equipment_enhance_data
-            .get(&equipment_data.promotion_level)
-            .unwrap_or(&EMPTY_VECTOR)
-            .iter()
-            .map(|x| x.equipment_enhance_level)
-            .max()
-            .unwrap_or(0);
+        let max_enhance_level = self.max_enhance_level(equipment_data.promotion_level);
 
         Ok(EquipmentData {
             id: equipment_id,
             data: equipment_data,
             enhance_rate: equipment_enhance_rate,
-            max_enhance_level: max_enhance_level as i32,
+            max_enhance_level,
         })
     }
 
-    pub async fn unique_equip_data(
+    /// Max enhancement level of equipments of a promotion level
+    pub(crate) fn max_enhance_level(&self, promotion_level: i64) -> i32 {
+        const EMPTY_VECTOR: Vec<model::EquipmentEnhanceData> = Vec::new();
+        self.equipment_enhance_data
+            .get(&promotion_level)
+            .unwrap_or(&EMPTY_VECTOR)
+            .iter()
+            .map(|x| x.equipment_enhance_level)
+            .max()
+            .unwrap_or(0) as i32
+    }
+
+    /// Max enhancement level of the first unique equipment slot, the table
+    /// is empty in a database without unique equipments
+    pub(crate) fn max_unique_enhancement_level(
         &self,
         unique_equipment_id: i64,
-    ) -> Result<UniqueEquipmentData, DataManagerError> {
-        let unique_equipment_data = sqlx::query_as::<_, model::UniqueEquipmentData>(
-            "SELECT * FROM unique_equipment_data WHERE equipment_id == $1",
-        )
-        .bind(unique_equipment_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        let unique_equipment_enhance_rate = sqlx::query_as::<_, model::UniqueEquipmentEnhanceRate>(
-            "SELECT * FROM unique_equipment_enhance_rate WHERE equipment_id == $1",
-        )
-        .bind(unique_equipment_id)
-        .fetch_one(&self.pool)
-        .await?;
-
+    ) -> Result<i32, DataError> {
         let max_enhancement_level = self
             .unique_equipment_enhance_data
             .get(&1)
-            .unwrap()
-            .iter()
+            .into_iter()
+            .flatten()
             .map(|x| x.enhance_level)
             .max()
-            .ok_or(DataManagerError::UniqueEquipmentEnhanceData(
-                unique_equipment_id,
-            ))?;
+            .ok_or(DataError::UniqueEquipmentEnhanceDataNotFound {
+                unit_id: None,
+                equipment_id: unique_equipment_id,
+            })?;
+
+        Ok(max_enhancement_level as i32)
+    }
+
+    pub async fn unique_equip_data(
+        &self,
+        unique_equipment_id: i64,
+    ) -> Result<UniqueEquipmentData, Error> {
+        let unique_equipment_data = self
+            .source
+            .unique_equipment_data(Some(unique_equipment_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(DataError::EquipmentNotFound {
+                unit_id: None,
+                equipment_id: unique_equipment_id,
+            })?;
+
+        let unique_equipment_enhance_rate = self
+            .source
+            .unique_equipment_enhance_rate(Some(unique_equipment_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(DataError::UniqueEquipmentEnhanceDataNotFound {
+                unit_id: None,
+                equipment_id: unique_equipment_id,
+            })?;
+
+        let max_enhancement_level = self.max_unique_enhancement_level(unique_equipment_id)?;
 
         Ok(UniqueEquipmentData {
             id: unique_equipment_id,
             data: unique_equipment_data,
             enhance_rate: unique_equipment_enhance_rate,
-            max_enhancement_level: max_enhancement_level as i32,
+            max_enhancement_level,
         })
     }
 
-    pub async fn unit_data(&self, unit_id: i64) -> Result<UnitData, DataManagerError> {
-        // let promotion_bonus = HashMap::new();
-
-        // RIGHT and FULL OUTER JOINs are not currently supported
-        let mut promotion: Vec<UnitPromotion> = sqlx::query_as::<_, model::UnitPromotion>(
-            "SELECT * FROM unit_promotion WHERE unit_promotion.unit_id == $1 ORDER BY promotion_level ASC",
-        )
-        .bind(unit_id)
-        // .bind(rank)
-        .fetch_all(&self.pool)
-        .await?
-        .into_iter()
-        .map(|promotion| {
-            UnitPromotion {
-                promotion,
-                bonus: None,
-                status: None
-            }
-        })
-        .collect();
-
-        let mut promotion_status = sqlx::query_as::<_, model::UnitPromotionStatus>(
-            "SELECT * FROM unit_promotion_status WHERE unit_id == $1 ORDER BY promotion_level ASC",
-        )
-        .bind(unit_id)
-        // .bind(rank)
-        .fetch(&self.pool);
-
-        while let Some(promotion_status) = promotion_status.try_next().await? {
-            let rank = promotion_status.promotion_level as usize;
-            assert_eq!(
-                promotion[rank - 1].promotion.promotion_level,
-                promotion_status.promotion_level
-            );
-            promotion[rank - 1].status = Some(promotion_status);
+    pub async fn unit_data(&self, unit_id: i64) -> Result<UnitData, Error> {
+        if !self.is_visible(unit_id).await? {
+            return Err(DataError::UnitNotFound { unit_id }.into());
         }
 
-        let mut promotion_bonus = sqlx::query_as::<_, model::PromotionBonus>(
-            "SELECT * FROM promotion_bonus WHERE unit_id == $1",
-        )
-        .bind(unit_id)
-        // .bind(rank)
-        .fetch(&self.pool);
-
-        while let Some(promotion_bonus) = promotion_bonus.try_next().await? {
-            let rank = promotion_bonus.promotion_level as usize;
-            promotion[rank - 1].bonus = Some(promotion_bonus);
+        if let Some(preloaded) = &self.preloaded {
+            return preloaded.unit_data(unit_id);
         }
 
-        let rarity = sqlx::query_as::<_, model::UnitRarity>(
-            "SELECT * FROM unit_rarity WHERE unit_id == $1 ORDER BY rarity ASC",
-        )
-        .bind(unit_id)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let unlock_rarity_6_rows: Vec<model::UnlockRarity6> =
-            sqlx::query_as::<_, model::UnlockRarity6>(
-                "SELECT * FROM unlock_rarity_6 WHERE unit_id == $1 AND unlock_level != 0 ORDER BY slot_id, unlock_level",
-            )
-            .bind(unit_id)
-            .fetch_all(&self.pool)
+        let unit = Some(unit_id);
+        let promotion = self.source.unit_promotion(unit).await?;
+        let promotion_status = self.source.unit_promotion_status(unit).await?;
+        let promotion_bonus = self.source.promotion_bonus(unit).await?;
+        let rarity = self.source.unit_rarity(unit).await?;
+        let unlock_rarity_6 = self.source.unlock_rarity_6(unit).await?;
+        let unique_equip = self.source.unit_unique_equip(unit).await?;
+        let skill_data = self
+            .source
+            .unit_skill_data(unit)
             .await?
             .into_iter()
-            .collect();
+            .next()
+            .ok_or(DataError::UnitNotFound { unit_id })?;
+        let story_bonus_vec = self.source.chara_story_status(Some(unit_id / 100)).await?;
 
-        let unlock_rarity_6 = if !unlock_rarity_6_rows.is_empty() {
-            let mut slots = [
-                HashMap::<i64, model::UnlockRarity6>::new(),
-                HashMap::<i64, model::UnlockRarity6>::new(),
-                HashMap::<i64, model::UnlockRarity6>::new(),
-            ];
-
-            for row in unlock_rarity_6_rows.into_iter() {
-                if row.slot_id > 3 {
-                    return Err(DataManagerError::UnlockRarity6SlotOutOfRange(row.slot_id));
-                }
-
-                slots[row.slot_id as usize - 1].insert(row.unlock_level, row);
-            }
-
-            for (i, slot) in slots.iter().enumerate() {
-                if slot.is_empty() {
-                    return Err(DataManagerError::UnlockRarity6NotFound(i));
-                }
-            }
-
-            Some(slots)
-        } else {
-            None
-        };
-
-        // .map(|unlock_rarity_6| {
-        //     (
-        //         (unlock_rarity_6.slot_id, unlock_rarity_6.unlock_level),
-        //         unlock_rarity_6,
-        //     )
-        // })
-        // .collect();
-
-        let unique_equip = sqlx::query_as::<_, model::UnitUniqueEquip>(
-            "SELECT * FROM unit_unique_equip WHERE unit_id == $1 ORDER BY equip_slot ASC",
-        )
-        .bind(unit_id)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let skill_data = sqlx::query_as::<_, model::UnitSkillData>(
-            "SELECT * FROM unit_skill_data WHERE unit_id == $1",
-        )
-        .bind(unit_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        let story_bonus_vec = sqlx::query_as::<_, model::CharaStoryStatus>(
-            "SELECT * FROM chara_story_status WHERE $1 in (chara_id_1, chara_id_2, chara_id_3, chara_id_4, chara_id_5, chara_id_6, chara_id_7, chara_id_8, chara_id_9, chara_id_10) ORDER BY story_id ASC",
-        )
-        .bind(unit_id / 100)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let mut story_status: HashMap<i64, StoryData> = HashMap::new();
-
-        for story_bonus_item in story_bonus_vec.into_iter() {
-            let story_id = story_bonus_item.story_id;
-            let story_group_id = story_id / 1000;
-            let entry = story_status.entry(story_group_id).or_default();
-            entry.0.push(story_bonus_item);
-        }
+        let stories = StoryData::group(story_bonus_vec)
+            .into_iter()
+            .map(|(story_group_id, story)| (story_group_id, Arc::new(story)))
+            .collect();
 
-        Ok(UnitData {
+        UnitData::assemble(
             unit_id,
-            rarity,
-            unlock_rarity_6,
-            promotion,
-            unique_equip,
-            skill_data,
-            stories: story_status,
-        })
+            UnitDataRows {
+                promotion,
+                promotion_status,
+                promotion_bonus,
+                rarity,
+                unlock_rarity_6,
+                unique_equip,
+                skill_data,
+            },
+            stories,
+        )
     }
 }
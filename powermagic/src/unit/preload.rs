@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::data::*;
+use crate::error::{DataError, Error};
+use crate::manager::DataManager;
+use crate::model;
+use crate::unit::{EquipmentCache, StoryData, UniqueEquipmentCache};
+
+/// Master data loaded in bulk
+///
+/// Building a unit cache from it needs no query. Equipments and story
+/// groups are shared between units through `Arc`.
+#[derive(Debug, Default)]
+pub struct PreloadedData {
+    /// Rows of `unit_data` by unit id, for the as of date
+    pub units: HashMap<i64, model::UnitData>,
+    /// Equipments by id, or the error to report when a unit uses one whose
+    /// data is incomplete
+    pub equipment: HashMap<i64, Result<Arc<EquipmentCache>, DataError>>,
+    pub unique_equipment: HashMap<i64, Result<UniqueEquipmentCache, DataError>>,
+    pub promotion: HashMap<i64, Vec<model::UnitPromotion>>,
+    pub promotion_status: HashMap<i64, Vec<model::UnitPromotionStatus>>,
+    pub promotion_bonus: HashMap<i64, Vec<model::PromotionBonus>>,
+    pub rarity: HashMap<i64, Vec<model::UnitRarity>>,
+    pub unlock_rarity_6: HashMap<i64, Vec<model::UnlockRarity6>>,
+    pub unique_equip: HashMap<i64, Vec<model::UnitUniqueEquip>>,
+    pub skill_data: HashMap<i64, model::UnitSkillData>,
+    /// Story groups of each chara id
+    pub story: HashMap<i64, HashMap<i64, Arc<StoryData>>>,
+}
+
+fn group_by_unit<T>(rows: Vec<T>, unit_id: impl Fn(&T) -> i64) -> HashMap<i64, Vec<T>> {
+    let mut map: HashMap<i64, Vec<T>> = HashMap::new();
+    for row in rows.into_iter() {
+        map.entry(unit_id(&row)).or_default().push(row);
+    }
+
+    map
+}
+
+fn rows_of<T: Clone>(map: &HashMap<i64, Vec<T>>, unit_id: i64) -> Vec<T> {
+    map.get(&unit_id).cloned().unwrap_or_default()
+}
+
+impl PreloadedData {
+    /// Ids of units that have both rarity and promotion data
+    pub fn unit_ids(&self) -> Vec<i64> {
+        let mut unit_ids: Vec<i64> = self
+            .rarity
+            .keys()
+            .filter(|unit_id| self.promotion.contains_key(unit_id))
+            .copied()
+            .collect();
+        unit_ids.sort_unstable();
+
+        unit_ids
+    }
+
+    pub fn unit_data(&self, unit_id: i64) -> Result<UnitData, Error> {
+        let skill_data = self
+            .skill_data
+            .get(&unit_id)
+            .cloned()
+            .ok_or(DataError::UnitNotFound { unit_id })?;
+
+        UnitData::assemble(
+            unit_id,
+            UnitDataRows {
+                promotion: rows_of(&self.promotion, unit_id),
+                promotion_status: rows_of(&self.promotion_status, unit_id),
+                promotion_bonus: rows_of(&self.promotion_bonus, unit_id),
+                rarity: rows_of(&self.rarity, unit_id),
+                unlock_rarity_6: rows_of(&self.unlock_rarity_6, unit_id),
+                unique_equip: rows_of(&self.unique_equip, unit_id),
+                skill_data,
+            },
+            self.story
+                .get(&(unit_id / 100))
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+}
+
+impl DataManager {
+    /// Load every table needed by [`DataManager::unit_cache`] with a few bulk
+    /// queries. Later calls of `unit_data` and `unit_cache` run no query.
+    pub async fn preload(&mut self) -> Result<(), Error> {
+        let equipment_data = self.source.equipment_data(None).await?;
+        let mut equipment_enhance_rate: HashMap<i64, model::EquipmentEnhanceRate> = self
+            .source
+            .equipment_enhance_rate(None)
+            .await?
+            .into_iter()
+            .map(|rate| (rate.equipment_id, rate))
+            .collect();
+
+        let equipment = equipment_data
+            .into_iter()
+            .map(|data| {
+                let equipment_id = data.equipment_id;
+                let equipment = match equipment_enhance_rate.remove(&equipment_id) {
+                    Some(enhance_rate) => Ok(Arc::new(
+                        EquipmentData {
+                            id: equipment_id,
+                            max_enhance_level: self.max_enhance_level(data.promotion_level),
+                            data,
+                            enhance_rate,
+                        }
+                        .cached(),
+                    )),
+                    None => Err(DataError::EquipmentEnhanceRateNotFound {
+                        unit_id: None,
+                        equipment_id,
+                    }),
+                };
+                (equipment_id, equipment)
+            })
+            .collect();
+
+        let unique_equipment_data = self.source.unique_equipment_data(None).await?;
+        let mut unique_equipment_enhance_rate: HashMap<i64, model::UniqueEquipmentEnhanceRate> =
+            self.source
+                .unique_equipment_enhance_rate(None)
+                .await?
+                .into_iter()
+                .map(|rate| (rate.equipment_id, rate))
+                .collect();
+
+        let mut unique_equipment = HashMap::new();
+        for data in unique_equipment_data.into_iter() {
+            let equipment_id = data.equipment_id;
+            let equipment = match unique_equipment_enhance_rate.remove(&equipment_id) {
+                Some(enhance_rate) => {
+                    self.max_unique_enhancement_level(equipment_id)
+                        .map(|max_enhancement_level| {
+                            UniqueEquipmentData {
+                                id: equipment_id,
+                                max_enhancement_level,
+                                data,
+                                enhance_rate,
+                            }
+                            .cached()
+                        })
+                }
+                None => Err(DataError::UniqueEquipmentEnhanceDataNotFound {
+                    unit_id: None,
+                    equipment_id,
+                }),
+            };
+            unique_equipment.insert(equipment_id, equipment);
+        }
+
+        let units = self
+            .source
+            .unit_data(None)
+            .await?
+            .into_iter()
+            .map(|unit| (unit.unit_id, unit))
+            .collect();
+        let promotion = self.source.unit_promotion(None).await?;
+        let promotion_status = self.source.unit_promotion_status(None).await?;
+        let promotion_bonus = self.source.promotion_bonus(None).await?;
+        let rarity = self.source.unit_rarity(None).await?;
+        let unlock_rarity_6 = self.source.unlock_rarity_6(None).await?;
+        let unique_equip = self.source.unit_unique_equip(None).await?;
+        let skill_data = self
+            .source
+            .unit_skill_data(None)
+            .await?
+            .into_iter()
+            .map(|skill| (skill.unit_id, skill))
+            .collect();
+        let story_rows = self.source.chara_story_status(None).await?;
+
+        self.preloaded = Some(PreloadedData {
+            units,
+            equipment,
+            unique_equipment,
+            promotion: group_by_unit(promotion, |row| row.unit_id),
+            promotion_status: group_by_unit(promotion_status, |row| row.unit_id),
+            promotion_bonus: group_by_unit(promotion_bonus, |row| row.unit_id),
+            rarity: group_by_unit(rarity, |row| row.unit_id),
+            unlock_rarity_6: group_by_unit(unlock_rarity_6, |row| row.unit_id),
+            unique_equip: group_by_unit(unique_equip, |row| row.unit_id),
+            skill_data,
+            story: intern_stories(story_rows),
+        });
+
+        Ok(())
+    }
+
+    pub fn is_preloaded(&self) -> bool {
+        self.preloaded.is_some()
+    }
+}
+
+/// Group story rows by chara, sharing identical story groups between charas
+fn intern_stories(
+    rows: Vec<model::CharaStoryStatus>,
+) -> HashMap<i64, HashMap<i64, Arc<StoryData>>> {
+    let mut chara_rows: HashMap<i64, Vec<model::CharaStoryStatus>> = HashMap::new();
+    for row in rows.into_iter() {
+        for chara_id in row.chara_id.iter() {
+            chara_rows.entry(*chara_id).or_default().push(row.clone());
+        }
+    }
+
+    let mut interned: HashMap<Vec<i64>, Arc<StoryData>> = HashMap::new();
+    chara_rows
+        .into_iter()
+        .map(|(chara_id, rows)| {
+            let groups = StoryData::group(rows)
+                .into_iter()
+                .map(|(story_group_id, story)| {
+                    let key = story.0.iter().map(|status| status.story_id).collect();
+                    let story = interned.entry(key).or_insert_with(|| Arc::new(story));
+                    (story_group_id, story.clone())
+                })
+                .collect();
+            (chara_id, groups)
+        })
+        .collect()
+}
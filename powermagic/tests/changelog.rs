@@ -0,0 +1,192 @@
+mod common;
+
+use std::sync::Arc;
+
+use powermagic::changelog::{diff, UnitChange};
+use powermagic::error::{DataError, Error, SchemaError};
+use powermagic::manager::DataManager;
+use powermagic::source::{DataSource, InMemorySource, SqliteSource};
+use powermagic::unit::*;
+use serde_json::json;
+
+use common::{add_unit, data_manager, database, dump, insert, rows, Dump, EQUIPMENT_ID, UNIT_ID};
+
+const NEW_UNIT_ID: i64 = 100201;
+const BROKEN_UNIT_ID: i64 = 100301;
+
+/// Old version, and the new one with a new unit, a new rank, a stronger
+/// rarity 5, another coefficient and a unit whose equipment is missing
+fn versions() -> (Dump, Dump) {
+    let mut old = dump();
+    add_unit(&mut old, BROKEN_UNIT_ID, 999999);
+
+    let mut new = dump();
+    add_unit(&mut new, NEW_UNIT_ID, 999999);
+    add_unit(&mut new, BROKEN_UNIT_ID, EQUIPMENT_ID + 1);
+    rows(&mut new, "unit_status_coefficient")[0]["hp_coefficient"] = json!(0.2);
+    for row in rows(&mut new, "unit_rarity").iter_mut() {
+        if row["unit_id"] == UNIT_ID && row["rarity"] == 5 {
+            row["hp"] = json!(550);
+        }
+    }
+    insert(
+        &mut new,
+        "unit_promotion",
+        json!({
+            "unit_id": UNIT_ID,
+            "promotion_level": 3,
+            "equip_slot_1": EQUIPMENT_ID,
+            "equip_slot_2": 999999,
+            "equip_slot_3": 999999,
+            "equip_slot_4": 999999,
+            "equip_slot_5": 999999,
+            "equip_slot_6": 999999,
+        }),
+    );
+    insert(
+        &mut new,
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 3, "hp": 60}),
+    );
+
+    (old, new)
+}
+
+async fn max_power(data_manager: &DataManager, unit_id: i64, level: i32) -> f64 {
+    let cache = data_manager.unit_cache(unit_id).await.unwrap();
+    let state = cache.max_state(level);
+    BorrowedUnitCalculator::new(&cache, &state).power()
+}
+
+#[tokio::test]
+async fn changelog_between_versions() {
+    let (old, new) = versions();
+    let old = data_manager(&old).await;
+    let new = data_manager(&new).await;
+
+    let changelog = diff(&old, 10, &new, 20).await.unwrap();
+
+    let coefficient: Vec<_> = changelog
+        .coefficient
+        .iter()
+        .map(|change| (change.name.as_str(), change.old, change.new))
+        .collect();
+    assert_eq!(coefficient, vec![("hp_coefficient", 0.1, 0.2)]);
+
+    assert_eq!(changelog.new_units.len(), 1);
+    assert_eq!(changelog.new_units[0].unit_id, NEW_UNIT_ID);
+    assert_eq!(
+        changelog.new_units[0].power.to_bits(),
+        max_power(&new, NEW_UNIT_ID, 20).await.to_bits()
+    );
+    assert!(changelog.removed_units.is_empty());
+
+    assert_eq!(changelog.units.len(), 1);
+    let unit = &changelog.units[0];
+    assert_eq!((unit.unit_id, unit.unit_name.as_str()), (UNIT_ID, "ユイ"));
+    let changes: Vec<String> = unit.changes.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        changes,
+        vec![
+            format!(
+                "new rank 3, equipments [{}, 999999, 999999, 999999, 999999, 999999]",
+                EQUIPMENT_ID
+            ),
+            "rarity 5 status: hp 500 -> 550".to_string(),
+        ]
+    );
+    assert!(matches!(
+        unit.changes[0],
+        UnitChange::NewRank { rank: 3, .. }
+    ));
+    assert_eq!(
+        unit.power.old.to_bits(),
+        max_power(&old, UNIT_ID, 10).await.to_bits()
+    );
+    assert_eq!(
+        unit.power.new.to_bits(),
+        max_power(&new, UNIT_ID, 20).await.to_bits()
+    );
+
+    assert_eq!(changelog.failed_units.len(), 1);
+    let failure = &changelog.failed_units[0];
+    assert_eq!(
+        (failure.unit_id, failure.database.as_str()),
+        (BROKEN_UNIT_ID, "new")
+    );
+    match &failure.error {
+        Error::Data(DataError::EquipmentNotFound {
+            unit_id: Some(BROKEN_UNIT_ID),
+            equipment_id,
+        }) => assert_eq!(*equipment_id, EQUIPMENT_ID + 1),
+        error => panic!("{}", error),
+    }
+
+    let text = changelog.to_string();
+    assert!(text.contains("Power at level 10 -> 20:\n"), "{}", text);
+    assert!(
+        text.ends_with(&format!("Failed units:\n  {}\n", failure)),
+        "{}",
+        text
+    );
+}
+
+#[tokio::test]
+async fn removed_units_and_failures_in_the_old_version() {
+    let (old, new) = versions();
+    // Swapped, the new unit is removed and the broken one fails in `old`
+    let changelog = diff(&data_manager(&new).await, 20, &data_manager(&old).await, 20)
+        .await
+        .unwrap();
+
+    assert_eq!(changelog.removed_units, vec![NEW_UNIT_ID]);
+    assert!(changelog.new_units.is_empty());
+    let failed: Vec<_> = changelog
+        .failed_units
+        .iter()
+        .map(|failure| (failure.unit_id, failure.database.as_str()))
+        .collect();
+    assert_eq!(failed, vec![(BROKEN_UNIT_ID, "old")]);
+}
+
+#[tokio::test]
+async fn max_unit_level_of_each_source() {
+    let pool = database(&dump()).await;
+    let data_manager = DataManager::with_pool(pool.clone()).await.unwrap();
+    match data_manager.max_unit_level().await {
+        Err(Error::Schema(SchemaError::TableNotFound { table })) => {
+            assert_eq!(table, "experience_unit")
+        }
+        result => panic!("{:?}", result),
+    }
+    let copy = InMemorySource::load(&SqliteSource::new(pool.clone()))
+        .await
+        .unwrap();
+    assert_eq!(copy.max_unit_level, None);
+
+    sqlx::query("CREATE TABLE experience_unit (unit_level INTEGER, total_exp INTEGER)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    match data_manager.max_unit_level().await {
+        Err(Error::Data(DataError::MaxUnitLevelNotFound)) => {}
+        result => panic!("{:?}", result),
+    }
+
+    for level in 1..=175 {
+        sqlx::query("INSERT INTO experience_unit VALUES (?, ?)")
+            .bind(level)
+            .bind(level * 100)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    assert_eq!(data_manager.max_unit_level().await.unwrap(), 175);
+
+    let copy = InMemorySource::load(&SqliteSource::new(pool))
+        .await
+        .unwrap();
+    assert_eq!(copy.max_unit_level().await.unwrap(), 175);
+    let data_manager = DataManager::with_source(Arc::new(copy)).await.unwrap();
+    assert_eq!(data_manager.max_unit_level().await.unwrap(), 175);
+}
@@ -0,0 +1,292 @@
+//! Master database of a single unit written as dump files
+//!
+//! Each test binary uses part of the fixture.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use powermagic::manager::DataManager;
+use powermagic::model::{ColumnType, TableColumns, TABLES};
+use powermagic::source::InMemorySource;
+use serde_json::{json, Map, Value};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+pub const UNIT_ID: i64 = 100101;
+pub const EQUIPMENT_ID: i64 = 101011;
+
+/// Row with every column the loaders read, text columns are empty strings
+pub fn row(table: &str, values: Value) -> Map<String, Value> {
+    let mut row: Map<String, Value> = TableColumns::get(table)
+        .unwrap()
+        .required()
+        .into_iter()
+        .map(|(column, column_type)| {
+            let value = match column_type {
+                ColumnType::Integer => json!(0),
+                ColumnType::Real => json!(0.0),
+                ColumnType::Text => json!(""),
+            };
+            (column, value)
+        })
+        .collect();
+    row.extend(values.as_object().unwrap().clone());
+
+    row
+}
+
+/// Rows of every table, in the order of [`TABLES`]
+pub type Dump = Vec<(&'static str, Vec<Map<String, Value>>)>;
+
+pub fn insert(tables: &mut Dump, table: &str, values: Value) {
+    rows(tables, table).push(row(table, values));
+}
+
+/// Unit with a single rank and rarity, wearing `equipment_id` in its first slot
+pub fn add_unit(tables: &mut Dump, unit_id: i64, equipment_id: i64) {
+    insert(
+        tables,
+        "unit_data",
+        json!({"unit_id": unit_id, "unit_name": format!("unit {}", unit_id), "rarity": 1}),
+    );
+    insert(
+        tables,
+        "unit_promotion",
+        json!({
+            "unit_id": unit_id,
+            "promotion_level": 1,
+            "equip_slot_1": equipment_id,
+            "equip_slot_2": 999999,
+            "equip_slot_3": 999999,
+            "equip_slot_4": 999999,
+            "equip_slot_5": 999999,
+            "equip_slot_6": 999999,
+        }),
+    );
+    insert(
+        tables,
+        "unit_promotion_status",
+        json!({"unit_id": unit_id, "promotion_level": 1, "hp": 20}),
+    );
+    insert(
+        tables,
+        "unit_rarity",
+        json!({"unit_id": unit_id, "rarity": 1, "hp": 80, "hp_growth": 8.5}),
+    );
+    insert(
+        tables,
+        "unit_skill_data",
+        json!({"unit_id": unit_id, "union_burst": unit_id * 10 + 1}),
+    );
+}
+
+/// Rows of a table, to edit them
+pub fn rows<'a>(tables: &'a mut Dump, table: &str) -> &'a mut Vec<Map<String, Value>> {
+    &mut tables
+        .iter_mut()
+        .find(|(name, _)| *name == table)
+        .unwrap()
+        .1
+}
+
+/// Tables of a single unit with one equipment and a story, the tables of
+/// unique equipments and rarity 6 are empty
+pub fn dump() -> Dump {
+    let mut tables: Dump = TABLES.iter().map(|table| (table.name, vec![])).collect();
+    let mut insert = |table: &str, values: Value| insert(&mut tables, table, values);
+
+    insert(
+        "unit_status_coefficient",
+        json!({
+            "coefficient_id": 1,
+            "hp_coefficient": 0.1,
+            "atk_coefficient": 4.5,
+            "def_coefficient": 4.5,
+            "skill_lv_coefficient": 10,
+            "overall_coefficient": 1,
+        }),
+    );
+    for level in 1..=5 {
+        insert(
+            "equipment_enhance_data",
+            json!({"promotion_level": 2, "equipment_enhance_level": level}),
+        );
+    }
+    insert(
+        "unit_data",
+        json!({"unit_id": UNIT_ID, "unit_name": "ユイ", "rarity": 1}),
+    );
+    for rank in 1..=2 {
+        insert(
+            "unit_promotion",
+            json!({
+                "unit_id": UNIT_ID,
+                "promotion_level": rank,
+                "equip_slot_1": EQUIPMENT_ID,
+                "equip_slot_2": 999999,
+                "equip_slot_3": 999999,
+                "equip_slot_4": 999999,
+                "equip_slot_5": 999999,
+                "equip_slot_6": 999999,
+            }),
+        );
+    }
+    insert(
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 2, "hp": 30, "atk": 4.5}),
+    );
+    for rarity in 1..=5 {
+        insert(
+            "unit_rarity",
+            json!({"unit_id": UNIT_ID, "rarity": rarity, "hp": 100 * rarity, "hp_growth": 12.5}),
+        );
+    }
+    insert(
+        "unit_skill_data",
+        json!({
+            "unit_id": UNIT_ID,
+            "union_burst": 1001001,
+            "main_skill_1": 1001002,
+            "ex_skill_1": 1001003,
+        }),
+    );
+    insert(
+        "chara_story_status",
+        json!({
+            "story_id": 1001001,
+            "unlock_story_name": "1",
+            "status_type_1": 1,
+            "status_rate_1": 100,
+            "chara_id_1": UNIT_ID / 100,
+        }),
+    );
+    insert(
+        "equipment_data",
+        json!({
+            "equipment_id": EQUIPMENT_ID,
+            "equipment_name": "123",
+            "promotion_level": 2,
+            "hp": 12.5,
+        }),
+    );
+    insert(
+        "equipment_enhance_rate",
+        json!({"equipment_id": EQUIPMENT_ID, "promotion_level": 2, "hp": 3}),
+    );
+
+    tables
+}
+
+pub fn write_json(dir: &Path, name: &str, rows: &[Map<String, Value>]) {
+    let path = dir.join(format!("{}.json", name));
+    std::fs::write(path, serde_json::to_string(rows).unwrap()).unwrap();
+}
+
+/// CSV with the columns of the first row, an empty file without any row
+pub fn write_csv(dir: &Path, name: &str, rows: &[Map<String, Value>]) {
+    let path = dir.join(format!("{}.csv", name));
+    let mut writer = csv::Writer::from_path(path).unwrap();
+    if let Some(first) = rows.first() {
+        writer.write_record(first.keys()).unwrap();
+    }
+    for row in rows.iter() {
+        let record: Vec<String> = row
+            .values()
+            .map(|value| match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            })
+            .collect();
+        writer.write_record(record).unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+/// Source over the tables written as a JSON dump
+pub async fn source(tables: &Dump) -> InMemorySource {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, rows) in tables.iter() {
+        write_json(dir.path(), name, rows);
+    }
+
+    InMemorySource::from_dump_dir(dir.path()).await.unwrap()
+}
+
+pub async fn data_manager(tables: &Dump) -> DataManager {
+    DataManager::with_source(Arc::new(source(tables).await))
+        .await
+        .unwrap()
+}
+
+/// In-memory master database with the tables of the dump, tables missing
+/// from the dump aren't created
+pub async fn database(tables: &Dump) -> sqlx::Pool<sqlx::Sqlite> {
+    // Each connection has its own in-memory database, keep the only one open
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    create_tables(&pool, tables).await;
+
+    pool
+}
+
+/// Same as [`database`], written to a new file at `path`
+pub async fn database_file(path: &Path, tables: &Dump) {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .unwrap();
+    create_tables(&pool, tables).await;
+    pool.close().await;
+}
+
+async fn create_tables(pool: &sqlx::Pool<sqlx::Sqlite>, tables: &Dump) {
+    for (name, rows) in tables.iter() {
+        let columns = TableColumns::get(name).unwrap().required();
+        let definition: Vec<String> = columns
+            .iter()
+            .map(|(column, column_type)| {
+                let column_type = match column_type {
+                    ColumnType::Integer => "INTEGER",
+                    ColumnType::Real => "REAL",
+                    ColumnType::Text => "TEXT",
+                };
+                format!("{} {}", column, column_type)
+            })
+            .collect();
+        sqlx::query(&format!(
+            "CREATE TABLE {} ({})",
+            name,
+            definition.join(", ")
+        ))
+        .execute(pool)
+        .await
+        .unwrap();
+
+        let names: Vec<&str> = columns.iter().map(|(column, _)| column.as_str()).collect();
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            name,
+            names.join(", "),
+            vec!["?"; names.len()].join(", ")
+        );
+        for row in rows.iter() {
+            let mut query = sqlx::query(&insert);
+            for column in names.iter() {
+                query = match &row[*column] {
+                    Value::String(value) => query.bind(value.clone()),
+                    Value::Number(value) if value.is_f64() => query.bind(value.as_f64()),
+                    value => query.bind(value.as_i64()),
+                };
+            }
+            query.execute(pool).await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use std::sync::Arc;
+
+use powermagic::compare::{compare_servers, UnitCaps};
+use powermagic::error::{DataError, Error};
+use powermagic::manager::DataManager;
+use powermagic::region::Region;
+use serde_json::json;
+
+use common::{add_unit, dump, insert, source, Dump, EQUIPMENT_ID, UNIT_ID};
+
+const LEFT_ONLY_ID: i64 = 100201;
+const RIGHT_ONLY_ID: i64 = 100301;
+const BROKEN_UNIT_ID: i64 = 100401;
+const UNIQUE_EQUIPMENT_ID: i64 = 130011;
+
+async fn server(tables: &Dump, region: Region) -> DataManager {
+    DataManager::with_profile(Arc::new(source(tables).await), region.profile())
+        .await
+        .unwrap()
+}
+
+/// Left server, and a right one further ahead: the shared unit has a third
+/// rank and a unique equipment there, and another unit misses an equipment
+fn servers() -> (Dump, Dump) {
+    let mut left = dump();
+    add_unit(&mut left, LEFT_ONLY_ID, 999999);
+    add_unit(&mut left, BROKEN_UNIT_ID, 999999);
+
+    let mut right = dump();
+    add_unit(&mut right, RIGHT_ONLY_ID, 999999);
+    add_unit(&mut right, BROKEN_UNIT_ID, EQUIPMENT_ID + 1);
+    insert(
+        &mut right,
+        "unit_promotion",
+        json!({
+            "unit_id": UNIT_ID,
+            "promotion_level": 3,
+            "equip_slot_1": EQUIPMENT_ID,
+            "equip_slot_2": EQUIPMENT_ID,
+            "equip_slot_3": 999999,
+            "equip_slot_4": 999999,
+            "equip_slot_5": 999999,
+            "equip_slot_6": 999999,
+        }),
+    );
+    insert(
+        &mut right,
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 3, "hp": 60}),
+    );
+    insert(
+        &mut right,
+        "unit_unique_equip",
+        json!({"unit_id": UNIT_ID, "equip_slot": 1, "equip_id": UNIQUE_EQUIPMENT_ID}),
+    );
+    insert(
+        &mut right,
+        "unique_equipment_data",
+        json!({"equipment_id": UNIQUE_EQUIPMENT_ID, "hp": 100}),
+    );
+    insert(
+        &mut right,
+        "unique_equipment_enhance_rate",
+        json!({"equipment_id": UNIQUE_EQUIPMENT_ID, "hp": 10}),
+    );
+    for level in 1..=5 {
+        insert(
+            &mut right,
+            "unique_equipment_enhance_data",
+            json!({"equip_slot": 1, "enhance_level": level}),
+        );
+    }
+
+    (left, right)
+}
+
+#[tokio::test]
+async fn compare_units_of_two_servers() {
+    let (left, right) = servers();
+    let left = server(&left, Region::Jp).await;
+    let right = server(&right, Region::Global).await;
+
+    let comparison = compare_servers(&left, 10, &right, 20).await.unwrap();
+    assert_eq!(comparison.left_region, Some(Region::Jp));
+    assert_eq!(comparison.right_region, Some(Region::Global));
+    assert_eq!(comparison.left_only, vec![LEFT_ONLY_ID]);
+    assert_eq!(comparison.right_only, vec![RIGHT_ONLY_ID]);
+
+    assert_eq!(comparison.units.len(), 1);
+    let unit = &comparison.units[0];
+    assert_eq!(unit.unit_id, UNIT_ID);
+    assert_eq!(
+        unit.left,
+        UnitCaps {
+            rarity: 5,
+            rank: 2,
+            rarity_6: false,
+            unique_equipment: None,
+        }
+    );
+    assert_eq!(
+        unit.right,
+        UnitCaps {
+            rarity: 5,
+            rank: 3,
+            rarity_6: false,
+            unique_equipment: Some((UNIQUE_EQUIPMENT_ID, 5)),
+        }
+    );
+    assert!(!unit.is_same());
+    assert_eq!(unit.equipments.len(), 1);
+    assert_eq!(unit.equipments[0].rank, 3);
+    assert_eq!(unit.equipments[0].left, None);
+    assert_eq!(
+        unit.equipments[0].right,
+        Some(vec![
+            EQUIPMENT_ID,
+            EQUIPMENT_ID,
+            999999,
+            999999,
+            999999,
+            999999
+        ])
+    );
+
+    let (_, left_power) = left.max_power(UNIT_ID, 10).await.unwrap();
+    let (_, right_power) = right.max_power(UNIT_ID, 20).await.unwrap();
+    assert_eq!(unit.left_power.to_bits(), left_power.to_bits());
+    assert_eq!(unit.right_power.to_bits(), right_power.to_bits());
+    assert!(right_power > left_power);
+
+    assert_eq!(comparison.failed_units.len(), 1);
+    let failure = &comparison.failed_units[0];
+    assert_eq!(
+        (failure.unit_id, failure.database.as_str()),
+        (BROKEN_UNIT_ID, "global")
+    );
+    assert!(matches!(
+        failure.error,
+        Error::Data(DataError::EquipmentNotFound {
+            unit_id: Some(BROKEN_UNIT_ID),
+            ..
+        })
+    ));
+
+    let text = comparison.to_string();
+    assert!(
+        text.starts_with("jp (lv 10) / global (lv 20): 2 units on both\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("  rank 3 equipments - / [101011, 101011, 999999, 999999, 999999, 999999]\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(&format!("Only on jp: [{}]\n", LEFT_ONLY_ID)),
+        "{}",
+        text
+    );
+    assert!(
+        text.ends_with(&format!("Failed units:\n  {}\n", failure)),
+        "{}",
+        text
+    );
+}
+
+#[tokio::test]
+async fn same_server_has_no_difference() {
+    let (left, _) = servers();
+    let left = server(&left, Region::Tw).await;
+
+    let comparison = compare_servers(&left, 10, &left, 10).await.unwrap();
+    assert!(comparison.left_only.is_empty());
+    assert!(comparison.right_only.is_empty());
+    assert!(comparison.failed_units.is_empty());
+    assert_eq!(comparison.units.len(), 3);
+    for unit in comparison.units.iter() {
+        assert!(unit.is_same(), "{:?}", unit);
+        assert_eq!(unit.left_power.to_bits(), unit.right_power.to_bits());
+    }
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use powermagic::doctor::{diagnose, diagnose_with_schema, DoctorReport};
+use powermagic::region::Schema;
+use serde_json::json;
+
+use common::{database, dump, insert, Dump, EQUIPMENT_ID, UNIT_ID};
+
+/// Problems as `(table, unit id, message)`
+fn problems(report: &DoctorReport) -> Vec<(&str, Option<i64>, &str)> {
+    report
+        .problems
+        .iter()
+        .map(|problem| (problem.table, problem.unit_id, problem.message.as_str()))
+        .collect()
+}
+
+fn without(mut tables: Dump, table: &str) -> Dump {
+    tables.retain(|(name, _)| *name != table);
+    tables
+}
+
+#[tokio::test]
+async fn fixture_has_no_problem() {
+    let report = diagnose(&database(&dump()).await).await;
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.units, 1);
+    assert_eq!(report.to_string(), "No problem found in 1 units\n");
+}
+
+#[tokio::test]
+async fn missing_table() {
+    let pool = database(&without(dump(), "unit_skill_data")).await;
+    let report = diagnose(&pool).await;
+    assert_eq!(
+        problems(&report),
+        vec![("unit_skill_data", None, "table does not exist")]
+    );
+
+    // Only tables of features a lagging server lacks may be missing
+    let pool = database(&without(dump(), "unlock_rarity_6")).await;
+    assert_eq!(
+        problems(&diagnose(&pool).await),
+        vec![("unlock_rarity_6", None, "table does not exist")]
+    );
+    let report = diagnose_with_schema(&pool, Schema::Lagging).await;
+    assert!(report.is_ok(), "{}", report);
+}
+
+#[tokio::test]
+async fn missing_column_skips_the_checks_of_its_table() {
+    let mut tables = dump();
+    // Equipment that would be reported if `equipment_data` were read
+    insert(
+        &mut tables,
+        "unit_promotion",
+        json!({
+            "unit_id": UNIT_ID,
+            "promotion_level": 3,
+            "equip_slot_1": EQUIPMENT_ID + 1,
+            "equip_slot_2": 999999,
+            "equip_slot_3": 999999,
+            "equip_slot_4": 999999,
+            "equip_slot_5": 999999,
+            "equip_slot_6": 999999,
+        }),
+    );
+    insert(
+        &mut tables,
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 3}),
+    );
+    let pool = database(&tables).await;
+    sqlx::query("ALTER TABLE equipment_data DROP COLUMN hp")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let report = diagnose(&pool).await;
+    assert_eq!(
+        problems(&report),
+        vec![
+            ("equipment_data", None, "column hp is missing"),
+            (
+                "equipment_enhance_rate",
+                Some(UNIT_ID),
+                &*format!("equipment {} does not exist", EQUIPMENT_ID + 1)
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn contradicting_rows() {
+    let mut tables = dump();
+    insert(
+        &mut tables,
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 2}),
+    );
+    insert(
+        &mut tables,
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 4}),
+    );
+    insert(
+        &mut tables,
+        "chara_story_status",
+        json!({
+            "story_id": 1001002,
+            "unlock_story_name": "2",
+            "chara_id_1": UNIT_ID / 100 + 1,
+        }),
+    );
+    let report = diagnose(&database(&tables).await).await;
+
+    assert_eq!(
+        problems(&report),
+        vec![
+            (
+                "unit_promotion_status",
+                Some(UNIT_ID),
+                "rank 2 has more than one row"
+            ),
+            (
+                "unit_promotion_status",
+                Some(UNIT_ID),
+                "rank 4 is not in unit_promotion"
+            ),
+            (
+                "chara_story_status",
+                None,
+                &*format!(
+                    "story 1001002 has charas [{}], but story 1001001 of the same group has [{}]",
+                    UNIT_ID / 100 + 1,
+                    UNIT_ID / 100
+                )
+            ),
+        ]
+    );
+    assert!(report
+        .to_string()
+        .starts_with("3 problems found in 1 units\n[unit_promotion_status] unit 100101: rank 2"));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use powermagic::error::{DataError, DumpError, Error};
+use powermagic::manager::DataManager;
+use powermagic::region::Schema;
+use powermagic::source::{DataSource, InMemorySource};
+use powermagic::unit::*;
+use serde_json::json;
+
+use common::{dump, row, write_csv, write_json, EQUIPMENT_ID, UNIT_ID};
+
+/// Load the dump and check it reads back like the master database
+async fn check_round_trip(dir: &Path, schema: Schema) -> f64 {
+    let source = InMemorySource::from_dump_dir_with_schema(dir, schema)
+        .await
+        .unwrap();
+
+    let unit = &source.unit_data(Some(UNIT_ID)).await.unwrap()[0];
+    assert_eq!(unit.unit_name, "ユイ");
+    assert_eq!(unit.kana, "");
+    assert_eq!(unit.comment, "");
+    assert_eq!(unit.normal_atk_cast_time, 0.0);
+
+    let data_manager = DataManager::with_source(Arc::new(source)).await.unwrap();
+    let equipment = data_manager.equip_data(EQUIPMENT_ID).await.unwrap();
+    assert_eq!(equipment.data.equipment_name, "123");
+    assert_eq!(equipment.data.description, "");
+    assert_eq!(equipment.data.hp, 12.5);
+    assert_eq!(equipment.enhance_rate.hp, 3.0);
+    assert_eq!(equipment.max_enhance_level, 5);
+
+    let unit_data = data_manager.unit_data(UNIT_ID).await.unwrap();
+    assert_eq!(unit_data.rarity.len(), 5);
+    assert_eq!(unit_data.rarity[4].hp, 500.0);
+    assert!(unit_data.unique_equip.is_empty());
+    assert!(unit_data.unlock_rarity_6.is_none());
+
+    let cache = data_manager.unit_cache(UNIT_ID).await.unwrap();
+    let state = cache.max_state(10);
+    BorrowedUnitCalculator::new(&cache, &state)
+        .try_power()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn json_dump_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, rows) in dump().iter() {
+        write_json(dir.path(), name, rows);
+    }
+
+    check_round_trip(dir.path(), Schema::Current).await;
+}
+
+#[tokio::test]
+async fn csv_dump_round_trip() {
+    let json = tempfile::tempdir().unwrap();
+    let csv = tempfile::tempdir().unwrap();
+    for (name, rows) in dump().iter() {
+        write_json(json.path(), name, rows);
+        write_csv(csv.path(), name, rows);
+    }
+
+    let power = check_round_trip(csv.path(), Schema::Current).await;
+    assert_eq!(
+        power.to_bits(),
+        check_round_trip(json.path(), Schema::Current)
+            .await
+            .to_bits()
+    );
+}
+
+#[tokio::test]
+async fn lagging_dump_without_optional_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, rows) in dump().iter() {
+        if !Schema::Lagging.is_optional(name) {
+            write_csv(dir.path(), name, rows);
+        }
+    }
+
+    check_round_trip(dir.path(), Schema::Lagging).await;
+    match InMemorySource::from_dump_dir(dir.path()).await {
+        Err(Error::Dump(DumpError::TableNotFound(table, _))) => {
+            assert!(Schema::Lagging.is_optional(&table))
+        }
+        result => panic!("{:?}", result.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn unique_equipment_without_enhance_data() {
+    const UNIQUE_EQUIPMENT_ID: i64 = 130011;
+
+    let dir = tempfile::tempdir().unwrap();
+    for (name, mut rows) in dump().into_iter() {
+        match name {
+            "unit_unique_equip" => rows.push(row(
+                name,
+                json!({"unit_id": UNIT_ID, "equip_slot": 1, "equip_id": UNIQUE_EQUIPMENT_ID}),
+            )),
+            "unique_equipment_data" | "unique_equipment_enhance_rate" => {
+                rows.push(row(name, json!({"equipment_id": UNIQUE_EQUIPMENT_ID})))
+            }
+            "unique_equipment_enhance_data" => continue,
+            _ => {}
+        }
+        write_json(dir.path(), name, &rows);
+    }
+
+    let source = InMemorySource::from_dump_dir_with_schema(dir.path(), Schema::Lagging)
+        .await
+        .unwrap();
+    let mut data_manager = DataManager::with_source(Arc::new(source)).await.unwrap();
+    for preload in [false, true] {
+        if preload {
+            data_manager.preload().await.unwrap();
+        }
+        match data_manager.unit_cache(UNIT_ID).await {
+            Err(Error::Data(DataError::UniqueEquipmentEnhanceDataNotFound {
+                unit_id: Some(UNIT_ID),
+                equipment_id: UNIQUE_EQUIPMENT_ID,
+            })) => {}
+            result => panic!("{:?}", result.map(|_| ())),
+        }
+    }
+}
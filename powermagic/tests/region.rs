@@ -0,0 +1,123 @@
+mod common;
+
+use std::sync::Arc;
+
+use powermagic::manager::DataManager;
+use powermagic::region::*;
+use serde_json::json;
+
+use common::{data_manager, dump, insert, source};
+
+#[test]
+fn profile_of_each_region() {
+    let profiles: Vec<_> = Region::ALL
+        .iter()
+        .map(|region| {
+            let profile = region.profile();
+            assert_eq!(profile.region, Some(*region));
+            (*region, profile.schema, profile.coefficient, profile.names)
+        })
+        .collect();
+
+    assert_eq!(
+        profiles,
+        vec![
+            (
+                Region::Jp,
+                Schema::Current,
+                CoefficientRow::Latest,
+                NameStyle::Kana
+            ),
+            (
+                Region::Tw,
+                Schema::Lagging,
+                CoefficientRow::First,
+                NameStyle::FullWidth
+            ),
+            (
+                Region::Cn,
+                Schema::Lagging,
+                CoefficientRow::First,
+                NameStyle::FullWidth
+            ),
+            (
+                Region::Global,
+                Schema::Lagging,
+                CoefficientRow::First,
+                NameStyle::Latin
+            ),
+        ]
+    );
+
+    for region in Region::ALL {
+        assert_eq!(region.to_string().parse::<Region>().unwrap(), region);
+    }
+}
+
+#[test]
+fn names_of_each_region() {
+    let normalize = |region: Region, name: &str| region.profile().names.normalize(name);
+
+    assert_eq!(normalize(Region::Jp, "ﾕｲ(ﾌﾟﾘﾝｾｽ)"), "ユイ（プリンセス）");
+    assert_eq!(
+        normalize(Region::Jp, " ﾍﾟｺﾘｰﾇ（ﾆｭｰｲﾔｰ） "),
+        "ペコリーヌ（ニューイヤー）"
+    );
+    assert_eq!(normalize(Region::Jp, "ｳﾞｨｵﾗ"), "ヴィオラ");
+    // Marks with nothing to voice stay apart
+    assert_eq!(normalize(Region::Jp, "ﾏﾞ"), "マ゛");
+    assert_eq!(normalize(Region::Tw, "優衣 (公主)"), "優衣（公主）");
+    assert_eq!(normalize(Region::Cn, "优衣(公主)"), "优衣（公主）");
+    assert_eq!(normalize(Region::Tw, "ﾕｲ"), "ﾕｲ");
+    assert_eq!(
+        normalize(Region::Global, "Yui　（Princess）"),
+        "yui (princess)"
+    );
+}
+
+#[tokio::test]
+async fn coefficient_row_of_each_region() {
+    let mut tables = dump();
+    insert(
+        &mut tables,
+        "unit_status_coefficient",
+        json!({"coefficient_id": 2, "hp_coefficient": 0.2, "skill_lv_coefficient": 10}),
+    );
+    let source = Arc::new(source(&tables).await);
+
+    for region in Region::ALL {
+        let data_manager = DataManager::with_profile(source.clone(), region.profile())
+            .await
+            .unwrap();
+        let coefficient_id = match region {
+            Region::Jp => 2,
+            Region::Tw | Region::Cn | Region::Global => 1,
+        };
+        assert_eq!(
+            data_manager.status_coefficient.coefficient_id, coefficient_id,
+            "{}",
+            region
+        );
+    }
+}
+
+#[tokio::test]
+async fn latest_coefficient_row_is_opt_in() {
+    let mut tables = dump();
+    // Sources sort the rows, the first one isn't the first written
+    let rows = &mut tables
+        .iter_mut()
+        .find(|(name, _)| *name == "unit_status_coefficient")
+        .unwrap()
+        .1;
+    rows.insert(0, rows[0].clone());
+    rows[0]["coefficient_id"] = json!(2);
+    let mut data_manager = data_manager(&tables).await;
+    assert_eq!(data_manager.profile().coefficient, CoefficientRow::First);
+    assert_eq!(data_manager.status_coefficient.coefficient_id, 1);
+
+    data_manager
+        .select_status_coefficient(CoefficientRow::Latest)
+        .unwrap();
+    assert_eq!(data_manager.status_coefficient.coefficient_id, 2);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use powermagic::error::Error;
+use powermagic::registry::UnitCacheRegistry;
+use powermagic::unit::UnitCache;
+use tokio::sync::oneshot;
+
+use common::{data_manager, dump, UNIT_ID};
+
+async fn base_cache() -> UnitCache {
+    data_manager(&dump())
+        .await
+        .unit_cache(UNIT_ID)
+        .await
+        .unwrap()
+}
+
+/// Load of `base` under another id, counted in `loads`
+async fn load(base: &UnitCache, unit_id: i64, loads: &AtomicUsize) -> Result<UnitCache, Error> {
+    loads.fetch_add(1, Ordering::SeqCst);
+    tokio::task::yield_now().await;
+    Ok(UnitCache {
+        unit_id,
+        ..base.clone()
+    })
+}
+
+#[tokio::test]
+async fn concurrent_loads_of_a_unit_run_once() {
+    let base = base_cache().await;
+    let registry = UnitCacheRegistry::default();
+    let loads = AtomicUsize::new(0);
+
+    let (first, second) = tokio::join!(
+        registry.get_or_load(UNIT_ID, load(&base, UNIT_ID, &loads)),
+        registry.get_or_load(UNIT_ID, load(&base, UNIT_ID, &loads)),
+    );
+    assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    registry
+        .get_or_load(UNIT_ID, load(&base, UNIT_ID, &loads))
+        .await
+        .unwrap();
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    assert_eq!(registry.len(), 1);
+}
+
+#[tokio::test]
+async fn least_recently_used_unit_is_evicted() {
+    let base = base_cache().await;
+    let registry = UnitCacheRegistry::new(Some(2));
+    let loads = AtomicUsize::new(0);
+
+    for unit_id in [1, 2, 1, 3] {
+        registry
+            .get_or_load(unit_id, load(&base, unit_id, &loads))
+            .await
+            .unwrap();
+    }
+    assert_eq!(registry.unit_ids(), vec![1, 3]);
+    assert!(registry.get(2).is_none());
+    assert_eq!(loads.load(Ordering::SeqCst), 3);
+
+    let held = registry
+        .get_or_load(4, load(&base, 4, &loads))
+        .await
+        .unwrap();
+    registry
+        .get_or_load(2, load(&base, 2, &loads))
+        .await
+        .unwrap();
+    assert_eq!(registry.unit_ids(), vec![4, 2]);
+    // Evicted caches live on in their callers
+    assert_eq!(held.unit_id, 4);
+    assert_eq!(loads.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn shrinking_capacity_evicts_right_away() {
+    let base = base_cache().await;
+    let mut registry = UnitCacheRegistry::default();
+    let loads = AtomicUsize::new(0);
+
+    for unit_id in [1, 2, 3, 4, 1] {
+        registry
+            .get_or_load(unit_id, load(&base, unit_id, &loads))
+            .await
+            .unwrap();
+    }
+    assert_eq!(registry.len(), 4);
+
+    registry.set_capacity(Some(2));
+    assert_eq!(registry.unit_ids(), vec![4, 1]);
+    assert_eq!(registry.len(), 2);
+
+    registry.set_capacity(None);
+    registry
+        .get_or_load(5, load(&base, 5, &loads))
+        .await
+        .unwrap();
+    assert_eq!(registry.unit_ids(), vec![4, 1, 5]);
+}
+
+#[tokio::test]
+async fn units_still_loading_are_not_counted() {
+    let base = base_cache().await;
+    let registry = UnitCacheRegistry::new(Some(1));
+    let loads = AtomicUsize::new(0);
+    registry
+        .get_or_load(1, load(&base, 1, &loads))
+        .await
+        .unwrap();
+
+    let (sender, receiver) = oneshot::channel::<()>();
+    let pending = async {
+        receiver.await.unwrap();
+        load(&base, 2, &loads).await
+    };
+    let check = async {
+        tokio::task::yield_now().await;
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.unit_ids(), vec![1]);
+        sender.send(()).unwrap();
+    };
+    let (cache, _) = tokio::join!(registry.get_or_load(2, pending), check);
+
+    assert_eq!(cache.unwrap().unit_id, 2);
+    assert_eq!(registry.unit_ids(), vec![2]);
+    assert_eq!(registry.len(), 1);
+}
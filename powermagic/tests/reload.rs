@@ -0,0 +1,101 @@
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use powermagic::reload::ReloadableDataManager;
+use powermagic::unit::*;
+use serde_json::json;
+
+use common::{database_file, dump, rows, Dump, UNIT_ID};
+
+const LEVEL: i32 = 10;
+
+/// Tables whose rarity 5 has `hp` health
+fn version(hp: i64) -> Dump {
+    let mut tables = dump();
+    for row in rows(&mut tables, "unit_rarity").iter_mut() {
+        if row["rarity"] == 5 {
+            row["hp"] = json!(hp);
+        }
+    }
+
+    tables
+}
+
+/// Replace the database at `path` the way a patch does, with a new file
+async fn replace(path: &Path, tables: &Dump) {
+    let next = path.with_extension("next");
+    database_file(&next, tables).await;
+    std::fs::rename(next, path).unwrap();
+}
+
+fn max_power(cache: &UnitCache) -> f64 {
+    let state = cache.max_state(LEVEL);
+    BorrowedUnitCalculator::new(cache, &state).power()
+}
+
+#[tokio::test]
+async fn calculation_keeps_the_old_manager() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("master.db");
+    database_file(&path, &version(500)).await;
+    let manager = ReloadableDataManager::open(&path).await.unwrap();
+
+    let old = manager.current();
+    let old_cache = old.shared_unit_cache(UNIT_ID).await.unwrap();
+    let state = old_cache.max_state(LEVEL);
+    let calculator = BorrowedUnitCalculator::new(&old_cache, &state);
+    let old_power = calculator.power();
+
+    replace(&path, &version(900)).await;
+    assert!(manager.is_changed());
+    assert!(manager.reload_if_changed().await.unwrap());
+    assert!(!manager.is_changed());
+    assert_eq!(manager.generation(), 1);
+
+    // The held manager and calculator still see the old data
+    let new = manager.current();
+    assert!(!Arc::ptr_eq(&old, &new));
+    assert_eq!(calculator.power().to_bits(), old_power.to_bits());
+    let cache = old.unit_cache(UNIT_ID).await.unwrap();
+    assert_eq!(max_power(&cache).to_bits(), old_power.to_bits());
+
+    // Shared units of the old manager are loaded again before the swap
+    let new_cache = new.registry().get(UNIT_ID).unwrap();
+    assert!(!Arc::ptr_eq(&old_cache, &new_cache));
+    assert!(max_power(&new_cache) > old_power);
+    drop(old);
+    assert_eq!(calculator.power().to_bits(), old_power.to_bits());
+}
+
+#[tokio::test]
+async fn concurrent_reloads_run_one_at_a_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("master.db");
+    database_file(&path, &version(500)).await;
+    let manager = ReloadableDataManager::open(&path).await.unwrap();
+
+    // Both see the change, the second one waits and finds it already loaded
+    replace(&path, &version(700)).await;
+    let (first, second) = tokio::join!(manager.reload_if_changed(), manager.reload_if_changed());
+    assert_eq!((first.unwrap(), second.unwrap()), (true, false));
+    assert_eq!(manager.generation(), 1);
+
+    // Replaced while a reload runs, the next one reads the newest file
+    replace(&path, &version(800)).await;
+    let first = manager.reload();
+    let second = async {
+        tokio::task::yield_now().await;
+        replace(&path, &version(900)).await;
+        manager.reload().await
+    };
+    let (first, second) = tokio::join!(first, second);
+    first.unwrap();
+    second.unwrap();
+
+    assert_eq!(manager.generation(), 3);
+    assert!(!manager.is_changed());
+    let cache = manager.current().unit_cache(UNIT_ID).await.unwrap();
+    assert_eq!(cache.rarity[4].status[0], 900.0);
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use powermagic::error::{DataError, Error};
+use powermagic::timeline::GameTime;
+use serde_json::json;
+
+use common::{add_unit, data_manager, dump, rows, Dump, UNIT_ID};
+
+const LATER_UNIT_ID: i64 = 100201;
+const EXPIRED_UNIT_ID: i64 = 100301;
+
+#[test]
+fn parse_game_time() {
+    let time = GameTime::new(2018, 2, 15).and_hms(15, 0, 0);
+    for text in [
+        "2018/02/15 15:00:00",
+        "2018-02-15 15:00:00",
+        "2018-02-15T15:00:00",
+        "2018/2/15 15:00",
+        " 2018/02/15  15:00:00 ",
+    ] {
+        assert_eq!(text.parse::<GameTime>().unwrap(), time, "{}", text);
+    }
+    assert_eq!(
+        "2018/02/15".parse::<GameTime>().unwrap(),
+        GameTime::new(2018, 2, 15)
+    );
+    assert_eq!(time.to_string(), "2018/02/15 15:00:00");
+    assert_eq!(time.to_string().parse::<GameTime>().unwrap(), time);
+
+    // Edges of each part
+    assert_eq!(
+        "2030/12/31 23:59:59".parse::<GameTime>().unwrap(),
+        GameTime::new(2030, 12, 31).and_hms(23, 59, 59)
+    );
+    for text in [
+        "",
+        "2018/02",
+        "2018/02/15/01",
+        "2018/02/15 15",
+        "2018/02/15 15:00:00:00",
+        "2018/00/15",
+        "2018/13/15",
+        "2018/02/00",
+        "2018/02/32",
+        "2018/02/15 24:00:00",
+        "2018/02/15 15:60:00",
+        "2018/02/15 15:00:60",
+        "2018/02/15 15:00:-1",
+        "2018/02/15 300:00:00",
+        "2018/Feb/15",
+    ] {
+        assert!(text.parse::<GameTime>().is_err(), "{}", text);
+    }
+}
+
+const RELEASE: &str = "2020/01/01 12:00:00";
+const LATER: &str = "2020/01/01 12:00:01";
+
+/// The fixture unit released at [`RELEASE`], a unit released one second
+/// later and one expired at [`LATER`]
+fn timeline() -> Dump {
+    let mut tables = dump();
+    add_unit(&mut tables, LATER_UNIT_ID, 999999);
+    add_unit(&mut tables, EXPIRED_UNIT_ID, 999999);
+    for row in rows(&mut tables, "unit_data").iter_mut() {
+        let (start, end, is_limited) = match row["unit_id"].as_i64().unwrap() {
+            UNIT_ID => (RELEASE, "2030/12/31 23:59:59", 0),
+            LATER_UNIT_ID => (LATER, "", 1),
+            _ => ("2019/06/01 00:00:00", LATER, 1),
+        };
+        row["start_time"] = json!(start);
+        row["end_time"] = json!(end);
+        row["is_limited"] = json!(is_limited);
+    }
+
+    tables
+}
+
+#[tokio::test]
+async fn availability_at_the_edges() {
+    let data_manager = data_manager(&timeline()).await;
+    let release: GameTime = RELEASE.parse().unwrap();
+    let later: GameTime = LATER.parse().unwrap();
+
+    let available = |time: GameTime| {
+        let data_manager = &data_manager;
+        async move {
+            let mut unit_ids: Vec<i64> = data_manager
+                .units_at(time)
+                .await
+                .unwrap()
+                .iter()
+                .map(|unit| unit.unit_id)
+                .collect();
+            unit_ids.sort_unstable();
+            unit_ids
+        }
+    };
+    // Released at the second, expired at the second
+    assert_eq!(available(release).await, vec![UNIT_ID, EXPIRED_UNIT_ID]);
+    assert_eq!(available(later).await, vec![UNIT_ID, LATER_UNIT_ID]);
+    assert_eq!(
+        available(GameTime::new(2019, 6, 1)).await,
+        vec![EXPIRED_UNIT_ID]
+    );
+
+    let unit = data_manager
+        .source()
+        .unit_data(Some(LATER_UNIT_ID))
+        .await
+        .unwrap();
+    // An end time that can't be read never expires
+    assert!(unit[0].is_available_at(GameTime::new(9999, 1, 1)));
+    assert!(unit[0].limited());
+}
+
+#[tokio::test]
+async fn as_of_hides_future_units() {
+    for preload in [false, true] {
+        let mut data_manager = data_manager(&timeline()).await;
+        if preload {
+            data_manager.preload().await.unwrap();
+        }
+        data_manager.set_as_of(Some(RELEASE.parse().unwrap()));
+
+        assert_eq!(
+            data_manager.unit_ids().await.unwrap(),
+            vec![UNIT_ID, EXPIRED_UNIT_ID]
+        );
+        data_manager.unit_cache(UNIT_ID).await.unwrap();
+        match data_manager.unit_cache(LATER_UNIT_ID).await {
+            Err(Error::Data(DataError::UnitNotFound {
+                unit_id: LATER_UNIT_ID,
+            })) => {}
+            result => panic!("{:?}", result.map(|_| ())),
+        }
+        assert!(data_manager
+            .search_units("unit")
+            .await
+            .unwrap()
+            .iter()
+            .all(|unit| unit.unit_id != LATER_UNIT_ID));
+
+        let releases: Vec<String> = data_manager
+            .release_timeline()
+            .await
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            releases,
+            vec![
+                format!(
+                    "2019/06/01 00:00:00 {} unit {} (limited)",
+                    EXPIRED_UNIT_ID, EXPIRED_UNIT_ID
+                ),
+                format!("{} {} ユイ", RELEASE, UNIT_ID),
+            ]
+        );
+
+        // One second later the next unit is out and the limited one is gone
+        data_manager.set_as_of(Some(LATER.parse().unwrap()));
+        assert_eq!(
+            data_manager.unit_ids().await.unwrap(),
+            vec![UNIT_ID, LATER_UNIT_ID]
+        );
+        data_manager.unit_cache(LATER_UNIT_ID).await.unwrap();
+        assert_eq!(data_manager.release_timeline().await.unwrap().len(), 3);
+
+        data_manager.set_as_of(None);
+        assert_eq!(data_manager.unit_ids().await.unwrap().len(), 3);
+    }
+}
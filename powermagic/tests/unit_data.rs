@@ -0,0 +1,116 @@
+mod common;
+
+use powermagic::error::{DataError, Error};
+use serde_json::json;
+
+use common::{data_manager, dump, insert, rows, Dump, UNIT_ID};
+
+/// Data error of the fixture unit, with and without preloading
+async fn unit_error(tables: &Dump) -> Vec<DataError> {
+    let mut errors = vec![];
+    for preload in [false, true] {
+        let mut data_manager = data_manager(tables).await;
+        if preload {
+            data_manager.preload().await.unwrap();
+        }
+        match data_manager.unit_data(UNIT_ID).await {
+            Err(Error::Data(error)) => errors.push(error),
+            result => panic!("{:?}", result.map(|unit| unit.unit_id)),
+        }
+    }
+
+    errors
+}
+
+fn rarity_6_slot(tables: &mut Dump, slot_id: i64) {
+    for slot in 1..=3 {
+        insert(
+            tables,
+            "unlock_rarity_6",
+            json!({"unit_id": UNIT_ID, "slot_id": slot, "unlock_level": 1}),
+        );
+    }
+    rows(tables, "unlock_rarity_6")[0]["slot_id"] = json!(slot_id);
+}
+
+#[tokio::test]
+async fn rarity_6_slot_out_of_range() {
+    for slot_id in [0, -1, 4] {
+        let mut tables = dump();
+        rarity_6_slot(&mut tables, slot_id);
+
+        for error in unit_error(&tables).await {
+            match error {
+                DataError::Rarity6SlotOutOfRange {
+                    unit_id: UNIT_ID,
+                    slot,
+                } if slot == slot_id => {}
+                error => panic!("{}: {:?}", slot_id, error),
+            }
+        }
+    }
+
+    let mut tables = dump();
+    rarity_6_slot(&mut tables, 1);
+    let unit = data_manager(&tables)
+        .await
+        .unit_data(UNIT_ID)
+        .await
+        .unwrap();
+    assert!(unit.unlock_rarity_6.is_some());
+}
+
+#[tokio::test]
+async fn promotion_rows_without_their_rank() {
+    // Status of a rank past the last one
+    let mut tables = dump();
+    insert(
+        &mut tables,
+        "unit_promotion_status",
+        json!({"unit_id": UNIT_ID, "promotion_level": 3, "hp": 60}),
+    );
+    for error in unit_error(&tables).await {
+        match error {
+            DataError::MissingPromotion {
+                unit_id: UNIT_ID,
+                rank: 3,
+            } => {}
+            error => panic!("{:?}", error),
+        }
+    }
+
+    // Bonus of rank 0
+    let mut tables = dump();
+    insert(
+        &mut tables,
+        "promotion_bonus",
+        json!({"unit_id": UNIT_ID, "promotion_level": 0, "hp": 10}),
+    );
+    for error in unit_error(&tables).await {
+        match error {
+            DataError::MissingPromotion {
+                unit_id: UNIT_ID,
+                rank: 0,
+            } => {}
+            error => panic!("{:?}", error),
+        }
+    }
+
+    // Rank 1 is missing, the status of rank 2 lands on rank 3
+    let mut tables = dump();
+    let promotion = rows(&mut tables, "unit_promotion");
+    let mut rank_3 = promotion[1].clone();
+    rank_3["promotion_level"] = json!(3);
+    promotion.push(rank_3);
+    promotion.retain(|row| row["promotion_level"] != 1);
+    for error in unit_error(&tables).await {
+        match error {
+            DataError::PromotionMismatch {
+                unit_id: UNIT_ID,
+                rank: 2,
+                found: 3,
+            } => {}
+            error => panic!("{:?}", error),
+        }
+    }
+}